@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use embedded_hal::i2c::I2c as _;
+use embedded_hal_bus::i2c::RefCellDevice;
+use panic_rtt_target as _;
+use rtt_target::{rprintln, rtt_init_print};
+use stm32f4xx_hal::{
+    i2c::{self, I2c},
+    pac,
+    prelude::*,
+};
+
+use lcd1602_driver::{
+    command::DataWidth,
+    lcd::{self, Basic, Ext, Lcd},
+    sender::I2cSender,
+};
+
+// a BMP280 sensor on the same I2C bus
+const BMP280_ADDR: u8 = 0x76;
+const BMP280_CHIP_ID_REG: u8 = 0xD0;
+
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    rtt_init_print!();
+
+    let dp = pac::Peripherals::take().expect("Cannot take device peripherals");
+    let cp = pac::CorePeripherals::take().expect("Cannot take core peripherals");
+
+    let rcc = dp.RCC.constrain();
+    let clocks = rcc.cfgr.use_hse(12.MHz()).freeze();
+
+    let mut delayer = cp.SYST.delay(&clocks);
+
+    let gpiob = dp.GPIOB.split();
+
+    let i2c = I2c::new(
+        dp.I2C1,
+        (gpiob.pb6, gpiob.pb7),
+        i2c::Mode::standard(100.kHz()), // The PCF8574T max I2C speed
+        &clocks,
+    );
+
+    // share one I2C bus between LCD1602 and the sensor
+    let i2c_bus = RefCell::new(i2c);
+
+    let mut sensor = RefCellDevice::new(&i2c_bus);
+
+    // I2cSender takes the device handle by value
+    let mut sender = I2cSender::new(RefCellDevice::new(&i2c_bus), 0x27);
+
+    let lcd_config = lcd::Config::default().set_data_width(DataWidth::Bit4);
+
+    let mut lcd = Lcd::new(&mut sender, &mut delayer, lcd_config, 10);
+
+    lcd.write_str_to_cur("chip id:");
+
+    // LCD and sensor can take turns to use the bus
+    let mut chip_id = [0u8];
+    sensor
+        .write_read(BMP280_ADDR, &[BMP280_CHIP_ID_REG], &mut chip_id)
+        .unwrap();
+
+    rprintln!("BMP280 chip id: {:#04x}", chip_id[0]);
+
+    lcd.set_cursor_pos((0, 1));
+    lcd.write_str_to_cur(if chip_id[0] == 0x58 { "BMP280" } else { "unknown" });
+
+    #[allow(clippy::empty_loop)]
+    loop {}
+}
@@ -0,0 +1,124 @@
+//! Character ROM of LCD1602, and how [`char`] maps to it
+
+/// [`CharRom`] is the character ROM inside LCD1602 controller
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum CharRom {
+    /// Japanese standard font, the most common ROM
+    #[default]
+    A00,
+    /// European standard font
+    A02,
+}
+
+/// The characters of A00 ROM, indexed by ROM byte, [`None`] if the position is a CGRAM graph,
+/// blank, or a glyph without an equivalent [`char`] (e.g. a `g` with descender)
+///
+/// Note:
+/// 0x5C is `¥` rather than `\`, 0xDF (the semi-voiced sound mark) is used as `°`
+#[rustfmt::skip]
+pub const A00_MAP: [Option<char>; 256] = [
+    // 0x00
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    // 0x10
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    // 0x20
+    Some(' '), Some('!'), Some('"'), Some('#'), Some('$'), Some('%'), Some('&'), Some('\''),
+    Some('('), Some(')'), Some('*'), Some('+'), Some(','), Some('-'), Some('.'), Some('/'),
+    // 0x30
+    Some('0'), Some('1'), Some('2'), Some('3'), Some('4'), Some('5'), Some('6'), Some('7'),
+    Some('8'), Some('9'), Some(':'), Some(';'), Some('<'), Some('='), Some('>'), Some('?'),
+    // 0x40
+    Some('@'), Some('A'), Some('B'), Some('C'), Some('D'), Some('E'), Some('F'), Some('G'),
+    Some('H'), Some('I'), Some('J'), Some('K'), Some('L'), Some('M'), Some('N'), Some('O'),
+    // 0x50
+    Some('P'), Some('Q'), Some('R'), Some('S'), Some('T'), Some('U'), Some('V'), Some('W'),
+    Some('X'), Some('Y'), Some('Z'), Some('['), Some('¥'), Some(']'), Some('^'), Some('_'),
+    // 0x60
+    Some('`'), Some('a'), Some('b'), Some('c'), Some('d'), Some('e'), Some('f'), Some('g'),
+    Some('h'), Some('i'), Some('j'), Some('k'), Some('l'), Some('m'), Some('n'), Some('o'),
+    // 0x70
+    Some('p'), Some('q'), Some('r'), Some('s'), Some('t'), Some('u'), Some('v'), Some('w'),
+    Some('x'), Some('y'), Some('z'), Some('{'), Some('|'), Some('}'), Some('→'), Some('←'),
+    // 0x80
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    // 0x90
+    None, None, None, None, None, None, None, None,
+    None, None, None, None, None, None, None, None,
+    // 0xA0
+    None, Some('｡'), Some('｢'), Some('｣'), Some('､'), Some('･'), Some('ｦ'), Some('ｧ'),
+    Some('ｨ'), Some('ｩ'), Some('ｪ'), Some('ｫ'), Some('ｬ'), Some('ｭ'), Some('ｮ'), Some('ｯ'),
+    // 0xB0
+    Some('ｰ'), Some('ｱ'), Some('ｲ'), Some('ｳ'), Some('ｴ'), Some('ｵ'), Some('ｶ'), Some('ｷ'),
+    Some('ｸ'), Some('ｹ'), Some('ｺ'), Some('ｻ'), Some('ｼ'), Some('ｽ'), Some('ｾ'), Some('ｿ'),
+    // 0xC0
+    Some('ﾀ'), Some('ﾁ'), Some('ﾂ'), Some('ﾃ'), Some('ﾄ'), Some('ﾅ'), Some('ﾆ'), Some('ﾇ'),
+    Some('ﾈ'), Some('ﾉ'), Some('ﾊ'), Some('ﾋ'), Some('ﾌ'), Some('ﾍ'), Some('ﾎ'), Some('ﾏ'),
+    // 0xD0
+    Some('ﾐ'), Some('ﾑ'), Some('ﾒ'), Some('ﾓ'), Some('ﾔ'), Some('ﾕ'), Some('ﾖ'), Some('ﾗ'),
+    Some('ﾘ'), Some('ﾙ'), Some('ﾚ'), Some('ﾛ'), Some('ﾜ'), Some('ﾝ'), Some('ﾞ'), Some('°'),
+    // 0xE0
+    Some('α'), Some('ä'), Some('β'), Some('ε'), Some('μ'), Some('σ'), Some('ρ'), None,
+    Some('√'), None, None, None, Some('¢'), Some('£'), Some('ñ'), Some('ö'),
+    // 0xF0
+    None, None, Some('θ'), Some('∞'), Some('Ω'), Some('ü'), Some('Σ'), Some('π'),
+    None, None, Some('千'), Some('万'), Some('円'), Some('÷'), None, Some('█'),
+];
+
+impl CharRom {
+    /// Map a [`char`] to the byte of the ROM, return [`None`] if the ROM doesn't have the character
+    pub fn map_char(&self, char: char) -> Option<u8> {
+        match self {
+            CharRom::A00 => match char {
+                // 0x20 to 0x7D of A00 is ASCII, except 0x5C
+                '\u{20}'..='\u{7D}' if char != '\\' => Some(char as u8),
+                // characters look the same as some in A00_MAP
+                'µ' => Some(0xE4),
+                'ﾟ' => Some(0xDF),
+                _ => A00_MAP
+                    .iter()
+                    .position(|&rom_char| rom_char == Some(char))
+                    .map(|byte| byte as u8),
+            },
+            // A02 is ASCII in lower half, and mostly follows ISO-8859-1 in upper half
+            CharRom::A02 => match char {
+                '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => Some(char as u8),
+                _ => None,
+            },
+        }
+    }
+
+    /// The byte to display when a character is not in the ROM
+    pub fn replacement_byte(&self) -> u8 {
+        match self {
+            // a full filled rectangle
+            CharRom::A00 => 0xFF,
+            CharRom::A02 => b'?',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CharRom, A00_MAP};
+
+    #[test]
+    fn known_a00_mappings() {
+        assert_eq!(A00_MAP[b'A' as usize], Some('A'));
+        assert_eq!(A00_MAP[0x5C], Some('¥'));
+        assert_eq!(A00_MAP[0xDF], Some('°'));
+        assert_eq!(A00_MAP[0xE4], Some('μ'));
+        assert_eq!(A00_MAP[0x00], None);
+
+        let a00 = CharRom::A00;
+        assert_eq!(a00.map_char('A'), Some(b'A'));
+        assert_eq!(a00.map_char('°'), Some(0xDF));
+        assert_eq!(a00.map_char('µ'), Some(0xE4));
+        assert_eq!(a00.map_char('Σ'), Some(0xF6));
+        assert_eq!(a00.map_char('Ω'), Some(0xF4));
+        assert_eq!(a00.map_char('\\'), None);
+        assert_eq!(a00.replacement_byte(), 0xFF);
+    }
+}
@@ -18,7 +18,16 @@ pub(crate) enum CommandSet {
     // It's the first (half) command of 4 pin mode
     // we name it, to make things tidy
     HalfFunctionSet,
+    // A 0b0011 nibble, 3 of them put the controller into 8 bit mode from any state,
+    // to recover from an unknown nibble phase of 4 pin mode
+    ResyncNibble,
     FunctionSet(DataWidth, LineMode, Font),
+    // Function Set with IS bit set, it switches AIP31068 / ST7032 alike controllers into extended instruction set
+    ExtendedFunctionSet(DataWidth, LineMode),
+    // low 4 bits of contrast, in extended instruction set
+    ContrastSet(u8),
+    // turn on booster, and set high 2 bits of contrast, in extended instruction set
+    PowerIconContrastSet(u8),
     SetCGRAM(u8),
     SetDDRAM(u8),
     ReadBusyFlagAndAddress,
@@ -26,8 +35,19 @@ pub(crate) enum CommandSet {
     ReadDataFromRAM,
 }
 
+impl CommandSet {
+    // the maximum execution time of a command from datasheet (in microseconds)
+    pub(crate) fn get_execution_time_us(&self) -> u32 {
+        match self {
+            CommandSet::ClearDisplay | CommandSet::ReturnHome => 1_520,
+            CommandSet::ReadBusyFlagAndAddress => 0,
+            _ => 37,
+        }
+    }
+}
+
 /// [`MoveDirection`] defines the cursor and display window move direction
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum MoveDirection {
     #[allow(missing_docs)]
     RightToLeft,
@@ -37,7 +57,7 @@ pub enum MoveDirection {
 }
 
 /// [`ShiftType`] defines the movement is cursor only or both cursor and display window
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum ShiftType {
     #[allow(missing_docs)]
     #[default]
@@ -47,7 +67,7 @@ pub enum ShiftType {
 }
 
 /// [`State`] defines a On/Off state
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum State {
     #[allow(missing_docs)]
     Off,
@@ -58,7 +78,7 @@ pub enum State {
 
 /// [`DataWidth`] defines data width of a [`Command`]  
 /// Should match current Sender's pin config
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
 pub enum DataWidth {
     #[allow(missing_docs)]
     #[default]
@@ -68,7 +88,7 @@ pub enum DataWidth {
 }
 
 /// [`LineMode`] is current LCD display line count
-#[derive(Clone, Copy, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
 pub enum LineMode {
     #[allow(missing_docs)]
     OneLine,
@@ -78,7 +98,7 @@ pub enum LineMode {
 }
 
 /// [`Font`] is current display font
-#[derive(Clone, Copy, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
 pub enum Font {
     #[allow(missing_docs)]
     #[default]
@@ -88,7 +108,7 @@ pub enum Font {
 }
 
 /// [`RAMType`] is the type of memory to access
-#[derive(Clone, Copy, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
 pub enum RAMType {
     /// Display Data RAM
     #[default]
@@ -98,6 +118,7 @@ pub enum RAMType {
 }
 
 /// A sender should parse a [`Command`] and send the data to hardware to write/read data to/from hardware.
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Command {
     rs: RegisterSelection,
     rw: ReadWriteOp,
@@ -106,7 +127,7 @@ pub struct Command {
 
 /// [`RegisterSelection`] defines LCD1602's register type that driver interact with.  
 /// A sender should change its "RS" pin state based on this variant.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum RegisterSelection {
     /// Access to Command register
     Command,
@@ -116,7 +137,7 @@ pub enum RegisterSelection {
 
 /// [`ReadWriteOp`] defines read/write operation that driver interact with.  
 /// A sender should change its "RW" pin state based on this variant.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ReadWriteOp {
     /// It's a write command
     Write,
@@ -126,7 +147,7 @@ pub enum ReadWriteOp {
 
 /// [`Bits`] defines *current command's* data width.  
 /// Most of the command should be 8 bit long, but **fisrt** command in [`DataWidth::Bit4`] mode is special, it requires 4 bit data.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Bits {
     /// Current command has 4 bit long data
     Bit4(u8),
@@ -144,7 +165,8 @@ impl Command {
         Self { rs, rw, data }
     }
 
-    pub(crate) fn get_register_selection(&self) -> RegisterSelection {
+    #[allow(missing_docs)]
+    pub fn get_register_selection(&self) -> RegisterSelection {
         self.rs
     }
 
@@ -152,7 +174,8 @@ impl Command {
         self.rs = rs
     }
 
-    pub(crate) fn get_read_write_op(&self) -> ReadWriteOp {
+    #[allow(missing_docs)]
+    pub fn get_read_write_op(&self) -> ReadWriteOp {
         self.rw
     }
 
@@ -160,7 +183,8 @@ impl Command {
         self.rw = rw
     }
 
-    pub(crate) fn get_data(&self) -> Option<Bits> {
+    #[allow(missing_docs)]
+    pub fn get_data(&self) -> Option<Bits> {
         self.data
     }
 
@@ -263,6 +287,12 @@ impl From<CommandSet> for Command {
                 Some(Bits::Bit4(0b0010)),
             ),
 
+            CommandSet::ResyncNibble => Self::new(
+                RegisterSelection::Command,
+                ReadWriteOp::Write,
+                Some(Bits::Bit4(0b0011)),
+            ),
+
             CommandSet::FunctionSet(width, line, font) => {
                 let mut raw_bits = 0b0010_0000;
 
@@ -288,6 +318,48 @@ impl From<CommandSet> for Command {
                 )
             }
 
+            CommandSet::ExtendedFunctionSet(width, line) => {
+                // IS bit is set, and DH (double height) bit is cleared
+                let mut raw_bits = 0b0010_0001;
+
+                match width {
+                    DataWidth::Bit4 => raw_bits.clear_bit(4),
+                    DataWidth::Bit8 => raw_bits.set_bit(4),
+                };
+
+                match line {
+                    LineMode::OneLine => raw_bits.clear_bit(3),
+                    LineMode::TwoLine => raw_bits.set_bit(3),
+                };
+
+                Self::new(
+                    RegisterSelection::Command,
+                    ReadWriteOp::Write,
+                    Some(Bits::Bit8(raw_bits)),
+                )
+            }
+
+            CommandSet::ContrastSet(contrast) => {
+                let raw_bits = 0b0111_0000 | (contrast & 0b1111);
+
+                Self::new(
+                    RegisterSelection::Command,
+                    ReadWriteOp::Write,
+                    Some(Bits::Bit8(raw_bits)),
+                )
+            }
+
+            CommandSet::PowerIconContrastSet(contrast) => {
+                // booster on, icon off
+                let raw_bits = 0b0101_0100 | ((contrast >> 4) & 0b11);
+
+                Self::new(
+                    RegisterSelection::Command,
+                    ReadWriteOp::Write,
+                    Some(Bits::Bit8(raw_bits)),
+                )
+            }
+
             CommandSet::SetCGRAM(addr) => {
                 let mut raw_bits = 0b0100_0000;
 
@@ -3,8 +3,10 @@
 use embedded_hal::delay::DelayNs;
 
 use crate::{
-    command::{Font, LineMode, MoveDirection, RAMType, ShiftType, State},
+    charset::CharRom,
+    command::{Font, LineMode, MoveDirection, RAMType, RegisterSelection, ShiftType, State},
     state::LcdState,
+    widgets::Marquee,
 };
 
 mod init;
@@ -13,6 +15,51 @@ pub use init::Config;
 
 mod impls;
 
+mod fallible;
+
+pub(crate) mod graph;
+
+mod cgram_allocator;
+
+pub use cgram_allocator::CgramAllocator;
+
+mod line_buffered;
+
+#[cfg(feature = "demo")]
+mod demo;
+
+pub use line_buffered::LineBufferedLcd;
+
+#[cfg(feature = "liquid-crystal-compat")]
+mod compat;
+
+#[cfg(feature = "liquid-crystal-compat")]
+pub use compat::LiquidCrystalCompat;
+
+#[cfg(feature = "async")]
+mod asynch;
+
+#[cfg(feature = "async")]
+pub use asynch::LcdAsync;
+
+#[cfg(feature = "heapless")]
+mod queue;
+
+#[cfg(feature = "heapless")]
+pub use queue::{CommandQueue, QueuedCommand};
+
+#[cfg(feature = "heapless")]
+mod terminal;
+
+#[cfg(feature = "heapless")]
+pub use terminal::TerminalView;
+
+#[cfg(feature = "critical-section")]
+mod shared;
+
+#[cfg(feature = "critical-section")]
+pub use shared::SharedLcd;
+
 /// [`Lcd`] is the main struct to drive a LCD1602
 pub struct Lcd<'a, 'b, Sender, Delayer>
 where
@@ -22,6 +69,166 @@ where
     delayer: &'b mut Delayer,
     state: LcdState,
     poll_interval_us: u32,
+    timing_mode: TimingMode,
+    resync_nibbles_on_init: bool,
+    busy_fallback_after_us: Option<u32>,
+    busy_fallback_triggered: bool,
+    cgram_allocator: CgramAllocator,
+    pending_modes: Option<PendingModes>,
+    clear_trailing: bool,
+    force_mode_commands: bool,
+    preserve_direction_on_clear: bool,
+    batch_data_writes: bool,
+    extended_instruction_set: bool,
+    // a copy of DDRAM, indexed by DDRAM address
+    shadow: Option<[u8; 0x80]>,
+}
+
+// mode commands that are built from state
+#[derive(Clone, Copy)]
+enum ModeCommand {
+    FunctionSet,
+    DisplayOnOff,
+    EntryModeSet,
+}
+
+// mode commands need to be sent when a batch is committed
+#[derive(Default)]
+struct PendingModes {
+    function_set: bool,
+    display_on_off: bool,
+    entry_mode_set: bool,
+}
+
+/// [`TimingMode`] defines how the driver waits for LCD to finish a command
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum TimingMode {
+    /// Poll the busy flag until LCD is idle, it requires RW pin to be wired
+    #[default]
+    BusyFlag,
+    /// Wait for a fixed worst-case duration from datasheet after each command
+    /// (37 µs for most commands, 1.52 ms for clear display and return home),
+    /// use this mode when RW pin is tied to ground
+    FixedDelay,
+}
+
+/// [`Timings`] is the timing configuration of a [`Lcd`], see [`Basic::timings()`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Timings {
+    /// How the driver waits for LCD to finish a command
+    pub timing_mode: TimingMode,
+    /// The interval (in microseconds) between busy flag polls
+    pub poll_interval_us: u32,
+    /// See [`Config::set_busy_fallback_after_us()`]
+    pub busy_fallback_after_us: Option<u32>,
+    /// The total deliberate delay (in microseconds) of hardware init, same as the one returned by [`Lcd::new_timed()`]
+    pub init_delay_us: u32,
+    /// The worst case execution time (in microseconds) of clear display
+    pub clear_display_us: u32,
+    /// The worst case execution time (in microseconds) of return home
+    pub return_home_us: u32,
+    /// The worst case execution time (in microseconds) of other commands
+    pub command_us: u32,
+}
+
+// on/off cycles of a dimmed backlight toggled by software, see [`Basic::set_backlight_level()`]
+const DIM_BACKLIGHT_CYCLES: u32 = 50;
+
+// the duration (in microseconds) of each half of a software dimming cycle
+const DIM_BACKLIGHT_HALF_PERIOD_US: u32 = 200;
+
+/// [`BacklightLevel`] is the brightness level of backlight
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BacklightLevel {
+    #[allow(missing_docs)]
+    Off,
+    /// Half brightness, only for RGB (PWM) backlight, see [`Lcd::set_backlight_level_rgb()`]
+    Dim,
+    #[allow(missing_docs)]
+    On,
+}
+
+#[cfg(feature = "test-util")]
+impl<'a, 'b, Sender, Delayer> Lcd<'a, 'b, Sender, Delayer>
+where
+    Delayer: DelayNs,
+{
+    /// Get the LCD1602 state simulated by the driver, for debugging and testing
+    pub fn get_state(&self) -> &LcdState {
+        &self.state
+    }
+}
+
+/// [`DisplayMode`] is the combination of display control and entry mode
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DisplayMode {
+    /// Display on/off
+    pub display: State,
+    /// Cursor on/off
+    pub cursor: State,
+    /// Cursor blink on/off
+    pub blink: State,
+    /// Cursor move direction after write
+    pub direction: MoveDirection,
+    /// Shift cursor only or both cursor and display after write
+    pub shift_type: ShiftType,
+}
+
+/// [`Pos`] is a position on LCD, methods taking a position accept both [`Pos`] and a `(col, row)` tuple
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Pos {
+    #[allow(missing_docs)]
+    pub col: u8,
+    #[allow(missing_docs)]
+    pub row: u8,
+}
+
+impl Pos {
+    /// Create a [`Pos`]
+    pub const fn new(col: u8, row: u8) -> Self {
+        Self { col, row }
+    }
+}
+
+impl From<(u8, u8)> for Pos {
+    fn from((col, row): (u8, u8)) -> Self {
+        Self { col, row }
+    }
+}
+
+impl From<Pos> for (u8, u8) {
+    fn from(pos: Pos) -> Self {
+        (pos.col, pos.row)
+    }
+}
+
+/// [`GlyphSlot`] is a valid index (0 to 7) of custom graph in CGRAM
+///
+/// A `u8` can be converted into [`GlyphSlot`] with [`From`], which panics if the index is out of range,
+/// use [`GlyphSlot::new()`] to check a dynamic index
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GlyphSlot(u8);
+
+impl GlyphSlot {
+    /// Create a [`GlyphSlot`], [`None`] if `index` is not less than 8
+    pub const fn new(index: u8) -> Option<Self> {
+        if index < 8 {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+
+    /// Get the index of the slot
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<u8> for GlyphSlot {
+    fn from(index: u8) -> Self {
+        Self::new(index).expect("Only 8 graphs allowed in CGRAM")
+    }
 }
 
 /// All basic command to control LCD1602
@@ -31,14 +238,82 @@ pub trait Basic {
 
     fn write_u8_to_cur(&mut self, byte: u8);
 
-    fn write_graph_to_cgram(&mut self, index: u8, graph_data: &[u8; 8]);
+    /// Write bytes to current position one after another, like calling [`Basic::write_u8_to_cur()`] for each byte
+    ///
+    /// With [`Config::set_batch_data_writes()`] enabled and a sender can batch (see [`SendCommand::can_batch()`](crate::sender::SendCommand::can_batch)),
+    /// consecutive bytes in a row are sent with [`SendCommand::send_batch()`](crate::sender::SendCommand::send_batch)
+    fn write_bytes_to_cur(&mut self, bytes: &[u8]);
+
+    /// Get the bytes written to a row of DDRAM, from a copy in MCU's memory,
+    /// it's empty if [`Config::set_shadow_contents()`] is not enabled
+    ///
+    /// Note:
+    /// It's the whole row in DDRAM (e.g. 40 bytes in two line mode), not only the display window
+    fn shadow_row(&self, row: u8) -> &[u8];
+
+    fn write_graph_to_cgram(&mut self, index: impl Into<GlyphSlot>, graph_data: &[u8; 8]);
+
+    /// Upload a custom graph 11 rows tall into CGRAM, with [`Font::Font5x11`] only
+    ///
+    /// Note:
+    /// With 5x8 font, a graph takes 8 bytes of CGRAM, and CGRAM holds 8 graphs;
+    /// with 5x11 font, a graph takes 16 bytes of CGRAM (11 rows used, the last row is cursor line), and CGRAM holds 4 graphs.
+    /// Thus `index` is 0 to 3, and CGRAM address of the graph is `index * 16`,
+    /// the graph is shown with character code `index * 2` (e.g. `write_graph_to_cur(index * 2)`).
+    fn write_graph_to_cgram_5x11(&mut self, index: u8, graph_data: &[u8; 11]);
+
+    /// Upload a custom graph into CGRAM, then read it back to check whether it's correctly written
+    ///
+    /// Note:
+    /// LCD should be readable (RW pin connected, and not in [`TimingMode::FixedDelay`])
+    fn write_graph_to_cgram_verified(
+        &mut self,
+        index: impl Into<GlyphSlot>,
+        graph_data: &[u8; 8],
+    ) -> Result<(), VerifyError>;
+
+    fn write_graph_to_cur(&mut self, index: impl Into<GlyphSlot>);
 
-    fn write_graph_to_cur(&mut self, index: u8);
+    /// Get the custom graph uploaded by the driver,
+    /// [`None`] if the slot is not defined since init
+    fn get_cgram_graph(&self, index: u8) -> Option<[u8; 8]>;
 
+    /// Fill CGRAM with empty graphs, mark all slots as not defined, and free all slots of [`Lcd::write_graph_alloc()`]
+    fn clear_cgram(&mut self);
+
+    /// Write one row of a custom graph in CGRAM
+    fn write_cgram_row(&mut self, index: u8, row: u8, line_data: u8);
+
+    /// Clean the display, cursor moves to home, and display window moves back to the start of line
+    ///
+    /// Note:
+    /// LCD also resets direction to [`MoveDirection::LeftToRight`] on cleaning,
+    /// set [`Config::set_preserve_direction_on_clear()`] to restore the direction afterward
     fn clean_display(&mut self);
 
+    /// Move cursor to home, and display window back to the start of line
     fn return_home(&mut self);
 
+    /// Move cursor to (0, 0), and display window back to the start of line, like [`Basic::return_home()`],
+    /// but with a Set DDRAM Address command and display shifts (the shorter way), rather than Return Home command
+    ///
+    /// Note:
+    /// Return Home takes up to 1.52 ms, while each command here takes 37 µs,
+    /// with at most half of line capacity shifts, it's faster, especially when display window is near its origin
+    fn reset_view(&mut self) {
+        self.set_cursor_pos((0, 0));
+
+        let offset = self.get_display_offset();
+        let line_capacity = self.get_line_capacity();
+        let (dir, steps) = match offset <= line_capacity / 2 {
+            // display content moved right `offset` times, move it back to the left
+            true => (MoveDirection::RightToLeft, offset),
+            false => (MoveDirection::LeftToRight, line_capacity - offset),
+        };
+
+        (0..steps).for_each(|_| self.shift_cursor_or_display(ShiftType::CursorAndDisplay, dir));
+    }
+
     fn set_line_mode(&mut self, line: LineMode);
 
     fn get_line_mode(&self) -> LineMode;
@@ -47,6 +322,54 @@ pub trait Basic {
 
     fn get_font(&self) -> Font;
 
+    /// Get how many custom graphs can be stored in CGRAM with current font,
+    /// 8 for [`Font::Font5x8`], 4 for [`Font::Font5x11`] (a graph takes 2 slots, only even indices are valid)
+    fn max_custom_glyphs(&self) -> u8 {
+        match self.get_font() {
+            Font::Font5x8 => 8,
+            Font::Font5x11 => 4,
+        }
+    }
+
+    /// Get the character ROM of the LCD
+    fn get_char_rom(&self) -> CharRom;
+
+    /// Find characters of `str` that are not in current character ROM,
+    /// they will be displayed as [`CharRom::replacement_byte()`]
+    ///
+    /// Return the count of unsupported characters, only first `out.len()` of them will be filled into `out`
+    fn unsupported_chars(&self, str: &str, out: &mut [char]) -> usize {
+        let char_rom = self.get_char_rom();
+
+        str.chars()
+            .filter(|&char| char_rom.map_char(char).is_none())
+            .fold(0, |count, char| {
+                if let Some(slot) = out.get_mut(count) {
+                    *slot = char;
+                }
+                count + 1
+            })
+    }
+
+    /// Get the bytes that `str` will be written as with `char_rom`, without touching LCD,
+    /// unsupported characters are converted to [`CharRom::replacement_byte()`]
+    ///
+    /// Return the count of bytes, only first `out.len()` of them will be filled into `out`
+    fn preview_bytes(&self, str: &str, char_rom: CharRom, out: &mut [u8]) -> usize {
+        str.chars()
+            .map(|char| {
+                char_rom
+                    .map_char(char)
+                    .unwrap_or(char_rom.replacement_byte())
+            })
+            .fold(0, |count, byte| {
+                if let Some(slot) = out.get_mut(count) {
+                    *slot = byte;
+                }
+                count + 1
+            })
+    }
+
     fn set_display_state(&mut self, display: State);
 
     fn get_display_state(&self) -> State;
@@ -57,6 +380,16 @@ pub trait Basic {
 
     fn get_ram_type(&self) -> RAMType;
 
+    /// Check if current RAM is DDRAM
+    fn is_in_ddram(&self) -> bool {
+        self.get_ram_type() == RAMType::DDRam
+    }
+
+    /// Check if current RAM is CGRAM
+    fn is_in_cgram(&self) -> bool {
+        self.get_ram_type() == RAMType::CGRam
+    }
+
     fn set_cursor_blink_state(&mut self, blink: State);
 
     fn get_cursor_blink_state(&self) -> State;
@@ -69,30 +402,137 @@ pub trait Basic {
 
     fn get_shift_type(&self) -> ShiftType;
 
-    fn set_cursor_pos(&mut self, pos: (u8, u8));
+    /// Get display control and entry mode in one value
+    fn display_mode(&self) -> DisplayMode {
+        DisplayMode {
+            display: self.get_display_state(),
+            cursor: self.get_cursor_state(),
+            blink: self.get_cursor_blink_state(),
+            direction: self.get_direction(),
+            shift_type: self.get_shift_type(),
+        }
+    }
+
+    /// Write from left to right, and shift display content left on each write,
+    /// thus cursor stays at the same place of the display window, and new text pushes old text left
+    fn enable_autoscroll(&mut self);
+
+    /// Stop shifting display content on each write, keep current direction
+    fn disable_autoscroll(&mut self);
+
+    fn set_cursor_pos(&mut self, pos: impl Into<Pos>);
 
     fn set_cgram_addr(&mut self, addr: u8);
 
+    /// Set Address Counter to a raw address of DDRAM or CGRAM,
+    /// for DDRAM, cursor position will be derived from the address
+    fn set_ac(&mut self, addr: u8, ram: RAMType);
+
     fn get_cursor_pos(&self) -> (u8, u8);
 
     fn shift_cursor_or_display(&mut self, shift_type: ShiftType, dir: MoveDirection);
 
     fn get_display_offset(&self) -> u8;
 
+    /// Convert a position in display window to a position in DDRAM, with respect to display offset
+    ///
+    /// Display offset increases when display content shifts right,
+    /// thus the left edge of display window is at DDRAM column `(line capacity - offset) % line capacity`
+    fn visible_to_ddram(&self, visible_col: u8, row: u8) -> (u8, u8) {
+        let line_capacity = self.get_line_capacity() as u16;
+        let ddram_col =
+            (line_capacity - self.get_display_offset() as u16 + visible_col as u16) % line_capacity;
+        (ddram_col as u8, row)
+    }
+
+    /// Convert a position in DDRAM to a position in display window, with respect to display offset,
+    /// [`None`] if the position is out of display window
+    fn ddram_to_visible(&self, col: u8, row: u8) -> Option<(u8, u8)> {
+        let line_capacity = self.get_line_capacity() as u16;
+        let visible_col = (col as u16 + self.get_display_offset() as u16) % line_capacity;
+        (visible_col < self.get_columns() as u16).then_some((visible_col as u8, row))
+    }
+
+    /// Start a batch, mode setters (line mode, font, display control and entry mode) only update state,
+    /// the commands will be sent in [`Basic::commit_batch()`]
+    fn begin_batch(&mut self);
+
+    /// Finish a batch, send each changed mode command once
+    fn commit_batch(&mut self);
+
+    /// Send Function Set, Display On/Off Control and Entry Mode Set built from current state,
+    /// even if the modes are unchanged, e.g. to recover mode registers of LCD after a glitch,
+    /// it's cheaper than [`Lcd::reinit()`], and doesn't clean the display
+    fn resync_modes(&mut self);
+
+    /// Get how the driver waits for LCD to finish a command
+    fn get_timing_mode(&self) -> TimingMode;
+
+    /// Get the timing configuration in one value
+    fn timings(&self) -> Timings;
+
+    /// Whether LCD can be read, it's false if the sender cannot read,
+    /// or in [`TimingMode::FixedDelay`] (RW pin is supposed to be tied to ground)
+    ///
+    /// Note:
+    /// Read methods (e.g. [`Basic::read_u8_from_cur()`]) panic if LCD cannot be read
+    fn can_read(&self) -> bool;
+
+    /// Whether LCD is ready to accept a command now
+    ///
+    /// In [`TimingMode::BusyFlag`] the busy flag is read once (without waiting),
+    /// otherwise the driver already waited for the last command, thus it's always ready
+    fn is_ready(&mut self) -> bool;
+
     fn set_poll_interval(&mut self, interval_us: u32);
 
+    /// Whether [`Ext::write_str_to_pos()`] fills the rest of the row in display window with white space,
+    /// see [`Config::set_clear_trailing()`]
+    fn set_clear_trailing(&mut self, clear: bool);
+
+    fn get_clear_trailing(&self) -> bool;
+
     fn get_poll_interval_us(&self) -> u32;
 
     fn get_line_capacity(&self) -> u8;
 
+    /// Get the DDRAM columns a row can address, it's the line capacity,
+    /// unless each row takes a part of a line (e.g. a 4 rows display, see [`Config::set_row_layout()`])
+    fn get_row_capacity(&self, row: u8) -> u8;
+
+    /// Get the visible column count of the display window
+    fn get_columns(&self) -> u8;
+
+    /// Get the physical row count of the display, it's not [`LineMode`] of the controller
+    fn get_rows(&self) -> u8;
+
     /// Note:
     /// Due to driver implementation, this function may have actual effect, or not
     fn set_backlight(&mut self, backlight: State);
 
-    fn get_backlight(self) -> State;
+    fn get_backlight(&self) -> State;
+
+    /// Set backlight to [`BacklightLevel`]
+    ///
+    /// [`BacklightLevel::Dim`] is passed to [`SendCommand::dim_backlight()`](crate::sender::SendCommand::dim_backlight),
+    /// e.g. [`PwmBacklight`](crate::sender::PwmBacklight) sets half duty.
+    ///
+    /// Note:
+    /// If the backlight of sender can only be on or off, [`BacklightLevel::Dim`] toggles backlight rapidly in a blocking loop
+    /// (50 on/off cycles of 400 µs, 20 ms in total), then leaves backlight on.
+    /// It's a compromise, backlight only looks dimmed during the loop, call it repeatedly to keep backlight dimmed.
+    fn set_backlight_level(&mut self, level: BacklightLevel);
 
     fn calculate_pos_by_offset(&self, start: (u8, u8), offset: (i8, i8)) -> (u8, u8);
 
+    /// Send raw bytes as write commands, each `(register, byte, delay_us)` is sent,
+    /// then wait for `delay_us`, e.g. for a custom init sequence of an exotic controller
+    ///
+    /// Note:
+    /// Busy flag is not checked, and the state of the driver is not updated,
+    /// thus the state may no longer match LCD after the sequence
+    fn run_raw_sequence(&mut self, steps: &[(RegisterSelection, u8, u32)]);
+
     /// Wait for specified milliseconds
     fn delay_ms(&mut self, ms: u32);
 
@@ -111,37 +551,53 @@ pub trait Ext: Basic {
     }
 
     /// write [char] to current position
-    /// In default implementation, character only support
-    /// from ASCII 0x20 (white space) to ASCII 0x7D (`}`)
+    /// In default implementation, character will be mapped by current [`CharRom`],
+    /// character not in the ROM will be displayed as [`CharRom::replacement_byte()`]
     fn write_char_to_cur(&mut self, char: char) {
         assert!(
             self.get_ram_type() == RAMType::DDRam,
             "Current in CGRAM, use .set_cursor_pos() to change to DDRAM"
         );
 
-        // map char out side of ASCII 0x20 and 0x7D to full rectangle
-        let out_byte = match char.is_ascii() {
-            true if (0x20 <= char as u8) && (char as u8 <= 0x7D) => char as u8,
-            _ => 0xFF,
-        };
+        let char_rom = self.get_char_rom();
+        let out_byte = char_rom
+            .map_char(char)
+            .unwrap_or(char_rom.replacement_byte());
 
         self.write_u8_to_cur(out_byte);
     }
 
     /// write string to current position
     fn write_str_to_cur(&mut self, str: &str) {
-        str.chars().for_each(|char| self.write_char_to_cur(char));
+        let char_rom = self.get_char_rom();
+
+        // map characters in chunks, thus a run of bytes can be sent in batch
+        let mut chars = str.chars().peekable();
+        while chars.peek().is_some() {
+            let mut buf = [0u8; 16];
+            let mut len = 0;
+            chars.by_ref().take(buf.len()).for_each(|char| {
+                buf[len] = char_rom
+                    .map_char(char)
+                    .unwrap_or(char_rom.replacement_byte());
+                len += 1;
+            });
+
+            self.write_bytes_to_cur(&buf[..len]);
+        }
     }
 
     /// write a byte to specific position
-    fn write_byte_to_pos(&mut self, byte: u8, pos: (u8, u8)) {
+    fn write_byte_to_pos(&mut self, byte: u8, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
         self.set_cursor_pos(pos);
 
         self.write_u8_to_cur(byte);
     }
 
     /// read a byte from specific position
-    fn read_byte_from_pos(&mut self, pos: (u8, u8)) -> u8 {
+    fn read_byte_from_pos(&mut self, pos: impl Into<Pos>) -> u8 {
+        let pos: (u8, u8) = pos.into().into();
         let original_pos = self.get_cursor_pos();
         self.set_cursor_pos(pos);
         let data = self.read_u8_from_cur();
@@ -149,27 +605,312 @@ pub trait Ext: Basic {
         data
     }
 
+    /// read a rectangular region into `out` row by row, e.g. to save the background of a popup,
+    /// cursor will be restored after reading
+    ///
+    /// Note:
+    /// LCD should be readable (RW pin connected, and not in [`TimingMode::FixedDelay`])
+    fn read_region(&mut self, top_left: impl Into<Pos>, width: u8, height: u8, out: &mut [u8]) {
+        let top_left: (u8, u8) = top_left.into().into();
+        assert_region(self, top_left, width, height);
+        assert!(
+            out.len() >= width as usize * height as usize,
+            "out buffer is too small for the region"
+        );
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let original_pos = self.get_cursor_pos();
+
+        // AC moves along current direction after each read, so start from the end that AC moves away from
+        let direction = self.get_direction();
+        out.chunks_mut(width as usize)
+            .take(height as usize)
+            .zip(top_left.1..)
+            .for_each(|(row_buf, row)| match direction {
+                MoveDirection::LeftToRight => {
+                    self.set_cursor_pos((top_left.0, row));
+                    row_buf
+                        .iter_mut()
+                        .for_each(|byte| *byte = self.read_u8_from_cur());
+                }
+                MoveDirection::RightToLeft => {
+                    self.set_cursor_pos((top_left.0 + width - 1, row));
+                    row_buf
+                        .iter_mut()
+                        .rev()
+                        .for_each(|byte| *byte = self.read_u8_from_cur());
+                }
+            });
+
+        self.set_cursor_pos(original_pos);
+    }
+
+    /// read a rectangular region, and compare it with `expected` row by row,
+    /// e.g. to detect a display garbled by noise, and re-init it;
+    /// return the position of the first mismatched cell, cursor will be restored after reading
+    ///
+    /// Note:
+    /// LCD should be readable (RW pin connected, and not in [`TimingMode::FixedDelay`])
+    fn verify_region(
+        &mut self,
+        top_left: impl Into<Pos>,
+        width: u8,
+        height: u8,
+        expected: &[u8],
+    ) -> Result<(), (u8, u8)> {
+        let top_left: (u8, u8) = top_left.into().into();
+        assert!(self.can_read(), "LCD cannot be read");
+        assert!(
+            expected.len() >= width as usize * height as usize,
+            "expected buffer is too small for the region"
+        );
+
+        if width == 0 {
+            return Ok(());
+        }
+
+        // a row never exceeds 80 cells (1 line display)
+        let mut row_buf = [0u8; 80];
+
+        expected
+            .chunks(width as usize)
+            .take(height as usize)
+            .zip(top_left.1..)
+            .try_for_each(|(expected_row, row)| {
+                self.read_region((top_left.0, row), width, 1, &mut row_buf);
+
+                match row_buf
+                    .iter()
+                    .zip(expected_row)
+                    .position(|(read, expected)| read != expected)
+                {
+                    Some(offset) => Err((top_left.0 + offset as u8, row)),
+                    None => Ok(()),
+                }
+            })
+    }
+
+    /// fill the display window on `line` with white space, and keep cursor position,
+    /// it's faster than [`Basic::clean_display()`] and doesn't touch the other rows
+    fn clear_line(&mut self, line: u8) {
+        assert!(line < self.get_rows(), "line is out of rows");
+
+        let columns = self.get_columns();
+        let line_capacity = self.get_line_capacity();
+        let (start_col, _) = self.visible_to_ddram(0, line);
+
+        // display window may wrap around the end of line in DDRAM
+        let first_width = columns.min(line_capacity - start_col);
+        self.clear_region((start_col, line), first_width, 1);
+        if first_width < columns {
+            self.clear_region((0, line), columns - first_width, 1);
+        }
+    }
+
+    /// fill a rectangular region with white space, and keep cursor position
+    fn clear_region(&mut self, top_left: impl Into<Pos>, width: u8, height: u8) {
+        let top_left: (u8, u8) = top_left.into().into();
+        assert_region(self, top_left, width, height);
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let original_pos = self.get_cursor_pos();
+
+        // AC moves along current direction after each write, so start from the end that AC moves away from
+        let start_col = match self.get_direction() {
+            MoveDirection::LeftToRight => top_left.0,
+            MoveDirection::RightToLeft => top_left.0 + width - 1,
+        };
+
+        (top_left.1..top_left.1 + height).for_each(|row| {
+            self.set_cursor_pos((start_col, row));
+            (0..width).for_each(|_| self.write_u8_to_cur(b' '));
+        });
+
+        self.set_cursor_pos(original_pos);
+    }
+
+    /// write bytes verbatim into a rectangular region row by row,
+    /// e.g. to restore the background saved by [`Ext::read_region()`]
+    fn write_region(&mut self, top_left: impl Into<Pos>, width: u8, height: u8, data: &[u8]) {
+        let top_left: (u8, u8) = top_left.into().into();
+        assert_region(self, top_left, width, height);
+        assert!(
+            data.len() >= width as usize * height as usize,
+            "data is too short for the region"
+        );
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        // AC moves along current direction after each write, so start from the end that AC moves away from
+        let direction = self.get_direction();
+        data.chunks(width as usize)
+            .take(height as usize)
+            .zip(top_left.1..)
+            .for_each(|(row_data, row)| match direction {
+                MoveDirection::LeftToRight => {
+                    self.set_cursor_pos((top_left.0, row));
+                    row_data.iter().for_each(|&byte| self.write_u8_to_cur(byte));
+                }
+                MoveDirection::RightToLeft => {
+                    self.set_cursor_pos((top_left.0 + width - 1, row));
+                    row_data
+                        .iter()
+                        .rev()
+                        .for_each(|&byte| self.write_u8_to_cur(byte));
+                }
+            });
+    }
+
     /// write a char to specific position
-    fn write_char_to_pos(&mut self, char: char, pos: (u8, u8)) {
+    fn write_char_to_pos(&mut self, char: char, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
         self.set_cursor_pos(pos);
         self.write_char_to_cur(char);
     }
 
-    /// write string to specific position
-    fn write_str_to_pos(&mut self, str: &str, pos: (u8, u8)) {
+    /// write string to specific position,
+    /// if clear trailing is enabled (see [`Basic::set_clear_trailing()`]),
+    /// the rest of the row in display window is filled with white space
+    fn write_str_to_pos(&mut self, str: &str, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
+        match self.get_clear_trailing() {
+            true => self.write_str_to_pos_clear(str, pos, self.get_columns()),
+            false => {
+                self.set_cursor_pos(pos);
+                self.write_str_to_cur(str);
+            }
+        }
+    }
+
+    /// write string to specific position, then fill the field with white space until `end_col` (exclusive),
+    /// thus a shorter string won't leave characters of a longer one behind
+    fn write_str_to_pos_clear(&mut self, str: &str, pos: impl Into<Pos>, end_col: u8) {
+        let pos: (u8, u8) = pos.into().into();
+        assert!(pos.1 < self.get_rows(), "row is out of display");
+        assert!(
+            end_col <= self.get_row_capacity(pos.1),
+            "end column is out of row capacity"
+        );
+
         self.set_cursor_pos(pos);
         self.write_str_to_cur(str);
+
+        let str_end_col = pos.0 as u16 + str.chars().count() as u16;
+        (str_end_col..end_col as u16).for_each(|_| self.write_u8_to_cur(b' '));
+    }
+
+    /// write each line of `str` (split by `'\n'`) from `start` of display window, one row per line,
+    /// lines are truncated at the right edge of display window, and lines beyond the last row are dropped
+    fn write_multiline(&mut self, str: &str, start: impl Into<Pos>) {
+        let start: (u8, u8) = start.into().into();
+        str.split('\n')
+            .zip(start.1..self.get_rows())
+            .for_each(|(line, row)| {
+                write_visible_str(self, line, start.0, row, &|_| 1);
+            });
+    }
+
+    /// write text from current cursor position, and wrap to the start of next row after `columns` characters,
+    /// the last row wraps to the first row
+    ///
+    /// Text is broken at white spaces if possible, a word longer than `columns` is broken anywhere,
+    /// white spaces at the start of a row are dropped
+    fn write_str_wrapped(&mut self, text: &str, columns: u8) {
+        assert!(
+            0 < columns && columns <= self.get_line_capacity(),
+            "columns should be 1 to line capacity"
+        );
+
+        let (mut col, mut row) = self.get_cursor_pos();
+        if col >= columns {
+            (col, row) = (0, wrap_to_next_row(self, row));
+        }
+
+        text.split(' ').enumerate().for_each(|(index, word)| {
+            // the white space between words
+            if index > 0 && col > 0 {
+                self.write_u8_to_cur(b' ');
+                col += 1;
+                if col == columns {
+                    (col, row) = (0, wrap_to_next_row(self, row));
+                }
+            }
+
+            // move the whole word to next row, if it fits in a row
+            let word_len = word.chars().count();
+            if col > 0 && word_len <= columns as usize && col as usize + word_len > columns as usize
+            {
+                (col, row) = (0, wrap_to_next_row(self, row));
+            }
+
+            word.chars().for_each(|char| {
+                self.write_char_to_cur(char);
+                col += 1;
+                if col == columns {
+                    (col, row) = (0, wrap_to_next_row(self, row));
+                }
+            });
+        });
+    }
+
+    /// write string into a field `width` cells wide at specific position,
+    /// the string is truncated to `width`, and the rest of the field is filled with white space,
+    /// thus the field always shows exactly the string
+    fn write_field(&mut self, str: &str, pos: impl Into<Pos>, width: u8) {
+        let pos: (u8, u8) = pos.into().into();
+        assert_region(self, pos, width, 1);
+
+        self.set_cursor_pos(pos);
+
+        let mut written = 0;
+        str.chars().take(width as usize).for_each(|char| {
+            self.write_char_to_cur(char);
+            written += 1;
+        });
+
+        (written..width).for_each(|_| self.write_u8_to_cur(b' '));
+    }
+
+    /// write a char to specific position of display window, with respect to display offset
+    fn write_char_at_visible(&mut self, char: char, visible_pos: impl Into<Pos>) {
+        let visible_pos: (u8, u8) = visible_pos.into().into();
+        let pos = self.visible_to_ddram(visible_pos.0, visible_pos.1);
+        self.write_char_to_pos(char, pos);
+    }
+
+    /// write string to specific position of display window, with respect to display offset,
+    /// characters beyond the line capacity are dropped
+    fn write_str_at_visible(&mut self, str: &str, visible_pos: impl Into<Pos>) {
+        let visible_pos: (u8, u8) = visible_pos.into().into();
+        let max_chars = self.get_line_capacity().saturating_sub(visible_pos.0);
+
+        str.chars()
+            .zip(visible_pos.0..)
+            .take(max_chars as usize)
+            .for_each(|(char, visible_col)| {
+                self.write_char_at_visible(char, (visible_col, visible_pos.1))
+            });
     }
 
     /// write custom graph to specific position
-    fn write_graph_to_pos(&mut self, index: u8, pos: (u8, u8)) {
-        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
-        self.write_byte_to_pos(index, pos);
+    fn write_graph_to_pos(&mut self, index: impl Into<GlyphSlot>, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
+        self.set_cursor_pos(pos);
+        self.write_graph_to_cur(index);
     }
 
     /// read custom graph data from CGRAM
     fn read_graph_from_cgram(&mut self, index: u8) -> [u8; 8] {
         assert!(index < 8, "index too big, should less than 8");
+        assert!(self.can_read(), "LCD cannot be read");
 
         // convert index to cgram address
         self.set_cgram_addr(index.checked_shl(3).unwrap());
@@ -183,33 +924,606 @@ pub trait Ext: Basic {
         graph
     }
 
-    /// change cursor position with relative offset
-    fn offset_cursor_pos(&mut self, offset: (i8, i8)) {
-        self.set_cursor_pos(self.calculate_pos_by_offset(self.get_cursor_pos(), offset));
+    /// Upload a custom graph into CGRAM, if the graph is already uploaded, the upload is skipped
+    ///
+    /// Return the slot index of the graph, or [`None`] if all slots are used by other graphs
+    fn define_glyph_deduped(&mut self, graph_data: &[u8; 8]) -> Option<u8> {
+        // with 5x11 font, only even indices are valid
+        let slot_step = (8 / self.max_custom_glyphs()) as usize;
+
+        if let Some(index) = (0..8)
+            .step_by(slot_step)
+            .find(|&index| self.get_cgram_graph(index) == Some(*graph_data))
+        {
+            return Some(index);
+        }
+
+        let index = (0..8)
+            .step_by(slot_step)
+            .find(|&index| self.get_cgram_graph(index).is_none())?;
+
+        // uploading CGRAM will move AC into CGRAM, move it back
+        let cursor_pos = self.is_in_ddram().then(|| self.get_cursor_pos());
+        self.write_graph_to_cgram(index, graph_data);
+        if let Some(cursor_pos) = cursor_pos {
+            self.set_cursor_pos(cursor_pos);
+        }
+
+        Some(index)
     }
-}
 
-/// The style of the offset display window
-pub enum MoveStyle {
-    /// Always move to left
-    ForceMoveLeft,
-    /// Always move to right
-    ForceMoveRight,
-    /// Top left of display window won't cross display boundary
-    NoCrossBoundary,
-    /// Automatic find the shortest path
-    Shortest,
-}
+    /// write `value` of `base_unit` at `pos`, scaled with a SI prefix (k, M or G), e.g. 1200 Pa is written as "1.2 kPa"
+    ///
+    /// Up to 3 significant digits are kept (e.g. "1.23 kPa", "12.3 kPa", "123 kPa"), the rest digits are truncated,
+    /// and trailing zeros of decimals are dropped (e.g. "1 kPa" rather than "1.00 kPa").
+    /// Value less than 1000 is written as is (e.g. "0 Pa", "999 Pa").
+    fn write_scaled_si(&mut self, value: u32, base_unit: &str, pos: impl Into<Pos>) {
+        use core::fmt::Write;
 
-/// The flip style of split flap display
-pub enum FlipStyle {
-    /// Flip first character to target character, then flip next one
-    Sequential,
-    /// Flip all characters at once, automatically stop when the characters reach the target one
-    Simultaneous,
-}
+        let pos: (u8, u8) = pos.into().into();
 
-/// Show animation on LCD1602
+        const PREFIXES: [(u32, char); 3] = [(1_000_000_000, 'G'), (1_000_000, 'M'), (1_000, 'k')];
+
+        self.set_cursor_pos(pos);
+
+        let mut writer = ExtWriter(self);
+
+        let Some(&(scale, prefix)) = PREFIXES.iter().find(|&&(scale, _)| value >= scale) else {
+            write!(writer, "{} {}", value, base_unit).unwrap();
+            return;
+        };
+
+        let integer = value / scale;
+        let mut decimals: u32 = match integer {
+            0..=9 => 2,
+            10..=99 => 1,
+            _ => 0,
+        };
+        let mut fraction = (value % scale) / (scale / 10u32.pow(decimals));
+
+        // `u32::is_multiple_of()` needs Rust 1.87
+        #[allow(clippy::manual_is_multiple_of)]
+        while decimals > 0 && fraction % 10 == 0 {
+            fraction /= 10;
+            decimals -= 1;
+        }
+
+        match decimals {
+            0 => write!(writer, "{} {}{}", integer, prefix, base_unit),
+            _ => write!(
+                writer,
+                "{}.{:0width$} {}{}",
+                integer,
+                fraction,
+                prefix,
+                base_unit,
+                width = decimals as usize
+            ),
+        }
+        .unwrap();
+    }
+
+    /// write a number from current cursor position
+    fn write_u32(&mut self, value: u32) {
+        let (digits, digit_cnt) = u32_digits(value);
+        digits[..digit_cnt]
+            .iter()
+            .rev()
+            .for_each(|&digit| self.write_u8_to_cur(digit));
+    }
+
+    /// write a number from current cursor position, with a `-` if it's negative
+    fn write_i32(&mut self, value: i32) {
+        if value < 0 {
+            self.write_u8_to_cur(b'-');
+        }
+        // unsigned_abs() won't overflow on i32::MIN
+        self.write_u32(value.unsigned_abs());
+    }
+
+    /// write a fixed point number from current cursor position, the last `decimals` digits of `value` are decimals,
+    /// e.g. `write_fixed(-1234, 2)` writes "-12.34", and `write_fixed(5, 2)` writes "0.05"
+    fn write_fixed(&mut self, value: i32, decimals: u8) {
+        assert!(decimals <= 9, "decimals should not bigger than 9");
+
+        let (digits, digit_cnt) = u32_digits(value.unsigned_abs());
+        // keep a zero before decimal point, and zeros after decimal point
+        let digit_cnt = digit_cnt.max(decimals as usize + 1);
+
+        if value < 0 {
+            self.write_u8_to_cur(b'-');
+        }
+
+        digits[..digit_cnt]
+            .iter()
+            .enumerate()
+            .rev()
+            .for_each(|(index, &digit)| {
+                self.write_u8_to_cur(digit);
+                if decimals > 0 && index == decimals as usize {
+                    self.write_u8_to_cur(b'.');
+                }
+            });
+    }
+
+    /// write a number, with its last digit at `pos` (e.g. an odometer),
+    /// the number reads correctly in both [`MoveDirection`]
+    fn write_u32_rtl(&mut self, n: u32, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
+        let (digits, digit_cnt) = u32_digits(n);
+
+        match self.get_direction() {
+            // AC decreases after each write, write from the last digit
+            MoveDirection::RightToLeft => {
+                self.set_cursor_pos(pos);
+                digits[..digit_cnt]
+                    .iter()
+                    .for_each(|&digit| self.write_u8_to_cur(digit));
+            }
+            // AC increases after each write, write from the first digit
+            MoveDirection::LeftToRight => {
+                self.set_cursor_pos(self.calculate_pos_by_offset(pos, (1 - digit_cnt as i8, 0)));
+                digits[..digit_cnt]
+                    .iter()
+                    .rev()
+                    .for_each(|&digit| self.write_u8_to_cur(digit));
+            }
+        }
+    }
+
+    /// Run a self test for troubleshooting, it will:
+    ///
+    /// 1. clean the display, and write a full row of blocks on the first row
+    /// 2. turn backlight off and on
+    /// 3. write a sample of character ROM on the second row
+    /// 4. if LCD can be read (not in [`TimingMode::FixedDelay`], and [`Basic::can_read()`]), read back a written byte
+    ///
+    /// The display will be cleaned and cursor will be moved to (0, 0) after the test
+    fn self_test(&mut self) -> SelfTestResult {
+        let columns = self.get_columns();
+
+        self.clean_display();
+        self.set_cursor_pos((0, 0));
+        (0..columns).for_each(|_| self.write_u8_to_cur(SELF_TEST_BLOCK));
+        self.delay_ms(SELF_TEST_STEP_DELAY_MS);
+
+        let backlight = self.get_backlight();
+        self.set_backlight(State::Off);
+        self.delay_ms(SELF_TEST_STEP_DELAY_MS);
+        self.set_backlight(State::On);
+        self.delay_ms(SELF_TEST_STEP_DELAY_MS);
+        self.set_backlight(backlight);
+
+        if self.get_line_mode() == LineMode::TwoLine {
+            self.set_cursor_pos((0, 1));
+            (0..columns).for_each(|offset| self.write_u8_to_cur(b' ' + offset));
+            self.delay_ms(SELF_TEST_STEP_DELAY_MS);
+        }
+
+        let result = match (self.get_timing_mode(), self.can_read()) {
+            (TimingMode::BusyFlag, true) => match self.read_byte_from_pos((0, 0)) {
+                SELF_TEST_BLOCK => SelfTestResult::Passed,
+                actual => SelfTestResult::ReadMismatch {
+                    expected: SELF_TEST_BLOCK,
+                    actual,
+                },
+            },
+            _ => SelfTestResult::ReadSkipped,
+        };
+
+        self.clean_display();
+        self.set_cursor_pos((0, 0));
+
+        result
+    }
+
+    /// write string at the center of display window on `row`
+    fn write_str_centered(&mut self, str: &str, row: u8) {
+        self.write_str_centered_with_width(str, row, |_| 1);
+    }
+
+    /// write string at the center of display window on `row`,
+    /// with `char_width` to tell how many cells a character takes
+    ///
+    /// Note:
+    /// A character wider than 1 cell only writes its first cell,
+    /// the rest cells are skipped (e.g. to be drawn as the rest part of a wide icon)
+    fn write_str_centered_with_width(
+        &mut self,
+        str: &str,
+        row: u8,
+        char_width: impl Fn(char) -> u8,
+    ) {
+        let str_width = str_width(str, &char_width);
+        let start_col = (self.get_columns() as u16).saturating_sub(str_width) / 2;
+        write_visible_str(self, str, start_col as u8, row, &char_width);
+    }
+
+    /// write `title` at the center of the first row, and `subtitle` at the center of the second row,
+    /// the rest cells of the 2 rows in display window are filled with white space, e.g. for a splash screen
+    ///
+    /// Note:
+    /// On a 1 row display, `subtitle` is dropped
+    fn write_title(&mut self, title: &str, subtitle: &str) {
+        let columns = self.get_columns();
+
+        [title, subtitle]
+            .iter()
+            .zip(0..self.get_rows())
+            .for_each(|(&str, row)| {
+                let start_col = (columns as u16).saturating_sub(str.chars().count() as u16) / 2;
+
+                (0..start_col as u8).for_each(|visible_col| {
+                    let pos = self.visible_to_ddram(visible_col, row);
+                    self.write_char_to_pos(' ', pos);
+                });
+
+                let end_col = write_visible_str(self, str, start_col as u8, row, &|_| 1);
+
+                (end_col..columns).for_each(|visible_col| {
+                    let pos = self.visible_to_ddram(visible_col, row);
+                    self.write_char_to_pos(' ', pos);
+                });
+            });
+    }
+
+    /// write `key` at the left edge and `value` at the right edge of display window on `row`,
+    /// and fill the cells between them with white space
+    fn write_kv(&mut self, key: &str, value: &str, row: u8) {
+        self.write_kv_with_width(key, value, row, |_| 1);
+    }
+
+    /// write `key` at the left edge and `value` at the right edge of display window on `row`,
+    /// and fill the cells between them with white space,
+    /// with `char_width` to tell how many cells a character takes
+    ///
+    /// Note:
+    /// A character wider than 1 cell only writes its first cell,
+    /// the rest cells are skipped (e.g. to be drawn as the rest part of a wide icon)
+    fn write_kv_with_width(
+        &mut self,
+        key: &str,
+        value: &str,
+        row: u8,
+        char_width: impl Fn(char) -> u8,
+    ) {
+        let columns = self.get_columns();
+
+        let key_end_col = write_visible_str(self, key, 0, row, &char_width);
+
+        let value_start_col = (columns as u16).saturating_sub(str_width(value, &char_width)) as u8;
+        let value_start_col = value_start_col.max(key_end_col);
+
+        (key_end_col..value_start_col).for_each(|visible_col| {
+            let pos = self.visible_to_ddram(visible_col, row);
+            self.write_char_to_pos(' ', pos);
+        });
+
+        write_visible_str(self, value, value_start_col, row, &char_width);
+    }
+
+    /// upload 5 graphs with 1 to 5 pixel columns filled, into CGRAM slot `base` to `base + 4`,
+    /// they are used by [`Ext::draw_horizontal_bar()`]
+    fn upload_horizontal_bar_glyphs(&mut self, base: u8) {
+        graph::upload_glyphs(
+            self,
+            base,
+            (1..=graph::CELL_COLUMNS).map(graph::column_fill_graph),
+        );
+    }
+
+    /// draw a bar `width_cells` long to the right of `start_pos`, filled in proportion of `value` to `max`,
+    /// the partial filled cell is rounded to the nearest pixel column, and cells after the bar are cleared,
+    /// `value` larger than `max` is treated as `max`
+    ///
+    /// Note:
+    /// Graphs should be uploaded by [`Ext::upload_horizontal_bar_glyphs()`] first
+    fn draw_horizontal_bar(
+        &mut self,
+        value: u8,
+        max: u8,
+        start_pos: impl Into<Pos>,
+        width_cells: u8,
+    ) {
+        let start_pos: (u8, u8) = start_pos.into().into();
+        assert_region(self, start_pos, width_cells, 1);
+
+        let filled =
+            graph::scale_to_steps(value, max, width_cells as u16 * graph::CELL_COLUMNS as u16);
+
+        self.set_cursor_pos(start_pos);
+
+        (0..width_cells).for_each(|cell| {
+            match graph::cell_fill(filled, cell, graph::CELL_COLUMNS) {
+                0 => self.write_u8_to_cur(b' '),
+                columns => {
+                    let index = graph::find_glyph(self, graph::column_fill_graph(columns));
+                    self.write_graph_to_cur(index);
+                }
+            }
+        });
+    }
+
+    /// upload 8 graphs with 1 to 8 bottom pixel rows filled, into CGRAM slot 0 to 7 (the whole CGRAM),
+    /// they are used by [`Ext::draw_vertical_bar()`]
+    fn upload_vertical_bar_glyphs(&mut self) {
+        graph::upload_glyphs(self, 0, (1..=graph::CELL_ROWS).map(graph::row_fill_graph));
+    }
+
+    /// draw a bar `height_cells` tall upward from `bottom_pos`, filled in proportion of `value` to `max`,
+    /// the partial filled cell is rounded to the nearest pixel row, and cells above the bar are cleared,
+    /// `value` larger than `max` is treated as `max`
+    ///
+    /// Note:
+    /// Graphs should be uploaded by [`Ext::upload_vertical_bar_glyphs()`] first
+    fn draw_vertical_bar(
+        &mut self,
+        value: u8,
+        max: u8,
+        bottom_pos: impl Into<Pos>,
+        height_cells: u8,
+    ) {
+        let bottom_pos: (u8, u8) = bottom_pos.into().into();
+        assert!(height_cells <= bottom_pos.1 + 1, "bar is out of display");
+
+        let filled =
+            graph::scale_to_steps(value, max, height_cells as u16 * graph::CELL_ROWS as u16);
+
+        (0..height_cells).for_each(|cell| {
+            let pos = (bottom_pos.0, bottom_pos.1 - cell);
+            match graph::cell_fill(filled, cell, graph::CELL_ROWS) {
+                0 => self.write_char_to_pos(' ', pos),
+                rows => {
+                    let index = graph::find_glyph(self, graph::row_fill_graph(rows));
+                    self.set_cursor_pos(pos);
+                    self.write_graph_to_cur(index);
+                }
+            }
+        });
+    }
+
+    /// draw a digit 3 columns wide and 2 rows tall, with its top left cell at `top_left`
+    ///
+    /// Note:
+    /// It's built from 8 custom graphs, which take the whole CGRAM (slot 0 to 7),
+    /// they are uploaded when they are not in CGRAM (e.g. at first call), and 5x11 font is not supported
+    fn draw_big_digit(&mut self, digit: u8, top_left: impl Into<Pos>) {
+        let top_left: (u8, u8) = top_left.into().into();
+        assert!(digit < 10, "digit should be 0 to 9");
+        assert_region(self, top_left, 3, 2);
+
+        let uploaded = graph::BIG_DIGIT_GLYPHS
+            .iter()
+            .enumerate()
+            .all(|(index, &glyph)| self.get_cgram_graph(index as u8) == Some(glyph));
+        if !uploaded {
+            graph::upload_glyphs(self, 0, graph::BIG_DIGIT_GLYPHS.into_iter());
+        }
+
+        graph::BIG_DIGITS[digit as usize]
+            .iter()
+            .enumerate()
+            .for_each(|(row_offset, cells)| {
+                self.set_cursor_pos((top_left.0, top_left.1 + row_offset as u8));
+                cells.iter().for_each(|&cell| match cell {
+                    graph::BigDigitCell::Glyph(index) => self.write_graph_to_cur(index),
+                    graph::BigDigitCell::Byte(byte) => self.write_u8_to_cur(byte),
+                });
+            });
+    }
+
+    /// draw `value` with big digits (see [`Ext::draw_big_digit()`]) from left to right,
+    /// digits are separated by an empty column, thus each digit takes 4 columns
+    fn draw_big_number(&mut self, value: u16, top_left: impl Into<Pos>) {
+        let top_left: (u8, u8) = top_left.into().into();
+        let mut digits = [0u8; 5];
+        let digit_cnt = (0..digits.len())
+            .find(|&cnt| value / 10u16.pow(cnt as u32) < 10)
+            .unwrap()
+            + 1;
+        (0..digit_cnt).for_each(|index| {
+            digits[digit_cnt - 1 - index] = (value / 10u16.pow(index as u32) % 10) as u8;
+        });
+
+        assert_region(self, top_left, digit_cnt as u8 * 4 - 1, 2);
+
+        digits[..digit_cnt]
+            .iter()
+            .enumerate()
+            .for_each(|(index, &digit)| {
+                let col = top_left.0 + index as u8 * 4;
+                self.draw_big_digit(digit, (col, top_left.1));
+
+                if index + 1 < digit_cnt {
+                    self.write_byte_to_pos(b' ', (col + 3, top_left.1));
+                    self.write_byte_to_pos(b' ', (col + 3, top_left.1 + 1));
+                }
+            });
+    }
+
+    /// change cursor position with relative offset
+    fn offset_cursor_pos(&mut self, offset: (i8, i8)) {
+        self.set_cursor_pos(self.calculate_pos_by_offset(self.get_cursor_pos(), offset));
+    }
+}
+
+// the byte written and read back in self test, a full filled rectangle
+const SELF_TEST_BLOCK: u8 = 0xFF;
+
+// how long each step of self test holds, for human to check
+const SELF_TEST_STEP_DELAY_MS: u32 = 500;
+
+/// The result of [`Ext::self_test()`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SelfTestResult {
+    /// All tests passed
+    Passed,
+    /// Write tests are done, read test is skipped, since LCD cannot be read in [`TimingMode::FixedDelay`],
+    /// or the sender cannot read
+    ReadSkipped,
+    /// The byte read back doesn't match the byte written
+    ReadMismatch {
+        /// The byte written
+        expected: u8,
+        /// The byte read back
+        actual: u8,
+    },
+}
+
+/// The first mismatched row found by [`Basic::write_graph_to_cgram_verified()`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VerifyError {
+    /// The row index in the graph
+    pub row: u8,
+    /// The row data written
+    pub expected: u8,
+    /// The row data read back
+    pub actual: u8,
+}
+
+/// [`LcdError`] is the error returned by `try_*` methods of [`Lcd`],
+/// `E` is the error of the sender (see [`TrySendCommand`](crate::sender::TrySendCommand))
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LcdError<E> {
+    /// The sender failed to access hardware
+    Sender(E),
+}
+
+impl<E> From<E> for LcdError<E> {
+    fn from(error: E) -> Self {
+        Self::Sender(error)
+    }
+}
+
+// check a rectangular region is in the display, each row of it should fit in the row capacity
+fn assert_region<L: Basic + ?Sized>(lcd: &L, top_left: (u8, u8), width: u8, height: u8) {
+    assert!(
+        top_left.1 as u16 + height as u16 <= lcd.get_rows() as u16,
+        "region is out of rows"
+    );
+    assert!(
+        (top_left.1..top_left.1 + height)
+            .all(|row| top_left.0 as u16 + width as u16 <= lcd.get_row_capacity(row) as u16),
+        "region is out of row capacity"
+    );
+}
+
+// adapts a LCD into core::fmt::Write, to format values without allocation
+struct ExtWriter<'l, L: Ext + ?Sized>(&'l mut L);
+
+impl<L: Ext + ?Sized> core::fmt::Write for ExtWriter<'_, L> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.write_str_to_cur(s);
+        Ok(())
+    }
+}
+
+// move cursor to the start of the row after `row`, wrap to the first row after the last one,
+// return the new row
+fn wrap_to_next_row<L: Ext + ?Sized>(lcd: &mut L, row: u8) -> u8 {
+    let next_row = (row + 1) % lcd.get_rows();
+    lcd.set_cursor_pos((0, next_row));
+    next_row
+}
+
+// ASCII digits of a number, stored from the least significant one, and the count of digits,
+// unused digits are '0'
+fn u32_digits(n: u32) -> ([u8; 10], usize) {
+    // u32::MAX has 10 digits
+    let mut digits = [b'0'; 10];
+    let mut digit_cnt = 0;
+    let mut rest = n;
+
+    loop {
+        digits[digit_cnt] = b'0' + (rest % 10) as u8;
+        digit_cnt += 1;
+        rest /= 10;
+        if rest == 0 {
+            break;
+        }
+    }
+
+    (digits, digit_cnt)
+}
+
+// total cells a string takes
+fn str_width(str: &str, char_width: &impl Fn(char) -> u8) -> u16 {
+    str.chars().map(|char| char_width(char) as u16).sum()
+}
+
+// write string from `start_col` of display window, until the string ends or reaches the right edge,
+// return the column after the last written character
+fn write_visible_str<L: Ext + ?Sized>(
+    lcd: &mut L,
+    str: &str,
+    start_col: u8,
+    row: u8,
+    char_width: &impl Fn(char) -> u8,
+) -> u8 {
+    let columns = lcd.get_columns();
+
+    str.chars()
+        .try_fold(start_col, |visible_col, char| {
+            let next_col = visible_col.checked_add(char_width(char))?;
+            if next_col > columns {
+                return None;
+            }
+
+            let pos = lcd.visible_to_ddram(visible_col, row);
+            lcd.write_char_to_pos(char, pos);
+
+            Some(next_col)
+        })
+        .unwrap_or(columns)
+}
+
+// write the visible part of the string, with its first character at visible column `start`,
+// and clear the cell the string just left
+fn slide_step<L: Ext + ?Sized>(lcd: &mut L, str: &str, row: u8, start: i16, dir: MoveDirection) {
+    let columns = lcd.get_columns() as i16;
+
+    str.chars()
+        .enumerate()
+        .map(|(index, char)| (start + index as i16, char))
+        .filter(|&(visible_col, _)| (0..columns).contains(&visible_col))
+        .for_each(|(visible_col, char)| {
+            lcd.write_char_to_pos(char, lcd.visible_to_ddram(visible_col as u8, row))
+        });
+
+    let trailing_col = match dir {
+        MoveDirection::RightToLeft => start + str.chars().count() as i16,
+        MoveDirection::LeftToRight => start - 1,
+    };
+
+    if (0..columns).contains(&trailing_col) {
+        lcd.write_char_to_pos(' ', lcd.visible_to_ddram(trailing_col as u8, row));
+    }
+}
+
+// the period (in microseconds) of each on/off cycle of display fading
+const DISPLAY_FADE_PERIOD_US: u32 = 10_000;
+
+/// The style of the offset display window
+pub enum MoveStyle {
+    /// Always move to left
+    ForceMoveLeft,
+    /// Always move to right
+    ForceMoveRight,
+    /// Top left of display window won't cross display boundary
+    NoCrossBoundary,
+    /// Automatic find the shortest path
+    Shortest,
+}
+
+/// The flip style of split flap display
+pub enum FlipStyle {
+    /// Flip first character to target character, then flip next one
+    Sequential,
+    /// Flip all characters at once, automatically stop when the characters reach the target one
+    Simultaneous,
+}
+
+/// Show animation on LCD1602
 pub trait Anim: Ext {
     /// Make the entire screen blink
     ///
@@ -218,20 +1532,124 @@ pub trait Anim: Ext {
     /// * `count` - the number of times to blink the screen. If the value is `0`, the screen will blink endless.
     /// * `interval_us` - The interval (in microseconds) at which the screen state changes
     fn full_display_blink(&mut self, count: u32, interval_us: u32) {
+        self.full_display_blink_with_yield(count, interval_us, || {});
+    }
+
+    /// Make the entire screen blink, see [`Anim::full_display_blink()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - the number of times to blink the screen. If the value is `0`, the screen will blink endless.
+    /// * `interval_us` - The interval (in microseconds) at which the screen state changes
+    /// * `yield_fn` - called after each visible step, e.g. to feed a watchdog or poll a scheduler
+    fn full_display_blink_with_yield(
+        &mut self,
+        count: u32,
+        interval_us: u32,
+        mut yield_fn: impl FnMut(),
+    ) {
         match count == 0 {
             true => loop {
                 self.delay_us(interval_us);
                 self.toggle_display();
+                yield_fn();
             },
             false => {
                 (0..count * 2).for_each(|_| {
                     self.delay_us(interval_us);
                     self.toggle_display();
+                    yield_fn();
                 });
             }
         }
     }
 
+    /// Fade the display on or off, by toggling display on and off with changing duty cycle
+    ///
+    /// Note:
+    /// It relies on persistence of vision, and it doesn't change backlight
+    ///
+    /// # Arguments
+    ///
+    /// * `to` - the display state after fading
+    /// * `duration_us` - The duration (in microseconds) of the fading
+    fn display_fade(&mut self, to: State, duration_us: u32) {
+        if self.get_display_state() != to {
+            let period_cnt = duration_us / DISPLAY_FADE_PERIOD_US;
+
+            (0..period_cnt).for_each(|period| {
+                let on_time_us = match to {
+                    State::On => DISPLAY_FADE_PERIOD_US * period / period_cnt,
+                    State::Off => DISPLAY_FADE_PERIOD_US * (period_cnt - period) / period_cnt,
+                };
+
+                self.set_display_state(State::On);
+                self.delay_us(on_time_us);
+                self.set_display_state(State::Off);
+                self.delay_us(DISPLAY_FADE_PERIOD_US - on_time_us);
+            });
+        }
+
+        self.set_display_state(to);
+    }
+
+    /// Place a custom graph at `pos`, then fill the graph from bottom to top, one row per frame
+    ///
+    /// Note:
+    /// The graph in CGRAM slot `index` is the first frame, usually it should be an empty graph
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - the CGRAM slot of the graph
+    /// * `pos` - the position to place the graph
+    /// * `frame_delay_us` - The interval (in microseconds) of each frame
+    fn fill_cell(&mut self, index: u8, pos: impl Into<Pos>, frame_delay_us: u32) {
+        let pos: (u8, u8) = pos.into().into();
+        self.write_graph_to_pos(index, pos);
+        let cursor_pos = self.get_cursor_pos();
+
+        (0..8).rev().for_each(|row| {
+            self.delay_us(frame_delay_us);
+            self.write_cgram_row(index, row, 0b11111);
+        });
+
+        // writing CGRAM will move AC into CGRAM, move it back
+        self.set_cursor_pos(cursor_pos);
+    }
+
+    /// Blink a cell between its content and `cursor_byte`, as a software cursor at any position,
+    /// the content will be restored at the end
+    ///
+    /// Note:
+    /// The content of the cell is read from LCD, it requires LCD to be readable
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - the position of the cell
+    /// * `cursor_byte` - the byte to show as cursor, e.g. `b'_'`
+    /// * `count` - the number of times to blink the cursor
+    /// * `interval_us` - The interval (in microseconds) at which the cell changes
+    fn software_cursor_blink(
+        &mut self,
+        pos: impl Into<Pos>,
+        cursor_byte: u8,
+        count: u32,
+        interval_us: u32,
+    ) {
+        let pos: (u8, u8) = pos.into().into();
+        let cursor_pos = self.get_cursor_pos();
+        let original_byte = self.read_byte_from_pos(pos);
+
+        (0..count).for_each(|_| {
+            self.write_byte_to_pos(cursor_byte, pos);
+            self.delay_us(interval_us);
+            self.write_byte_to_pos(original_byte, pos);
+            self.delay_us(interval_us);
+        });
+
+        self.set_cursor_pos(cursor_pos);
+    }
+
     /// Typewriter-style display
     ///
     /// # Arguments
@@ -239,12 +1657,256 @@ pub trait Anim: Ext {
     /// * `str` - string to display
     /// * `delay_us` - The interval (in microseconds) of each character show up
     fn typewriter_write(&mut self, str: &str, delay_us: u32) {
+        self.typewriter_write_with_yield(str, delay_us, || {});
+    }
+
+    /// Typewriter-style display, see [`Anim::typewriter_write()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `delay_us` - The interval (in microseconds) of each character show up
+    /// * `yield_fn` - called after each visible step, e.g. to feed a watchdog or poll a scheduler
+    fn typewriter_write_with_yield(
+        &mut self,
+        str: &str,
+        delay_us: u32,
+        mut yield_fn: impl FnMut(),
+    ) {
         str.chars().for_each(|char| {
             self.delay_us(delay_us);
             self.write_char_to_cur(char);
+            yield_fn();
         })
     }
 
+    /// Reverse of [`Anim::typewriter_write()`], erase the `len` cells before cursor one by one,
+    /// cursor stays at the last erased cell
+    ///
+    /// "Before" is against current direction, and it wraps into the previous row,
+    /// erasing stops at the home position (the upper-left corner, or the lower-right corner in [`MoveDirection::RightToLeft`])
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - the count of cells to erase
+    /// * `delay_us` - The interval (in microseconds) of each character disappear
+    fn typewriter_erase(&mut self, len: usize, delay_us: u32) {
+        let (step, home) = match self.get_direction() {
+            MoveDirection::LeftToRight => (-1, (0, 0)),
+            MoveDirection::RightToLeft => (1, self.calculate_pos_by_offset((0, 0), (-1, 0))),
+        };
+
+        let mut pos = self.get_cursor_pos();
+        for _ in 0..len {
+            if pos == home {
+                break;
+            }
+
+            pos = self.calculate_pos_by_offset(pos, (step, 0));
+            self.delay_us(delay_us);
+            self.write_byte_to_pos(b' ', pos);
+        }
+
+        self.set_cursor_pos(pos);
+    }
+
+    /// Slide a string into the display window from the right edge,
+    /// it will settle at the left edge of the display window
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `row` - the row to display the string
+    /// * `delay_us` - The interval (in microseconds) of each step
+    fn slide_in_from_right(&mut self, str: &str, row: u8, delay_us: u32) {
+        self.slide_in_from_right_with_yield(str, row, delay_us, || {});
+    }
+
+    /// Slide a string into the display window from the right edge, see [`Anim::slide_in_from_right()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `row` - the row to display the string
+    /// * `delay_us` - The interval (in microseconds) of each step
+    /// * `yield_fn` - called after each visible step, e.g. to feed a watchdog or poll a scheduler
+    fn slide_in_from_right_with_yield(
+        &mut self,
+        str: &str,
+        row: u8,
+        delay_us: u32,
+        mut yield_fn: impl FnMut(),
+    ) {
+        let columns = self.get_columns() as i16;
+
+        // string start from the last visible column, and move 1 column left per step
+        (0..columns).for_each(|step| {
+            self.delay_us(delay_us);
+            slide_step(
+                self,
+                str,
+                row,
+                columns - 1 - step,
+                MoveDirection::RightToLeft,
+            );
+            yield_fn();
+        });
+    }
+
+    /// Slide a string into the display window from the left edge,
+    /// it will settle at the right edge of the display window
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `row` - the row to display the string
+    /// * `delay_us` - The interval (in microseconds) of each step
+    fn slide_in_from_left(&mut self, str: &str, row: u8, delay_us: u32) {
+        self.slide_in_from_left_with_yield(str, row, delay_us, || {});
+    }
+
+    /// Slide a string into the display window from the left edge, see [`Anim::slide_in_from_left()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `row` - the row to display the string
+    /// * `delay_us` - The interval (in microseconds) of each step
+    /// * `yield_fn` - called after each visible step, e.g. to feed a watchdog or poll a scheduler
+    fn slide_in_from_left_with_yield(
+        &mut self,
+        str: &str,
+        row: u8,
+        delay_us: u32,
+        mut yield_fn: impl FnMut(),
+    ) {
+        let columns = self.get_columns() as i16;
+        let str_len = str.chars().count() as i16;
+
+        // string end at the first visible column, and move 1 column right per step
+        (0..columns).for_each(|step| {
+            self.delay_us(delay_us);
+            slide_step(
+                self,
+                str,
+                row,
+                1 - str_len + step,
+                MoveDirection::LeftToRight,
+            );
+            yield_fn();
+        });
+    }
+
+    /// Scroll a string through a row once, in a fixed total duration
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `row` - the row to display the string
+    /// * `total_duration_us` - The total duration (in microseconds) of the scrolling.
+    ///   If the string is too long, each step will still wait [`MARQUEE_MIN_STEP_DELAY_US`](crate::widgets::MARQUEE_MIN_STEP_DELAY_US),
+    ///   and the scrolling will overrun the duration.
+    fn marquee_for_duration(&mut self, str: &str, row: u8, total_duration_us: u32) {
+        let mut marquee = Marquee::new(str, row);
+        let step_delay_us = marquee.step_delay_for_duration(total_duration_us);
+
+        (0..marquee.steps_per_loop()).for_each(|_| {
+            marquee.tick(self);
+            self.delay_us(step_delay_us);
+        });
+    }
+
+    /// Scroll a string through a row continuously, by shifting the display window 1 cell per step
+    ///
+    /// The string is written into DDRAM of the row, starting at the left edge of display window.
+    /// A string not longer than line capacity loops through the whole line (padded with white space),
+    /// a longer string loops with a 1 cell gap, and the cells are rewritten just before they show up.
+    ///
+    /// Cursor position and display offset are restored afterward.
+    ///
+    /// Note:
+    /// Shifting display window moves all rows
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - string to display
+    /// * `row` - the row to display the string
+    /// * `step_delay_us` - The delay (in microseconds) of each step
+    /// * `loops` - the number of times to scroll the string. If the value is `0`, the string will scroll endless.
+    fn marquee(&mut self, text: &str, row: u8, step_delay_us: u32, loops: u32) {
+        self.marquee_with_yield(text, row, step_delay_us, loops, || {});
+    }
+
+    /// Scroll a string through a row continuously, see [`Anim::marquee()`]
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - string to display
+    /// * `row` - the row to display the string
+    /// * `step_delay_us` - The delay (in microseconds) of each step
+    /// * `loops` - the number of times to scroll the string. If the value is `0`, the string will scroll endless.
+    /// * `yield_fn` - called after each visible step, e.g. to feed a watchdog or poll a scheduler
+    fn marquee_with_yield(
+        &mut self,
+        text: &str,
+        row: u8,
+        step_delay_us: u32,
+        loops: u32,
+        mut yield_fn: impl FnMut(),
+    ) {
+        let line_capacity = self.get_line_capacity() as u32;
+        let columns = self.get_columns() as u32;
+        let text_len = text.chars().count() as u32;
+
+        // the count of steps of a loop, a string exactly as long as line capacity still loops through the whole line
+        let period = if text_len <= line_capacity {
+            line_capacity
+        } else {
+            text_len + 1
+        };
+
+        let char_at = |index: u32| text.chars().nth((index % period) as usize).unwrap_or(' ');
+
+        let cursor_pos = self.is_in_ddram().then(|| self.get_cursor_pos());
+        let display_offset = self.get_display_offset();
+
+        // writing should not shift display window
+        let shift_type = self.get_shift_type();
+        self.set_shift_type(ShiftType::CursorOnly);
+
+        (0..line_capacity as u8).for_each(|visible_col| {
+            let pos = self.visible_to_ddram(visible_col, row);
+            self.write_char_to_pos(char_at(visible_col as u32), pos);
+        });
+
+        let total_steps = loops as u64 * period as u64;
+        let mut step = 0u64;
+        while loops == 0 || step < total_steps {
+            // the cell next to the right edge of display window will show up after the shift
+            if period > line_capacity {
+                let pos = self.visible_to_ddram(columns as u8, row);
+                self.write_char_to_pos(char_at((step % period as u64) as u32 + columns), pos);
+            }
+
+            self.delay_us(step_delay_us);
+            self.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::RightToLeft);
+            yield_fn();
+
+            step += 1;
+        }
+
+        self.set_shift_type(shift_type);
+        self.shift_display_to_pos(
+            display_offset,
+            MoveStyle::Shortest,
+            self.get_display_state(),
+            0,
+        );
+        if let Some(cursor_pos) = cursor_pos {
+            self.set_cursor_pos(cursor_pos);
+        }
+    }
+
     /// Split-Flap-style display
     ///
     /// # Arguments
@@ -364,6 +2026,116 @@ pub trait Anim: Ext {
         }
     }
 
+    /// Split-Flap-style display, each character flips through `alphabet` (e.g. `b"ABCDEFGHIJKLMNOPQRSTUVWXYZ"`) in its order,
+    /// from the first byte of `alphabet` to the target character
+    ///
+    /// Characters not in `alphabet` are written directly without flipping.
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `alphabet` - the bytes to flip through, in order
+    /// * `fs` - flip style, see [FlipStyle]
+    /// * `max_flip_cnt` - The maximum number of times to flip the display before reaching the target character
+    /// * `per_flip_delay_us` - The delay (in microseconds) between each flip. It is recommended to set this value to at least `100_000`.
+    /// * `per_char_flip_delay_us` - Used in [FlipStyle::Sequential] mode, this is the time (in microseconds) to wait between flipping each character
+    fn split_flap_write_alphabet(
+        &mut self,
+        str: &str,
+        alphabet: &[u8],
+        fs: FlipStyle,
+        max_flip_cnt: Option<u8>,
+        per_flip_delay_us: u32,
+        per_char_flip_delay_us: Option<u32>,
+    ) {
+        let char_rom = self.get_char_rom();
+        let to_byte = |char: char| {
+            char_rom
+                .map_char(char)
+                .unwrap_or(char_rom.replacement_byte())
+        };
+
+        // the index range in `alphabet` to flip through, [`None`] if the byte is not in `alphabet`
+        let flip_range = |byte: u8| {
+            alphabet
+                .iter()
+                .position(|&flap| flap == byte)
+                .map(|target| {
+                    let start = max_flip_cnt.map_or(0, |cnt| target.saturating_sub(cnt as usize));
+                    start..=target
+                })
+        };
+
+        let cursor_state = self.get_cursor_state();
+
+        // turn off cursor, since it will always shift to next position
+        self.set_cursor_state(State::Off);
+
+        match fs {
+            FlipStyle::Sequential => {
+                assert!(
+                    per_char_flip_delay_us.is_some(),
+                    "Should set some per char delay in Sequential Mode"
+                );
+                str.chars().map(to_byte).for_each(|byte| {
+                    let cur_pos = self.get_cursor_pos();
+
+                    self.delay_us(per_char_flip_delay_us.unwrap());
+                    match flip_range(byte) {
+                        Some(range) => alphabet[range].iter().for_each(|&flap| {
+                            self.delay_us(per_flip_delay_us);
+                            self.write_byte_to_pos(flap, cur_pos);
+                        }),
+                        None => self.write_byte_to_pos(byte, cur_pos),
+                    }
+                })
+            }
+            FlipStyle::Simultaneous => {
+                let str_len = str.chars().count();
+                let frame_cnt = str
+                    .chars()
+                    .map(|char| flip_range(to_byte(char)).map_or(1, |range| range.count()))
+                    .max()
+                    .unwrap_or(0);
+
+                let start_pos = self.get_cursor_pos();
+                let dir_sign = match self.get_direction() {
+                    MoveDirection::RightToLeft => -1,
+                    MoveDirection::LeftToRight => 1,
+                };
+
+                (0..frame_cnt).for_each(|frame| {
+                    self.delay_us(per_flip_delay_us);
+
+                    str.chars().enumerate().for_each(|(index, char)| {
+                        let byte = to_byte(char);
+
+                        // characters already reached their targets are skipped
+                        let flap = match flip_range(byte) {
+                            Some(range) => match range.clone().nth(frame) {
+                                Some(flap_index) => alphabet[flap_index],
+                                None => return,
+                            },
+                            None if frame == 0 => byte,
+                            None => return,
+                        };
+
+                        let cur_pos =
+                            self.calculate_pos_by_offset(start_pos, (dir_sign * index as i8, 0));
+                        self.write_byte_to_pos(flap, cur_pos);
+                    });
+                });
+
+                // after the flip finished, we cannot ensure cursor position, move cursor to string end
+                let end_pos =
+                    self.calculate_pos_by_offset(start_pos, (dir_sign * str_len as i8, 0));
+                self.set_cursor_pos(end_pos);
+            }
+        }
+
+        self.set_cursor_state(cursor_state);
+    }
+
     /// Move the display window to the specified position (measured from the upper-left corner of the display)
     ///
     /// # Arguments
@@ -456,4 +2228,1128 @@ pub trait Anim: Ext {
         // restore original display state
         self.set_display_state(before_state);
     }
+
+    /// Run the showcase of the examples: typewriter, split-flap, custom graphs and display shifting,
+    /// e.g. to verify the wiring with one call, it takes about 20 seconds
+    ///
+    /// The display is cleaned at the end, and display, direction, shift type and cursor states are restored.
+    ///
+    /// Note:
+    /// CGRAM slot 0 and 2 are overwritten
+    #[cfg(feature = "demo")]
+    fn run_demo(&mut self) {
+        demo::run_demo(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use crate::{
+        charset::CharRom,
+        command::{
+            Bits, Font, LineMode, MoveDirection, RAMType, ReadWriteOp, RegisterSelection,
+            ShiftType, State,
+        },
+        sender::{MockDelay, MockSender},
+        widgets::ProgressBar,
+    };
+
+    use super::{
+        Anim, BacklightLevel, Basic, Config, DisplayMode, Ext, FlipStyle, GlyphSlot, Lcd,
+        MoveStyle, SelfTestResult, TimingMode, Timings, VerifyError,
+    };
+
+    #[test]
+    fn regions_fit_in_row_capacity() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_columns(20).set_rows(4);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        assert_eq!(lcd.get_row_capacity(2), 20);
+
+        lcd.write_region((15, 2), 5, 2, b"helloworld");
+        let mut out = [0; 10];
+        lcd.read_region((15, 2), 5, 2, &mut out);
+        assert_eq!(&out, b"helloworld");
+    }
+
+    #[test]
+    #[should_panic(expected = "region is out of row capacity")]
+    fn region_out_of_row_capacity_panics_up_front() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_columns(20).set_rows(4);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        lcd.clear_region((18, 1), 5, 1);
+    }
+
+    #[test]
+    fn slide_in_rests_at_edges() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut steps = 0;
+        lcd.slide_in_from_right_with_yield("abc", 0, 0, || steps += 1);
+        lcd.slide_in_from_left("xyz", 1, 0);
+        let (sender, _) = lcd.release();
+
+        // one step per column
+        assert_eq!(steps, 16);
+        let grid = sender.render::<16, 2>();
+        assert_eq!(grid[0][..4], ['a', 'b', 'c', ' ']);
+        assert_eq!(grid[1][12..], [' ', 'x', 'y', 'z']);
+    }
+
+    #[test]
+    fn ram_type_follows_address_set() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.set_cgram_addr(8);
+        assert!(lcd.is_in_cgram() && !lcd.is_in_ddram());
+        lcd.set_cursor_pos((0, 1));
+        assert!(lcd.is_in_ddram() && !lcd.is_in_cgram());
+    }
+
+    #[test]
+    fn long_commands_wait_worst_case_in_fixed_delay() {
+        let lcd_ops: [fn(&mut Lcd<MockSender, MockDelay>); 2] =
+            [|lcd| lcd.clean_display(), |lcd| lcd.return_home()];
+        for op in lcd_ops {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let config = Config::default().set_timing_mode(TimingMode::FixedDelay);
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            op(&mut lcd);
+            let (sender, delayer) = lcd.release();
+
+            assert_eq!(delayer.get_delays_ns().last(), Some(&1_520_000));
+            assert!(sender
+                .get_commands()
+                .iter()
+                .all(|command| command.get_read_write_op() == ReadWriteOp::Write));
+        }
+    }
+
+    #[test]
+    fn set_ac_sends_address_set_of_ram_type() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.set_ac(0x45, RAMType::DDRam);
+        assert!(lcd.is_in_ddram());
+        assert_eq!(lcd.get_cursor_pos(), (5, 1));
+        lcd.set_ac(0x10, RAMType::CGRam);
+        assert!(lcd.is_in_cgram());
+        let (sender, _) = lcd.release();
+
+        let mut writes = sender
+            .get_commands()
+            .iter()
+            .rev()
+            .filter(|command| command.get_read_write_op() == ReadWriteOp::Write)
+            .map(|command| command.get_data());
+        assert_eq!(writes.next(), Some(Some(Bits::Bit8(0b0100_0000 | 0x10))));
+        assert_eq!(writes.next(), Some(Some(Bits::Bit8(0b1000_0000 | 0x45))));
+    }
+
+    #[test]
+    fn glyph_index_is_offset_by_display_base() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_cgram_display_base(0x08);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        lcd.write_graph_to_cgram(1, &[0b11111; 8]);
+        lcd.write_graph_to_pos(1, (0, 0));
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[0], 0x09);
+    }
+
+    #[test]
+    fn unsupported_chars_under_a00() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut out = ['\0'; 4];
+        assert_eq!(lcd.unsupported_chars("Aé", &mut out), 1);
+        assert_eq!(out[0], 'é');
+        assert_eq!(lcd.unsupported_chars("A", &mut out), 0);
+    }
+
+    #[test]
+    fn display_fade_ends_in_target_state() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.display_fade(State::Off, 100_000);
+        assert_eq!(lcd.get_display_state(), State::Off);
+        lcd.display_fade(State::On, 100_000);
+        assert_eq!(lcd.get_display_state(), State::On);
+    }
+
+    #[test]
+    fn fill_cell_writes_eight_cgram_rows() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.fill_cell(2, (3, 1), 0);
+        assert_eq!(lcd.get_cursor_pos(), (4, 1));
+        let (sender, _) = lcd.release();
+
+        let cgram_addr_sets = sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Command
+                    && matches!(
+                        command.get_data(),
+                        Some(Bits::Bit8(0b0100_0000..=0b0111_1111))
+                    )
+            })
+            .count();
+        assert_eq!(cgram_addr_sets, 8);
+        assert_eq!(sender.get_cgram()[2 * 8..3 * 8], [0b11111; 8]);
+    }
+
+    #[test]
+    fn reinit_forgets_uploaded_glyphs() {
+        const GLYPH: [u8; 8] = [0b10101; 8];
+
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(lcd.define_glyph_deduped(&GLYPH), Some(0));
+        assert_eq!(lcd.define_glyph_deduped(&GLYPH), Some(0));
+        lcd.reinit();
+        assert_eq!(lcd.define_glyph_deduped(&GLYPH), Some(0));
+        let (sender, _) = lcd.release();
+
+        // uploaded once before reinit, and once after
+        let glyph_row_writes = sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Data
+                    && command.get_read_write_op() == ReadWriteOp::Write
+                    && command.get_data() == Some(Bits::Bit8(0b10101))
+            })
+            .count();
+        assert_eq!(glyph_row_writes, 2 * 8);
+    }
+
+    #[test]
+    fn write_u32_rtl_reads_correctly() {
+        for direction in [MoveDirection::RightToLeft, MoveDirection::LeftToRight] {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let config = Config::default().set_direction(direction);
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            lcd.write_u32_rtl(1234, (5, 0));
+            let (sender, _) = lcd.release();
+
+            assert_eq!(&sender.get_ddram()[2..6], b"1234");
+        }
+    }
+
+    #[test]
+    fn self_test_passes_on_readable_lcd() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(lcd.self_test(), SelfTestResult::Passed);
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+        let (sender, _) = lcd.release();
+
+        assert_eq!(
+            sender.render::<16, 2>(),
+            [[' '; 16]; 2],
+            "display should be clean after the test"
+        );
+    }
+
+    #[test]
+    fn autoscroll_writes_update_display_offset() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.enable_autoscroll();
+        assert_eq!(lcd.get_direction(), MoveDirection::LeftToRight);
+        assert_eq!(lcd.get_shift_type(), ShiftType::CursorAndDisplay);
+        // display content moves left once per write
+        lcd.write_str_to_cur("abc");
+        assert_eq!(lcd.get_display_offset(), 37);
+        assert_eq!(lcd.visible_to_ddram(0, 0), (3, 0));
+
+        lcd.disable_autoscroll();
+        lcd.write_str_to_cur("d");
+        assert_eq!(lcd.get_display_offset(), 37);
+    }
+
+    #[test]
+    fn display_mode_follows_setters() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.set_display_state(State::On);
+        lcd.set_cursor_state(State::Off);
+        lcd.set_cursor_blink_state(State::On);
+        lcd.set_direction(MoveDirection::RightToLeft);
+        lcd.set_shift_type(ShiftType::CursorAndDisplay);
+
+        assert_eq!(
+            lcd.display_mode(),
+            DisplayMode {
+                display: State::On,
+                cursor: State::Off,
+                blink: State::On,
+                direction: MoveDirection::RightToLeft,
+                shift_type: ShiftType::CursorAndDisplay,
+            }
+        );
+
+        lcd.set_display_state(State::Off);
+        lcd.set_direction(MoveDirection::LeftToRight);
+        let mode = lcd.display_mode();
+        assert_eq!(mode.display, State::Off);
+        assert_eq!(mode.direction, MoveDirection::LeftToRight);
+    }
+
+    #[test]
+    fn batch_sends_entry_mode_once_on_commit() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.sender.clear_commands();
+
+        lcd.begin_batch();
+        lcd.set_direction(MoveDirection::RightToLeft);
+        lcd.set_shift_type(ShiftType::CursorAndDisplay);
+        assert!(lcd.sender.get_commands().is_empty());
+        lcd.commit_batch();
+
+        let writes: Vec<_> = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| command.get_read_write_op() == ReadWriteOp::Write)
+            .map(|command| command.get_data())
+            .collect();
+        // Entry Mode Set, decrement, with display shift
+        assert_eq!(writes, [Some(Bits::Bit8(0b0000_0101))]);
+    }
+
+    #[test]
+    fn centering_counts_double_width_marker() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_centered("ab*c", 0);
+        lcd.write_str_centered_with_width("ab*c", 1, |char| match char {
+            '*' => 2,
+            _ => 1,
+        });
+        let (sender, _) = lcd.release();
+
+        let rows = sender.render::<16, 2>();
+        assert_eq!(rows[0].iter().collect::<String>(), "      ab*c      ");
+        // the second cell of the marker is left untouched
+        assert_eq!(rows[1].iter().collect::<String>(), "     ab* c      ");
+    }
+
+    #[test]
+    fn verified_upload_catches_wrong_read_back() {
+        const GLYPH: [u8; 8] = [0b10101; 8];
+
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(lcd.write_graph_to_cgram_verified(2, &GLYPH), Ok(()));
+
+        // the bus flips a bit of the 4th row
+        (0..8).for_each(|row| {
+            lcd.sender
+                .seed_data(if row == 3 { 0b10100 } else { GLYPH[row] })
+        });
+        assert_eq!(
+            lcd.write_graph_to_cgram_verified(2, &GLYPH),
+            Err(VerifyError {
+                row: 3,
+                expected: 0b10101,
+                actual: 0b10100,
+            })
+        );
+    }
+
+    #[test]
+    fn custom_glyph_limit_follows_font() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(lcd.max_custom_glyphs(), 8);
+
+        let config = Config::default()
+            .set_line_mode(LineMode::OneLine)
+            .set_font(Font::Font5x11);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        assert_eq!(lcd.max_custom_glyphs(), 4);
+        lcd.write_graph_to_pos(6, (0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "index should be 0, 2, 4 or 6")]
+    fn odd_glyph_index_panics_with_5x11_font() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default()
+            .set_line_mode(LineMode::OneLine)
+            .set_font(Font::Font5x11);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        lcd.write_graph_to_pos(3, (0, 0));
+    }
+
+    #[test]
+    fn glyph_slot_range() {
+        assert_eq!(GlyphSlot::new(7).map(GlyphSlot::index), Some(7));
+        assert_eq!(GlyphSlot::new(8), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only 8 graphs allowed in CGRAM")]
+    fn glyph_slot_from_out_of_range_u8_panics() {
+        let _ = GlyphSlot::from(8);
+    }
+
+    #[test]
+    fn preview_bytes_of_each_rom() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut out = [0u8; 8];
+
+        assert_eq!(lcd.preview_bytes("25°C", CharRom::A00, &mut out), 4);
+        assert_eq!(out[..4], [b'2', b'5', 0xDF, b'C']);
+        assert_eq!(lcd.preview_bytes("25°C", CharRom::A02, &mut out), 4);
+        assert_eq!(out[..4], [b'2', b'5', 0xB0, b'C']);
+
+        // only the bytes fit in `out` are filled
+        let mut short_out = [0u8; 2];
+        assert_eq!(lcd.preview_bytes("25°C", CharRom::A00, &mut short_out), 4);
+        assert_eq!(short_out, [b'2', b'5']);
+    }
+
+    #[test]
+    fn raw_sequence_sends_steps_as_given() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.sender.clear_commands();
+        lcd.delayer.clear_delays();
+
+        let steps = [
+            (RegisterSelection::Command, 0x39, 30),
+            (RegisterSelection::Command, 0x14, 30),
+            (RegisterSelection::Data, b'A', 0),
+        ];
+        lcd.run_raw_sequence(&steps);
+
+        let sent: Vec<_> = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .map(|command| {
+                (
+                    command.get_register_selection(),
+                    command.get_read_write_op(),
+                    command.get_data(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            sent,
+            steps.map(|(register, byte, _)| (register, ReadWriteOp::Write, Some(Bits::Bit8(byte))))
+        );
+        assert_eq!(lcd.delayer.get_delays_ns(), [30_000, 30_000, 0]);
+    }
+
+    #[test]
+    fn visible_and_ddram_columns_follow_offset() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("0123456789ABCDEFGHIJ", (0, 1));
+        // display content moves left 3 times, display window starts at DDRAM column 3
+        (0..3).for_each(|_| {
+            lcd.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::RightToLeft)
+        });
+        assert_eq!(lcd.get_display_offset(), 37);
+        assert_eq!(lcd.visible_to_ddram(0, 1), (3, 1));
+        assert_eq!(lcd.ddram_to_visible(3, 1), Some((0, 1)));
+        assert_eq!(lcd.ddram_to_visible(18, 0), Some((15, 0)));
+        assert_eq!(lcd.ddram_to_visible(19, 0), None);
+        assert_eq!(lcd.sender.render::<16, 2>()[1][0], '3');
+
+        // display content moves right 3 times from the origin, wraps around the end of line
+        (0..6).for_each(|_| {
+            lcd.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::LeftToRight)
+        });
+        assert_eq!(lcd.get_display_offset(), 3);
+        assert_eq!(lcd.visible_to_ddram(0, 0), (37, 0));
+        assert_eq!(lcd.visible_to_ddram(3, 1), (0, 1));
+        assert_eq!(lcd.ddram_to_visible(0, 1), Some((3, 1)));
+        assert_eq!(lcd.ddram_to_visible(36, 0), None);
+        assert_eq!(lcd.sender.render::<16, 2>()[1][3], '0');
+    }
+
+    #[test]
+    fn write_at_visible_follows_offset() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        // display window starts at DDRAM column 5
+        let config = Config::default().set_display_offset(35);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        lcd.write_char_at_visible('A', (0, 0));
+        lcd.write_str_at_visible("bc", (1, 1));
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[5], b'A');
+        assert_eq!(sender.get_ddram()[0x40 + 6..0x40 + 8], *b"bc");
+    }
+
+    #[test]
+    fn software_cursor_alternates_and_restores() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("hi", (4, 1));
+        lcd.set_cursor_pos((0, 0));
+        lcd.sender.clear_commands();
+
+        lcd.software_cursor_blink((5, 1), b'_', 2, 0);
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+
+        let written: Vec<_> = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Data
+                    && command.get_read_write_op() == ReadWriteOp::Write
+            })
+            .map(|command| command.get_data())
+            .collect();
+        assert_eq!(
+            written,
+            [b'_', b'i', b'_', b'i'].map(|byte| Some(Bits::Bit8(byte)))
+        );
+        assert_eq!(lcd.sender.get_ddram()[0x45], b'i');
+    }
+
+    #[test]
+    fn read_region_round_trips_through_write_region() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("abc", (5, 0));
+        lcd.write_str_to_pos("def", (5, 1));
+        lcd.set_cursor_pos((1, 1));
+
+        let mut saved = [0u8; 6];
+        lcd.read_region((5, 0), 3, 2, &mut saved);
+        assert_eq!(saved, *b"abcdef");
+        assert_eq!(lcd.get_cursor_pos(), (1, 1));
+
+        // a popup covers the region, then the background is restored
+        lcd.clear_region((5, 0), 3, 2);
+        lcd.write_region((5, 0), 3, 2, &saved);
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[0x05..0x08], *b"abc");
+        assert_eq!(sender.get_ddram()[0x45..0x48], *b"def");
+    }
+
+    #[test]
+    fn write_region_writes_bytes_verbatim() {
+        for direction in [MoveDirection::LeftToRight, MoveDirection::RightToLeft] {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let config = Config::default().set_direction(direction);
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            lcd.write_region((2, 0), 2, 2, &[0x00, 0xFF, 0xDF, b'~']);
+            let (sender, _) = lcd.release();
+
+            assert_eq!(sender.get_ddram()[0x02..0x04], [0x00, 0xFF]);
+            assert_eq!(sender.get_ddram()[0x42..0x44], [0xDF, b'~']);
+            assert_eq!(sender.get_ddram()[0x04], b' ');
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "data is too short for the region")]
+    fn write_region_with_short_data_panics() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_region((0, 0), 2, 2, b"abc");
+    }
+
+    #[test]
+    fn title_is_centered_on_first_two_rows() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("old content here", (0, 0));
+        lcd.write_title("Hi", "Boot v1");
+        let (sender, _) = lcd.release();
+
+        let rows = sender.render::<16, 2>();
+        assert_eq!(rows[0].iter().collect::<String>(), "       Hi       ");
+        assert_eq!(rows[1].iter().collect::<String>(), "    Boot v1     ");
+    }
+
+    #[test]
+    fn stuck_busy_falls_back_to_fixed_delay() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_busy_fallback_after_us(Some(100));
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 10);
+        assert!(!lcd.is_busy_fallback_triggered());
+
+        // busy flag never clears
+        (0..100).for_each(|_| lcd.sender.seed_status(0b1000_0000));
+        lcd.sender.clear_commands();
+        lcd.write_str_to_cur("AB");
+
+        assert!(lcd.is_busy_fallback_triggered());
+        assert_eq!(lcd.get_timing_mode(), TimingMode::FixedDelay);
+
+        // busy flag is polled for 100 us, then never read again
+        let status_reads = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| command.get_read_write_op() == ReadWriteOp::Read)
+            .count();
+        assert_eq!(status_reads, 11);
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[..2], *b"AB");
+    }
+
+    #[test]
+    fn scaled_si_values() {
+        let cases: [(u32, &str); 6] = [
+            (1_200, "1.2 kPa"),
+            (0, "0 Pa"),
+            (999, "999 Pa"),
+            (1_000, "1 kPa"),
+            (12_345, "12.3 kPa"),
+            (2_050_000, "2.05 MPa"),
+        ];
+
+        for (value, expected) in cases {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+            lcd.write_scaled_si(value, "Pa", (0, 1));
+            let (sender, _) = lcd.release();
+
+            let row = sender.render::<16, 2>()[1].iter().collect::<String>();
+            assert_eq!(row.trim_end(), expected);
+        }
+    }
+
+    #[test]
+    fn shadow_row_records_written_bytes() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert!(lcd.shadow_row(0).is_empty());
+        let (sender, delayer) = lcd.release();
+
+        let config = Config::default().set_shadow_contents(true);
+        let mut lcd = Lcd::new(sender, delayer, config, 0);
+        lcd.write_str_to_pos("Hi", (0, 1));
+        assert_eq!(lcd.shadow_row(1)[..3], *b"Hi ");
+        assert_eq!(lcd.shadow_row(1).len(), 40);
+        assert!(lcd.shadow_row(0).iter().all(|&byte| byte == b' '));
+    }
+
+    #[test]
+    fn shorter_overwrite_clears_field() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("hello", (3, 0));
+        lcd.write_str_to_pos_clear("hi", (3, 0), 8);
+        assert_eq!(lcd.sender.get_ddram()[3..8], *b"hi   ");
+
+        let config = Config::default().set_clear_trailing(true);
+        let mut lcd = Lcd::new(lcd.sender, lcd.delayer, config, 0);
+        lcd.write_str_to_pos("hello", (0, 1));
+        lcd.write_str_to_pos("hi", (0, 1));
+        assert_eq!(lcd.sender.get_ddram()[0x40..0x50], *b"hi              ");
+    }
+
+    #[test]
+    fn write_field_pads_and_truncates() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("999999", (2, 0));
+        lcd.write_field("42", (2, 0), 5);
+        assert_eq!(lcd.sender.get_ddram()[2..8], *b"42   9");
+
+        lcd.write_field("123456", (0, 1), 5);
+        assert_eq!(lcd.sender.get_ddram()[0x40..0x46], *b"12345 ");
+    }
+
+    #[test]
+    fn timings_follow_config() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default()
+            .set_timing_mode(TimingMode::FixedDelay)
+            .set_busy_fallback_after_us(Some(2_000));
+        let (lcd, init_delay_us) = Lcd::new_timed(&mut sender, &mut delayer, config, 25);
+
+        assert_eq!(
+            lcd.timings(),
+            Timings {
+                timing_mode: TimingMode::FixedDelay,
+                poll_interval_us: 25,
+                busy_fallback_after_us: Some(2_000),
+                init_delay_us,
+                clear_display_us: 1_520,
+                return_home_us: 1_520,
+                command_us: 37,
+            }
+        );
+    }
+
+    #[test]
+    fn wrapped_text_fills_visible_grid() {
+        let rows_of = |text: &str| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+            lcd.write_str_wrapped(text, 16);
+            let (sender, _) = lcd.release();
+
+            sender
+                .render::<16, 2>()
+                .map(|row| row.iter().collect::<String>())
+        };
+
+        assert_eq!(
+            rows_of("the quick brown fox jumps"),
+            ["the quick brown ", "fox jumps       "]
+        );
+        // a word longer than a row is broken, and the last row wraps to the first row
+        assert_eq!(
+            rows_of("0123456789abcdefghijklmnopqrstuvwxyz"),
+            ["wxyz456789abcdef", "ghijklmnopqrstuv"]
+        );
+    }
+
+    #[test]
+    fn multiline_writes_one_row_per_line() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_multiline("a\nb\nc", (0, 0));
+        lcd.write_multiline("0123456789abcdefgh", (3, 1));
+        let (sender, _) = lcd.release();
+
+        // the third line is dropped, and the long line is truncated at the right edge
+        assert_eq!(
+            sender
+                .render::<16, 2>()
+                .map(|row| row.iter().collect::<String>()),
+            ["a               ", "b  0123456789abc"]
+        );
+        assert_eq!(sender.get_ddram()[0x40 + 16], b' ');
+    }
+
+    #[test]
+    fn unchanged_mode_is_not_resent() {
+        // count of Entry Mode Set sent by setting direction right to left, then left to right twice
+        let entry_mode_sets = |config: Config| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            lcd.sender.clear_commands();
+            lcd.set_direction(MoveDirection::RightToLeft);
+            lcd.set_direction(MoveDirection::LeftToRight);
+            lcd.set_direction(MoveDirection::LeftToRight);
+
+            lcd.sender
+                .get_commands()
+                .iter()
+                .filter(|command| {
+                    command.get_register_selection() == RegisterSelection::Command
+                        && command.get_read_write_op() == ReadWriteOp::Write
+                        && matches!(
+                            command.get_data(),
+                            Some(Bits::Bit8(0b0000_0100..=0b0000_0111))
+                        )
+                })
+                .count()
+        };
+
+        assert_eq!(entry_mode_sets(Config::default()), 2);
+        assert_eq!(
+            entry_mode_sets(Config::default().set_force_mode_commands(true)),
+            3
+        );
+    }
+
+    #[test]
+    fn resync_modes_sends_three_mode_commands() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.sender.clear_commands();
+        lcd.resync_modes();
+
+        let writes: Vec<_> = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| command.get_read_write_op() == ReadWriteOp::Write)
+            .map(|command| (command.get_register_selection(), command.get_data()))
+            .collect();
+        assert!(matches!(
+            writes[..],
+            [
+                (
+                    RegisterSelection::Command,
+                    Some(Bits::Bit8(0b0010_0000..=0b0011_1111))
+                ),
+                (
+                    RegisterSelection::Command,
+                    Some(Bits::Bit8(0b0000_1000..=0b0000_1111))
+                ),
+                (
+                    RegisterSelection::Command,
+                    Some(Bits::Bit8(0b0000_0100..=0b0000_0111))
+                ),
+            ]
+        ));
+    }
+
+    #[test]
+    fn yield_fn_is_called_once_per_step() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+
+        let mut chars = 0;
+        lcd.typewriter_write_with_yield("abcd", 100, || chars += 1);
+        assert_eq!(chars, 4);
+
+        // each blink toggles display twice
+        let mut toggles = 0;
+        lcd.full_display_blink_with_yield(3, 100, || toggles += 1);
+        assert_eq!(toggles, 6);
+    }
+
+    #[test]
+    fn verify_region_reports_first_wrong_cell() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("abc", (4, 0));
+        lcd.write_str_to_pos("def", (4, 1));
+        assert_eq!(lcd.verify_region((4, 0), 3, 2, b"abcdef"), Ok(()));
+
+        // the second cell of the second row is garbled
+        b"abcdXf"
+            .iter()
+            .for_each(|&byte| lcd.sender.seed_data(byte));
+        assert_eq!(lcd.verify_region((4, 0), 3, 2, b"abcdef"), Err((5, 1)));
+    }
+
+    #[test]
+    fn clean_display_resets_direction_unless_preserved() {
+        let direction_after_clear = |config: Config| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            lcd.set_direction(MoveDirection::RightToLeft);
+            lcd.sender.clear_commands();
+            lcd.clean_display();
+            // Entry Mode Set sent after Clear Display
+            let entry_modes: Vec<u8> = lcd
+                .sender
+                .get_commands()
+                .iter()
+                .filter_map(|command| match command.get_data() {
+                    Some(Bits::Bit8(byte @ 0b0000_0100..=0b0000_0111))
+                        if command.get_register_selection() == RegisterSelection::Command
+                            && command.get_read_write_op() == ReadWriteOp::Write =>
+                    {
+                        Some(byte)
+                    }
+                    _ => None,
+                })
+                .collect();
+            (lcd.get_direction(), entry_modes)
+        };
+
+        assert_eq!(
+            direction_after_clear(Config::default()),
+            (MoveDirection::LeftToRight, Vec::new())
+        );
+        assert_eq!(
+            direction_after_clear(Config::default().set_preserve_direction_on_clear(true)),
+            (MoveDirection::RightToLeft, [0b0000_0100].into())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "demo")]
+    fn demo_ends_with_clean_display() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let direction = lcd.get_direction();
+        lcd.run_demo();
+
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+        assert_eq!(lcd.get_display_offset(), 0);
+        assert_eq!(lcd.get_display_state(), State::On);
+        assert_eq!(lcd.get_direction(), direction);
+        assert!(lcd.sender.get_ddram().iter().all(|&byte| byte == b' '));
+    }
+
+    #[test]
+    fn split_flap_flips_through_alphabet_only() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.sender.clear_commands();
+        lcd.split_flap_write_alphabet(
+            "C!",
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            FlipStyle::Sequential,
+            None,
+            0,
+            Some(0),
+        );
+
+        let written: Vec<u8> = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Data
+                    && command.get_read_write_op() == ReadWriteOp::Write
+            })
+            .filter_map(|command| match command.get_data() {
+                Some(Bits::Bit8(byte)) => Some(byte),
+                _ => None,
+            })
+            .collect();
+        // '!' is not in the alphabet, it's written directly
+        assert_eq!(written, b"ABC!");
+        assert_eq!(lcd.sender.get_ddram()[..2], *b"C!");
+    }
+
+    #[test]
+    fn reset_view_shifts_back_the_shorter_way() {
+        // commands sent by reset_view after shifting display window `shifts` times to the right
+        let reset_view_commands = |shifts: u8| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+            lcd.set_cursor_pos((5, 1));
+            (0..shifts).for_each(|_| {
+                lcd.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::RightToLeft)
+            });
+            lcd.sender.clear_commands();
+            lcd.reset_view();
+
+            assert_eq!(lcd.get_cursor_pos(), (0, 0));
+            assert_eq!(lcd.get_display_offset(), 0);
+
+            lcd.sender
+                .get_commands()
+                .iter()
+                .filter(|command| command.get_read_write_op() == ReadWriteOp::Write)
+                .filter_map(|command| match command.get_data() {
+                    Some(Bits::Bit8(byte)) => Some(byte),
+                    _ => None,
+                })
+                .collect::<Vec<u8>>()
+        };
+
+        // Set DDRAM Address to 0, then shift display content to the right
+        assert_eq!(
+            reset_view_commands(3),
+            [0b1000_0000, 0b0001_1100, 0b0001_1100, 0b0001_1100]
+        );
+        // near the end of line, shift display content to the left, and wrap to the start
+        assert_eq!(
+            reset_view_commands(38),
+            [0b1000_0000, 0b0001_1000, 0b0001_1000]
+        );
+    }
+
+    #[test]
+    fn batch_data_writes_need_sender_support() {
+        // commands and total delay of writing a string in fixed delay mode
+        let write_str = |batch: bool, can_batch: bool| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+            sender.set_can_batch(can_batch);
+
+            let config = Config::default()
+                .set_timing_mode(TimingMode::FixedDelay)
+                .set_batch_data_writes(batch);
+            let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+            lcd.sender.clear_commands();
+            lcd.delayer.clear_delays();
+            lcd.write_str_to_cur("abcd");
+
+            (
+                lcd.sender.get_commands().to_vec(),
+                lcd.delayer.get_total_ns(),
+            )
+        };
+
+        // a sender cannot batch writes bytes one by one, each waits for LCD
+        assert_eq!(write_str(true, false), write_str(false, false));
+
+        let (one_by_one, one_by_one_ns) = write_str(false, true);
+        let (batched, batched_ns) = write_str(true, true);
+        assert!(batched.len() < one_by_one.len());
+        assert!(batched_ns < one_by_one_ns);
+    }
+
+    #[test]
+    fn horizontal_bar_matches_progress_bar() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.upload_horizontal_bar_glyphs(0);
+        let progress_bar = ProgressBar::new(&mut lcd, 0);
+
+        // both use the same graphs in the same slots, thus the same bytes for the same fill
+        for percent in [0, 1, 37, 50, 99, 100, 150] {
+            progress_bar.render(&mut lcd, (0, 0), 4, percent);
+            lcd.draw_horizontal_bar(percent, 100, (0, 1), 4);
+            assert_eq!(
+                lcd.sender.get_ddram()[..4],
+                lcd.sender.get_ddram()[0x40..0x44],
+                "{percent}%"
+            );
+        }
+    }
+
+    #[test]
+    fn vertical_bar_rounds_to_nearest_row() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.upload_vertical_bar_glyphs();
+        assert_eq!(lcd.get_cgram_graph(2), Some([0, 0, 0, 0, 0, 31, 31, 31]));
+
+        // 16 pixel rows, 10.6 of them rounded to 11
+        lcd.draw_vertical_bar(2, 3, (4, 1), 2);
+        let (sender, _) = lcd.release();
+        assert_eq!(sender.get_ddram()[0x44], 7);
+        assert_eq!(sender.get_ddram()[0x04], 2);
+    }
+
+    #[test]
+    fn dim_toggles_on_off_backlight() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.delayer.clear_delays();
+        let init_writes = lcd.sender.get_backlight_writes().len();
+        lcd.set_backlight_level(BacklightLevel::Dim);
+        assert_eq!(lcd.get_backlight(), State::On);
+
+        let writes = &lcd.sender.get_backlight_writes()[init_writes..];
+        assert_eq!(writes.len(), 100);
+        assert!(writes
+            .chunks(2)
+            .all(|cycle| cycle == [State::Off, State::On]));
+        assert_eq!(lcd.delayer.get_total_ns(), 20_000_000);
+    }
+
+    #[test]
+    fn shift_display_to_pos_moves_content_right_for_positive_offset() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("abc", (0, 0));
+
+        lcd.shift_display_to_pos(2, MoveStyle::Shortest, State::On, 0);
+        assert_eq!(
+            lcd.sender.render::<16, 2>()[0][..5],
+            [' ', ' ', 'a', 'b', 'c']
+        );
+        assert_eq!(lcd.ddram_to_visible(0, 0), Some((2, 0)));
+
+        lcd.shift_display_to_pos(40 - 2, MoveStyle::Shortest, State::On, 0);
+        assert_eq!(lcd.sender.render::<16, 2>()[0][..2], ['c', ' ']);
+        assert_eq!(lcd.visible_to_ddram(0, 0), (2, 0));
+
+        lcd.shift_display_to_pos(0, MoveStyle::Shortest, State::On, 0);
+        assert_eq!(lcd.sender.render::<16, 2>()[0][..3], ['a', 'b', 'c']);
+    }
 }
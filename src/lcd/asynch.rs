@@ -0,0 +1,616 @@
+//! [`LcdAsync`] is the async version of [`Lcd`](super::Lcd)
+
+use embedded_hal_async::delay::DelayNs;
+
+use crate::{
+    command::{CommandSet, MoveDirection, RAMType, ShiftType, State},
+    sender::SendCommandAsync,
+    state::LcdState,
+};
+
+use super::{
+    init::{init_mode_commands, init_raw_commands},
+    Config, GlyphSlot, Pos, TimingMode,
+};
+
+/// [`LcdAsync`] drives a LCD1602 with an async sender,
+/// the executor is yielded to while waiting for LCD, instead of spinning
+///
+/// It mirrors the most used methods of [`Basic`](super::Basic) and [`Ext`](super::Ext),
+/// with the same state bookkeeping (mode setters skip unchanged modes, shadow contents, direction on cleaning).
+/// Commands are always sent immediately, there is no batch support.
+///
+/// Note:
+/// Of [`Anim`](super::Anim), only [`LcdAsync::full_display_blink()`] and [`LcdAsync::typewriter_write()`] are mirrored,
+/// other animations can be built from these methods with [`LcdAsync::delay_us()`].
+pub struct LcdAsync<'a, 'b, Sender, Delayer>
+where
+    Delayer: DelayNs,
+{
+    sender: &'a mut Sender,
+    delayer: &'b mut Delayer,
+    state: LcdState,
+    poll_interval_us: u32,
+    timing_mode: TimingMode,
+    // a copy of DDRAM content, if enabled in config
+    shadow: Option<[u8; 0x80]>,
+    force_mode_commands: bool,
+    preserve_direction_on_clear: bool,
+}
+
+impl<'a, 'b, Sender, Delayer> LcdAsync<'a, 'b, Sender, Delayer>
+where
+    Sender: SendCommandAsync<Delayer>,
+    Delayer: DelayNs,
+{
+    /// Create a [`LcdAsync`] driver, and init LCD hardware
+    pub async fn new(
+        sender: &'a mut Sender,
+        delayer: &'b mut Delayer,
+        config: Config,
+        poll_interval_us: u32,
+    ) -> Self {
+        let mut lcd = LcdAsync {
+            sender,
+            delayer,
+            state: config.state,
+            poll_interval_us,
+            timing_mode: config.timing_mode,
+            shadow: config.shadow_contents.then_some([b' '; 0x80]),
+            force_mode_commands: config.force_mode_commands,
+            preserve_direction_on_clear: config.preserve_direction_on_clear,
+        };
+
+        for (command, delay_us) in init_raw_commands(&lcd.state, config.resync_nibbles_on_init)
+            .into_iter()
+            .flatten()
+        {
+            lcd.sender
+                .delay_and_send(command.into(), lcd.delayer, delay_us)
+                .await;
+        }
+
+        // in fixed delay mode, we cannot poll busy flag, wait for the last Function Set to finish
+        if lcd.timing_mode == TimingMode::FixedDelay {
+            lcd.delayer.delay_us(40).await;
+        }
+
+        for command in init_mode_commands(&lcd.state) {
+            lcd.send_command(command).await;
+        }
+
+        // set backlight after LCD init
+        lcd.sender.set_backlight(lcd.state.get_backlight()).await;
+
+        if config.initial_glyph_cnt > 0 {
+            let cursor_pos = lcd.get_cursor_pos();
+
+            // graphs already in CGRAM (known from a snapshot config) won't be uploaded again
+            for (index, graph_data) in config.initial_glyphs[..config.initial_glyph_cnt as usize]
+                .iter()
+                .enumerate()
+            {
+                if lcd.state.get_cgram_graph(index as u8) != Some(*graph_data) {
+                    lcd.write_graph_to_cgram(index as u8, graph_data).await;
+                }
+            }
+
+            // uploading CGRAM will move AC into CGRAM, move it back
+            lcd.set_cursor_pos(cursor_pos).await;
+        }
+
+        lcd
+    }
+
+    // send a command to LCD, with respect to current timing mode
+    async fn send_command(&mut self, command: CommandSet) -> Option<u8> {
+        match self.timing_mode {
+            TimingMode::BusyFlag => {
+                self.sender
+                    .wait_and_send(command.into(), self.delayer, self.poll_interval_us)
+                    .await
+            }
+            TimingMode::FixedDelay => {
+                let data = self.sender.send(command.into()).await;
+                // we cannot read busy flag, wait for the worst case execution time
+                self.delayer.delay_us(command.get_execution_time_us()).await;
+                data
+            }
+        }
+    }
+
+    // whether a mode setter should skip sending command, since the mode is `unchanged`
+    fn skip_mode(&self, unchanged: bool) -> bool {
+        unchanged && !self.force_mode_commands
+    }
+
+    // record a byte written to `pos` of DDRAM
+    fn record_shadow(&mut self, pos: (u8, u8), byte: u8) {
+        let addr = self.state.pos_to_ddram_addr(pos);
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow[addr as usize] = byte;
+        }
+    }
+
+    async fn send_entry_mode(&mut self) {
+        self.send_command(CommandSet::EntryModeSet(
+            self.state.get_direction(),
+            self.state.get_shift_type(),
+        ))
+        .await;
+    }
+
+    async fn send_display_on_off(&mut self) {
+        self.send_command(CommandSet::DisplayOnOff {
+            display: self.state.get_display_state(),
+            cursor: self.state.get_cursor_state(),
+            cursor_blink: self.state.get_cursor_blink(),
+        })
+        .await;
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_backlight(&mut self, backlight: State) {
+        self.sender.set_backlight(backlight).await;
+        self.state.set_backlight(backlight);
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_backlight(&self) -> State {
+        self.state.get_backlight()
+    }
+
+    /// write a byte to current position
+    pub async fn write_u8_to_cur(&mut self, byte: u8) {
+        assert!(
+            self.state.get_ram_type() == RAMType::DDRam,
+            "Current in CGRAM, use .set_cursor_pos() to change to DDRAM"
+        );
+
+        self.send_command(CommandSet::WriteDataToRAM(byte)).await;
+        self.record_shadow(self.get_cursor_pos(), byte);
+
+        // AC of LCD will automaticlly increase, mimic it in state
+        let raw_pos = self
+            .state
+            .next_cursor_pos(self.get_cursor_pos(), self.state.get_direction());
+        self.set_cursor_pos(raw_pos).await;
+
+        // in display shift entry mode, display content moves opposite to the writing direction
+        if self.state.get_shift_type() == ShiftType::CursorAndDisplay {
+            let content_dir = match self.state.get_direction() {
+                MoveDirection::LeftToRight => MoveDirection::RightToLeft,
+                MoveDirection::RightToLeft => MoveDirection::LeftToRight,
+            };
+            self.state
+                .shift_cursor_or_display(ShiftType::CursorAndDisplay, content_dir);
+        }
+    }
+
+    /// Get the bytes written to a row of DDRAM, see [`Basic::shadow_row()`](super::Basic::shadow_row)
+    pub fn shadow_row(&self, row: u8) -> &[u8] {
+        match self.shadow.as_ref() {
+            Some(shadow) => {
+                assert!(row < self.state.get_rows(), "Row out of range");
+                let base = self.state.pos_to_ddram_addr((0, row)) as usize;
+                &shadow[base..base + self.state.get_row_capacity(row) as usize]
+            }
+            None => &[],
+        }
+    }
+
+    /// write [char] to current position,
+    /// character not in current [`CharRom`](crate::charset::CharRom) will be displayed as its replacement byte
+    pub async fn write_char_to_cur(&mut self, char: char) {
+        let char_rom = self.state.get_char_rom();
+        let out_byte = char_rom
+            .map_char(char)
+            .unwrap_or(char_rom.replacement_byte());
+
+        self.write_u8_to_cur(out_byte).await;
+    }
+
+    /// write string to current position
+    pub async fn write_str_to_cur(&mut self, str: &str) {
+        for char in str.chars() {
+            self.write_char_to_cur(char).await;
+        }
+    }
+
+    /// write string to specific position
+    pub async fn write_str_to_pos(&mut self, str: &str, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
+        self.set_cursor_pos(pos).await;
+        self.write_str_to_cur(str).await;
+    }
+
+    /// Upload a custom graph into CGRAM, see [`Basic::write_graph_to_cgram()`](super::Basic::write_graph_to_cgram)
+    pub async fn write_graph_to_cgram(
+        &mut self,
+        index: impl Into<GlyphSlot>,
+        graph_data: &[u8; 8],
+    ) {
+        let index = index.into().index();
+        self.state.assert_glyph_index(index);
+
+        assert!(
+            graph_data.iter().all(|&line| line < 2u8.pow(5)),
+            "Only lower 5 bits use to construct display"
+        );
+
+        // CGRAM should be written from lower to upper
+        let direction_fliped = self.state.get_direction() == MoveDirection::RightToLeft;
+        if direction_fliped {
+            self.send_command(CommandSet::EntryModeSet(
+                MoveDirection::LeftToRight,
+                self.state.get_shift_type(),
+            ))
+            .await;
+        }
+
+        self.state.set_ram_type(RAMType::CGRam);
+        self.send_command(CommandSet::SetCGRAM(index << 3)).await;
+        for &line_data in graph_data {
+            self.send_command(CommandSet::WriteDataToRAM(line_data))
+                .await;
+        }
+
+        if direction_fliped {
+            self.send_command(CommandSet::EntryModeSet(
+                MoveDirection::RightToLeft,
+                self.state.get_shift_type(),
+            ))
+            .await;
+        }
+
+        self.state.set_cgram_graph(index, Some(*graph_data));
+    }
+
+    /// write a custom graph to current position
+    pub async fn write_graph_to_cur(&mut self, index: impl Into<GlyphSlot>) {
+        let index = index.into().index();
+        self.state.assert_glyph_index(index);
+        self.write_u8_to_cur(self.state.get_cgram_display_base() + index)
+            .await;
+    }
+
+    /// Clean the display, see [`Basic::clean_display()`](super::Basic::clean_display)
+    pub async fn clean_display(&mut self) {
+        let direction = self.state.get_direction();
+
+        self.send_command(CommandSet::ClearDisplay).await;
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow.fill(b' ');
+        }
+
+        // Clear Display moves cursor to home, resets display window, and resets direction of LCD to left to right
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos((0, 0));
+        self.state.set_display_offset(0);
+        self.state.set_direction(MoveDirection::LeftToRight);
+
+        if self.preserve_direction_on_clear {
+            self.set_direction(direction).await;
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub async fn return_home(&mut self) {
+        self.send_command(CommandSet::ReturnHome).await;
+        // Return Home moves cursor to home, and resets display window
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos((0, 0));
+        self.state.set_display_offset(0);
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_cursor_pos(&mut self, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos(pos);
+
+        let raw_pos = self.state.pos_to_ddram_addr(pos);
+
+        self.send_command(CommandSet::SetDDRAM(raw_pos)).await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_cursor_pos(&self) -> (u8, u8) {
+        self.state.get_cursor_pos()
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_display_state(&mut self, display: State) {
+        if self.skip_mode(self.get_display_state() == display) {
+            return;
+        }
+
+        self.state.set_display_state(display);
+        self.send_display_on_off().await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_display_state(&self) -> State {
+        self.state.get_display_state()
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_cursor_state(&mut self, cursor: State) {
+        if self.skip_mode(self.get_cursor_state() == cursor) {
+            return;
+        }
+
+        self.state.set_cursor_state(cursor);
+        self.send_display_on_off().await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_cursor_state(&self) -> State {
+        self.state.get_cursor_state()
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_cursor_blink_state(&mut self, blink: State) {
+        if self.skip_mode(self.get_cursor_blink_state() == blink) {
+            return;
+        }
+
+        self.state.set_cursor_blink(blink);
+        self.send_display_on_off().await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_cursor_blink_state(&self) -> State {
+        self.state.get_cursor_blink()
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_direction(&mut self, dir: MoveDirection) {
+        if self.skip_mode(self.get_direction() == dir) {
+            return;
+        }
+
+        self.state.set_direction(dir);
+        self.send_entry_mode().await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_direction(&self) -> MoveDirection {
+        self.state.get_direction()
+    }
+
+    #[allow(missing_docs)]
+    pub async fn set_shift_type(&mut self, shift: ShiftType) {
+        if self.skip_mode(self.get_shift_type() == shift) {
+            return;
+        }
+
+        self.state.set_shift_type(shift);
+        self.send_entry_mode().await;
+    }
+
+    #[allow(missing_docs)]
+    pub fn get_shift_type(&self) -> ShiftType {
+        self.state.get_shift_type()
+    }
+
+    /// toggle entire display on and off (it doesn't toggle backlight)
+    pub async fn toggle_display(&mut self) {
+        match self.get_display_state() {
+            State::Off => self.set_display_state(State::On).await,
+            State::On => self.set_display_state(State::Off).await,
+        }
+    }
+
+    #[allow(missing_docs)]
+    pub async fn delay_ms(&mut self, ms: u32) {
+        self.delayer.delay_ms(ms).await;
+    }
+
+    #[allow(missing_docs)]
+    pub async fn delay_us(&mut self, us: u32) {
+        self.delayer.delay_us(us).await;
+    }
+
+    /// Make the entire screen blink
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - the number of times to blink the screen. If the value is `0`, the screen will blink endless.
+    /// * `interval_us` - The interval (in microseconds) at which the screen state changes
+    pub async fn full_display_blink(&mut self, count: u32, interval_us: u32) {
+        let mut toggled = 0;
+        while count == 0 || toggled < count * 2 {
+            self.delay_us(interval_us).await;
+            self.toggle_display().await;
+            if count != 0 {
+                toggled += 1;
+            }
+        }
+    }
+
+    /// Typewriter-style display
+    ///
+    /// # Arguments
+    ///
+    /// * `str` - string to display
+    /// * `delay_us` - The interval (in microseconds) of each character show up
+    pub async fn typewriter_write(&mut self, str: &str, delay_us: u32) {
+        for char in str.chars() {
+            self.delay_us(delay_us).await;
+            self.write_char_to_cur(char).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use crate::{
+        command::{Command, CommandSet, Font, LineMode, MoveDirection, State},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender, SendCommand, SendCommandAsync},
+    };
+
+    use super::LcdAsync;
+
+    // the mock never pends, poll the future once
+    fn block_on<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("mock future should not pend"),
+        }
+    }
+
+    // run the blocking mock as an async sender
+    struct AsyncMock {
+        inner: MockSender,
+        can_read: bool,
+    }
+
+    impl SendCommandAsync<MockDelay> for AsyncMock {
+        async fn send(&mut self, command: Command) -> Option<u8> {
+            SendCommand::<MockDelay>::send(&mut self.inner, command)
+        }
+
+        fn can_read(&self) -> bool {
+            self.can_read
+        }
+    }
+
+    impl embedded_hal_async::delay::DelayNs for MockDelay {
+        async fn delay_ns(&mut self, ns: u32) {
+            embedded_hal::delay::DelayNs::delay_ns(self, ns);
+        }
+    }
+
+    fn new_sender() -> AsyncMock {
+        AsyncMock {
+            inner: MockSender::new(),
+            can_read: true,
+        }
+    }
+
+    fn count(sender: &AsyncMock, command: CommandSet) -> usize {
+        sender
+            .inner
+            .get_commands()
+            .iter()
+            .filter(|&&sent| sent == command.into())
+            .count()
+    }
+
+    #[test]
+    fn clean_display_follows_blocking_driver() {
+        let mut sender = new_sender();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default()
+            .set_shadow_contents(true)
+            .set_preserve_direction_on_clear(true);
+        let mut lcd = block_on(LcdAsync::new(&mut sender, &mut delayer, config, 0));
+        block_on(lcd.write_str_to_pos("hi", (3, 1)));
+        block_on(lcd.set_direction(MoveDirection::RightToLeft));
+        assert_eq!(&lcd.shadow_row(1)[3..5], b"hi");
+
+        block_on(lcd.clean_display());
+
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+        assert_eq!(lcd.get_direction(), MoveDirection::RightToLeft);
+        assert_eq!(&lcd.shadow_row(1)[3..5], b"  ");
+    }
+
+    #[test]
+    fn setters_skip_unchanged_modes() {
+        let mut sender = new_sender();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = block_on(LcdAsync::new(
+            &mut sender,
+            &mut delayer,
+            Config::default(),
+            0,
+        ));
+        lcd.sender.inner.clear_commands();
+        block_on(lcd.set_display_state(State::Off));
+        block_on(lcd.set_display_state(State::Off));
+
+        let display_off = CommandSet::DisplayOnOff {
+            display: State::Off,
+            cursor: lcd.get_cursor_state(),
+            cursor_blink: lcd.get_cursor_blink_state(),
+        };
+        assert_eq!(count(&sender, display_off), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn glyph_index_is_checked_with_font() {
+        let mut sender = new_sender();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default()
+            .set_line_mode(LineMode::OneLine)
+            .set_font(Font::Font5x11);
+        let mut lcd = block_on(LcdAsync::new(&mut sender, &mut delayer, config, 0));
+        block_on(lcd.write_graph_to_cgram(1, &[0; 8]));
+    }
+
+    #[test]
+    fn write_only_sender_is_not_polled() {
+        let mut sender = new_sender();
+        sender.can_read = false;
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = block_on(LcdAsync::new(
+            &mut sender,
+            &mut delayer,
+            Config::default(),
+            0,
+        ));
+        block_on(lcd.write_str_to_cur("hi"));
+
+        assert_eq!(count(&sender, CommandSet::ReadBusyFlagAndAddress), 0);
+    }
+
+    #[test]
+    fn initial_glyphs_are_uploaded_once() {
+        const GLYPHS: [[u8; 8]; 2] = [[0b10101; 8], [0b01010; 8]];
+
+        // Set CGRAM Address commands of both slots sent to `sender`
+        let cgram_addr_sets = |sender: &AsyncMock| {
+            [0, 1]
+                .into_iter()
+                .map(|index| count(sender, CommandSet::SetCGRAM(index << 3)))
+                .sum::<usize>()
+        };
+
+        let mut sender = new_sender();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_initial_glyphs(&GLYPHS);
+        let mut lcd = block_on(LcdAsync::new(&mut sender, &mut delayer, config, 0));
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+        block_on(lcd.write_str_to_cur("ab"));
+
+        assert_eq!(cgram_addr_sets(&sender), 2);
+        assert_eq!(sender.inner.get_cgram()[..8], GLYPHS[0]);
+        assert_eq!(sender.inner.get_cgram()[8..16], GLYPHS[1]);
+        assert_eq!(sender.inner.get_ddram()[..2], *b"ab");
+
+        // graphs known from a snapshot are not uploaded again
+        let mut mock = MockSender::new();
+        let config = Config::default().set_initial_glyphs(&GLYPHS);
+        let mut lcd = Lcd::new(&mut mock, &mut delayer, config, 0);
+        let config = lcd.snapshot_config(false).set_initial_glyphs(&GLYPHS);
+
+        let mut sender = new_sender();
+        block_on(LcdAsync::new(&mut sender, &mut delayer, config, 0));
+        assert_eq!(cgram_addr_sets(&sender), 0);
+    }
+}
@@ -0,0 +1,55 @@
+//! Track CGRAM slots in use, to share CGRAM between widgets and user graphs
+
+/// [`CgramAllocator`] tracks which of the 8 CGRAM slots are in use,
+/// thus helpers and user graphs won't overwrite each other's slots
+///
+/// It only does the bookkeeping, see [`Lcd::write_graph_alloc()`](super::Lcd::write_graph_alloc) to upload a graph into a free slot
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct CgramAllocator {
+    // bit n is set if slot n is in use
+    used: u8,
+}
+
+impl CgramAllocator {
+    /// Create a [`CgramAllocator`] with all slots free
+    pub const fn new() -> Self {
+        Self { used: 0 }
+    }
+
+    /// Take the first free slot, [`None`] if all slots are in use
+    pub fn alloc(&mut self) -> Option<u8> {
+        self.alloc_step(1)
+    }
+
+    /// Mark a slot as in use, e.g. a slot written directly by [`Basic::write_graph_to_cgram()`](super::Basic::write_graph_to_cgram)
+    pub fn reserve(&mut self, index: u8) {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+        self.used |= 1 << index;
+    }
+
+    /// Give a slot back, it's fine to free a slot not in use
+    pub fn free(&mut self, index: u8) {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+        self.used &= !(1 << index);
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_used(&self, index: u8) -> bool {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+        self.used & (1 << index) != 0
+    }
+
+    /// Get the count of free slots
+    pub fn free_count(&self) -> u8 {
+        8 - self.used.count_ones() as u8
+    }
+
+    // take the first free slot of every `step` slots (with 5x11 font, only even slots are valid)
+    pub(super) fn alloc_step(&mut self, step: u8) -> Option<u8> {
+        let index = (0..8)
+            .step_by(step as usize)
+            .find(|&index| !self.is_used(index))?;
+        self.reserve(index);
+        Some(index)
+    }
+}
@@ -0,0 +1,128 @@
+//! Arduino `LiquidCrystal` style method names, to ease porting from Arduino
+
+use crate::command::{MoveDirection, ShiftType, State};
+
+use super::Ext;
+
+/// Arduino `LiquidCrystal` style methods, map to methods of [`Basic`](super::Basic) and [`Ext`]
+pub trait LiquidCrystalCompat: Ext {
+    /// `LiquidCrystal::clear()`, clean the display and move cursor to (0, 0)
+    fn clear(&mut self) {
+        self.clean_display();
+        self.set_cursor_pos((0, 0));
+    }
+
+    /// `LiquidCrystal::home()`
+    fn home(&mut self) {
+        self.return_home();
+    }
+
+    /// `LiquidCrystal::setCursor()`
+    fn set_cursor(&mut self, col: u8, row: u8) {
+        self.set_cursor_pos((col, row));
+    }
+
+    /// `LiquidCrystal::print()`
+    fn print(&mut self, str: &str) {
+        self.write_str_to_cur(str);
+    }
+
+    /// `LiquidCrystal::write()`
+    fn write(&mut self, byte: u8) {
+        self.write_u8_to_cur(byte);
+    }
+
+    /// `LiquidCrystal::createChar()`
+    fn create_char(&mut self, location: u8, charmap: &[u8; 8]) {
+        self.write_graph_to_cgram(location, charmap);
+    }
+
+    /// `LiquidCrystal::cursor()`
+    fn cursor(&mut self) {
+        self.set_cursor_state(State::On);
+    }
+
+    /// `LiquidCrystal::noCursor()`
+    fn no_cursor(&mut self) {
+        self.set_cursor_state(State::Off);
+    }
+
+    /// `LiquidCrystal::blink()`
+    fn blink(&mut self) {
+        self.set_cursor_blink_state(State::On);
+    }
+
+    /// `LiquidCrystal::noBlink()`
+    fn no_blink(&mut self) {
+        self.set_cursor_blink_state(State::Off);
+    }
+
+    /// `LiquidCrystal::display()`
+    fn display(&mut self) {
+        self.set_display_state(State::On);
+    }
+
+    /// `LiquidCrystal::noDisplay()`
+    fn no_display(&mut self) {
+        self.set_display_state(State::Off);
+    }
+
+    /// `LiquidCrystal::scrollDisplayLeft()`
+    fn scroll_display_left(&mut self) {
+        self.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::RightToLeft);
+    }
+
+    /// `LiquidCrystal::scrollDisplayRight()`
+    fn scroll_display_right(&mut self) {
+        self.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::LeftToRight);
+    }
+
+    /// `LiquidCrystal::autoscroll()`
+    fn autoscroll(&mut self) {
+        self.enable_autoscroll();
+    }
+
+    /// `LiquidCrystal::noAutoscroll()`
+    fn no_autoscroll(&mut self) {
+        self.disable_autoscroll();
+    }
+
+    /// `LiquidCrystal::leftToRight()`
+    fn left_to_right(&mut self) {
+        self.set_direction(MoveDirection::LeftToRight);
+    }
+
+    /// `LiquidCrystal::rightToLeft()`
+    fn right_to_left(&mut self) {
+        self.set_direction(MoveDirection::RightToLeft);
+    }
+}
+
+impl<T: Ext> LiquidCrystalCompat for T {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::LiquidCrystalCompat;
+
+    const HEART: [u8; 8] = [
+        0b00000, 0b01010, 0b11111, 0b11111, 0b11111, 0b01110, 0b00100, 0b00000,
+    ];
+
+    #[test]
+    fn create_char_uploads_to_slot() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.create_char(1, &HEART);
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_cgram()[8..16], HEART);
+        assert!(sender.get_cgram()[..8].iter().all(|&line| line == 0));
+    }
+}
@@ -0,0 +1,94 @@
+//! The self-demo of [`Anim::run_demo()`](super::Anim::run_demo), the showcase of the examples
+
+use crate::command::{MoveDirection, ShiftType, State};
+
+use super::{Anim, FlipStyle, MoveStyle};
+
+// a heart shape
+const HEART: [u8; 8] = [
+    0b00000, 0b00000, 0b01010, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000,
+];
+
+// a diamond shape
+const DIAMOND: [u8; 8] = [
+    0b00000, 0b00100, 0b01110, 0b11111, 0b01110, 0b00100, 0b00000, 0b00000,
+];
+
+// CGRAM slots of the demo graphs, even slots also work with 5x11 font
+const HEART_SLOT: u8 = 0;
+const DIAMOND_SLOT: u8 = 2;
+
+pub(super) fn run_demo<L: Anim + ?Sized>(lcd: &mut L) {
+    let display = lcd.get_display_state();
+    let direction = lcd.get_direction();
+    let shift_type = lcd.get_shift_type();
+    let cursor = lcd.get_cursor_state();
+    let cursor_blink = lcd.get_cursor_blink_state();
+
+    let last_col = lcd.get_columns() - 1;
+    let last_row = lcd.get_rows() - 1;
+
+    lcd.clean_display();
+    lcd.set_cursor_pos((0, 0));
+    lcd.set_display_state(State::On);
+    lcd.set_direction(MoveDirection::LeftToRight);
+    lcd.set_shift_type(ShiftType::CursorOnly);
+
+    // custom graphs
+    lcd.write_graph_to_cgram(HEART_SLOT, &HEART);
+    lcd.write_graph_to_cgram(DIAMOND_SLOT, &DIAMOND);
+
+    // type writer effect
+    lcd.set_cursor_state(State::On);
+    lcd.set_cursor_blink_state(State::On);
+    lcd.set_cursor_pos((1, 0));
+    lcd.typewriter_write("hello,", 250_000);
+    lcd.offset_cursor_pos((1, 0));
+    lcd.write_str_to_cur("world!");
+    lcd.delay_ms(250);
+
+    // right to left writing, and the 2 types of split flap effect
+    lcd.set_cursor_blink_state(State::Off);
+    lcd.set_direction(MoveDirection::RightToLeft);
+    lcd.set_cursor_pos((last_col - 1, last_row));
+    lcd.typewriter_write("~!", 250_000);
+    lcd.split_flap_write("2061", FlipStyle::Simultaneous, None, 150_000, None);
+    lcd.split_flap_write(
+        "DCL",
+        FlipStyle::Sequential,
+        Some(10),
+        150_000,
+        Some(250_000),
+    );
+    lcd.set_direction(MoveDirection::LeftToRight);
+    lcd.set_cursor_state(State::Off);
+
+    // place the custom graphs at the right edge
+    lcd.delay_ms(1_000);
+    lcd.write_graph_to_pos(HEART_SLOT, (last_col, 0));
+    lcd.delay_ms(1_000);
+    lcd.write_graph_to_pos(DIAMOND_SLOT, (last_col, last_row));
+
+    // shift display window
+    let line_capacity = lcd.get_line_capacity();
+    lcd.delay_ms(1_000);
+    lcd.shift_display_to_pos(2, MoveStyle::Shortest, State::On, 250_000);
+    lcd.delay_ms(1_000);
+    lcd.shift_display_to_pos(line_capacity - 2, MoveStyle::Shortest, State::On, 250_000);
+    lcd.delay_ms(1_000);
+    lcd.shift_display_to_pos(0, MoveStyle::Shortest, State::On, 250_000);
+
+    // blink display 3 times
+    lcd.delay_ms(1_000);
+    lcd.full_display_blink(3, 500_000);
+    lcd.delay_ms(1_000);
+
+    // leave a clean display
+    lcd.clean_display();
+    lcd.set_cursor_pos((0, 0));
+    lcd.set_display_state(display);
+    lcd.set_direction(direction);
+    lcd.set_shift_type(shift_type);
+    lcd.set_cursor_state(cursor);
+    lcd.set_cursor_blink_state(cursor_blink);
+}
@@ -0,0 +1,191 @@
+//! Fallible (`try_*`) methods of [`Lcd`], hardware errors are returned rather than panicking
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+    command::{CommandSet, RAMType, State},
+    sender::TrySendCommand,
+};
+
+use super::{Basic, Lcd, LcdError, Pos, TimingMode};
+
+impl<'a, 'b, Sender, Delayer> Lcd<'a, 'b, Sender, Delayer>
+where
+    Sender: TrySendCommand<Delayer>,
+    Delayer: DelayNs,
+{
+    // fallible version of .send_command()
+    fn try_send_command(
+        &mut self,
+        command: CommandSet,
+    ) -> Result<Option<u8>, LcdError<Sender::Error>> {
+        match self.timing_mode {
+            TimingMode::BusyFlag if self.sender.can_read() => {
+                // estimate polling time with poll interval, since there is no clock
+                let mut polled_us: u32 = 0;
+                while self.sender.try_check_busy()? {
+                    if self
+                        .busy_fallback_after_us
+                        .is_some_and(|after_us| polled_us >= after_us)
+                    {
+                        // LCD is stuck in busy, stop polling for the rest of the session
+                        self.timing_mode = TimingMode::FixedDelay;
+                        self.busy_fallback_triggered = true;
+                        return self.try_send_command(command);
+                    }
+                    self.delayer.delay_us(self.poll_interval_us);
+                    polled_us = polled_us.saturating_add(self.poll_interval_us.max(1));
+                }
+                Ok(self.sender.try_send(command.into())?)
+            }
+            // a sender cannot read busy flag is treated as in fixed delay mode
+            TimingMode::BusyFlag | TimingMode::FixedDelay => {
+                let data = self.sender.try_send(command.into())?;
+                // we cannot read busy flag, wait for the worst case execution time
+                self.delayer.delay_us(command.get_execution_time_us());
+                Ok(data)
+            }
+        }
+    }
+
+    /// Fallible version of [`Basic::write_u8_to_cur()`]
+    pub fn try_write_u8_to_cur(&mut self, byte: u8) -> Result<(), LcdError<Sender::Error>> {
+        assert!(
+            self.get_ram_type() == RAMType::DDRam,
+            "Current in CGRAM, use .set_cursor_pos() to change to DDRAM"
+        );
+
+        self.try_send_command(CommandSet::WriteDataToRAM(byte))?;
+        self.step_after_write(byte);
+
+        // since RAM of UT7066U is looped, we need to mimic it
+        self.try_set_cursor_pos(self.get_cursor_pos())
+    }
+
+    /// Fallible version of [`Ext::write_char_to_cur()`](super::Ext::write_char_to_cur)
+    pub fn try_write_char_to_cur(&mut self, char: char) -> Result<(), LcdError<Sender::Error>> {
+        let char_rom = self.get_char_rom();
+        let out_byte = char_rom
+            .map_char(char)
+            .unwrap_or(char_rom.replacement_byte());
+
+        self.try_write_u8_to_cur(out_byte)
+    }
+
+    /// Fallible version of [`Ext::write_str_to_cur()`](super::Ext::write_str_to_cur),
+    /// it stops at the first error, characters before it are already written
+    pub fn try_write_str_to_cur(&mut self, str: &str) -> Result<(), LcdError<Sender::Error>> {
+        str.chars()
+            .try_for_each(|char| self.try_write_char_to_cur(char))
+    }
+
+    /// Fallible version of [`Ext::write_str_to_pos()`](super::Ext::write_str_to_pos)
+    pub fn try_write_str_to_pos(
+        &mut self,
+        str: &str,
+        pos: impl Into<Pos>,
+    ) -> Result<(), LcdError<Sender::Error>> {
+        self.try_set_cursor_pos(pos)?;
+        self.try_write_str_to_cur(str)
+    }
+
+    /// Fallible version of [`Basic::set_cursor_pos()`]
+    pub fn try_set_cursor_pos(
+        &mut self,
+        pos: impl Into<Pos>,
+    ) -> Result<(), LcdError<Sender::Error>> {
+        let pos: (u8, u8) = pos.into().into();
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos(pos);
+
+        let raw_pos = self.state.pos_to_ddram_addr(pos);
+
+        self.try_send_command(CommandSet::SetDDRAM(raw_pos))?;
+
+        Ok(())
+    }
+
+    /// Fallible version of [`Basic::clean_display()`]
+    pub fn try_clean_display(&mut self) -> Result<(), LcdError<Sender::Error>> {
+        let direction = self.get_direction();
+
+        self.try_send_command(CommandSet::ClearDisplay)?;
+        if let Some(direction) = self.after_clear_display(direction) {
+            self.try_send_command(CommandSet::EntryModeSet(direction, self.get_shift_type()))?;
+            self.state.set_direction(direction);
+        }
+        Ok(())
+    }
+
+    /// Fallible version of [`Basic::return_home()`]
+    pub fn try_return_home(&mut self) -> Result<(), LcdError<Sender::Error>> {
+        self.try_send_command(CommandSet::ReturnHome)?;
+        self.after_return_home();
+        Ok(())
+    }
+
+    /// Fallible version of [`Basic::set_backlight()`]
+    pub fn try_set_backlight(&mut self, backlight: State) -> Result<(), LcdError<Sender::Error>> {
+        self.sender.try_set_backlight(backlight)?;
+        self.state.set_backlight(backlight);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Basic, Config, Lcd, LcdError, Pos},
+        sender::{MockDelay, MockError, MockSender},
+    };
+
+    #[test]
+    fn try_write_str_stops_at_error() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.try_set_cursor_pos((0, 0)).unwrap();
+        // fail after the first byte is written
+        lcd.sender.fail_on_command(4);
+
+        assert_eq!(
+            lcd.try_write_str_to_cur("abc"),
+            Err(LcdError::Sender(MockError))
+        );
+        // cursor stays at the failed cell
+        assert_eq!(lcd.get_cursor_pos(), (1, 0));
+        lcd.try_write_str_to_cur("B").unwrap();
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.render::<16, 2>()[0][..3], ['a', 'B', ' ']);
+    }
+
+    #[test]
+    fn try_clean_display_error_keeps_state() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.try_write_str_to_pos("hi", (4, 1)).unwrap();
+        lcd.sender.fail_on_command(2);
+
+        assert!(lcd.try_clean_display().is_err());
+        assert_eq!(lcd.get_cursor_pos(), (6, 1));
+        lcd.try_clean_display().unwrap();
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+    }
+
+    #[test]
+    fn try_write_str_to_pos_accepts_pos() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.try_write_str_to_pos("a", Pos::new(3, 1)).unwrap();
+        lcd.try_write_str_to_pos("b", (4, 1)).unwrap();
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.render::<16, 2>()[1][3..5], ['a', 'b']);
+    }
+}
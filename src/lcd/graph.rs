@@ -0,0 +1,153 @@
+//! Graphs and helpers for bar graphs of [`Ext`](super::Ext)
+
+use super::Basic;
+
+use BigDigitCell::{Byte, Glyph};
+
+// pixel columns of a cell
+pub(crate) const CELL_COLUMNS: u8 = 5;
+
+// pixel rows of a cell (5x8 font)
+pub(super) const CELL_ROWS: u8 = 8;
+
+// a graph with left `columns` pixel columns filled
+pub(crate) fn column_fill_graph(columns: u8) -> [u8; 8] {
+    assert!(
+        columns <= CELL_COLUMNS,
+        "there are only 5 columns in a graph"
+    );
+
+    let line = !(0b11111u8 >> columns) & 0b11111;
+    [line; 8]
+}
+
+// a graph with bottom `rows` pixel rows filled
+pub(super) fn row_fill_graph(rows: u8) -> [u8; 8] {
+    assert!(rows <= CELL_ROWS, "there are only 8 rows in a graph");
+
+    let mut graph = [0u8; 8];
+    graph[(CELL_ROWS - rows) as usize..].fill(0b11111);
+    graph
+}
+
+// upload graphs into contiguous CGRAM slots from `base`, and keep cursor in DDRAM
+pub(super) fn upload_glyphs<L: Basic + ?Sized>(
+    lcd: &mut L,
+    base: u8,
+    graphs: impl ExactSizeIterator<Item = [u8; 8]>,
+) {
+    assert!(
+        base as usize + graphs.len() <= lcd.max_custom_glyphs() as usize,
+        "Not enough CGRAM slots for bar graphs"
+    );
+
+    // uploading CGRAM will move AC into CGRAM, move it back
+    let cursor_pos = lcd.is_in_ddram().then(|| lcd.get_cursor_pos());
+    graphs
+        .enumerate()
+        .for_each(|(offset, graph)| lcd.write_graph_to_cgram(base + offset as u8, &graph));
+    if let Some(cursor_pos) = cursor_pos {
+        lcd.set_cursor_pos(cursor_pos);
+    }
+}
+
+// find the CGRAM slot holding `graph`
+pub(super) fn find_glyph<L: Basic + ?Sized>(lcd: &L, graph: [u8; 8]) -> u8 {
+    (0..lcd.max_custom_glyphs())
+        .find(|&index| lcd.get_cgram_graph(index) == Some(graph))
+        .expect("Bar graphs are not uploaded")
+}
+
+// scale `value` in `0..=max` to `0..=steps`, round to the nearest step
+pub(crate) fn scale_to_steps(value: u8, max: u8, steps: u16) -> u16 {
+    assert!(max > 0, "max should be larger than 0");
+
+    let value = value.min(max) as u32;
+    ((value * steps as u32 * 2 + max as u32) / (max as u32 * 2)) as u16
+}
+
+// filled pixels of the `cell`th cell, for a bar with `filled` pixels in total
+pub(crate) fn cell_fill(filled: u16, cell: u8, pixels_per_cell: u8) -> u8 {
+    filled
+        .saturating_sub(cell as u16 * pixels_per_cell as u16)
+        .min(pixels_per_cell as u16) as u8
+}
+
+// building blocks of big digits, a big digit takes 3 columns and 2 rows
+pub(super) const BIG_DIGIT_GLYPHS: [[u8; 8]; 8] = [
+    // left top corner
+    [
+        0b00111, 0b01111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111,
+    ],
+    // upper bar
+    [
+        0b11111, 0b11111, 0b11111, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000,
+    ],
+    // right top corner
+    [
+        0b11100, 0b11110, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111,
+    ],
+    // left bottom corner
+    [
+        0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b01111, 0b00111,
+    ],
+    // lower bar
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111, 0b11111, 0b11111,
+    ],
+    // right bottom corner
+    [
+        0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11111, 0b11110, 0b11100,
+    ],
+    // upper and middle bars
+    [
+        0b11111, 0b11111, 0b11111, 0b00000, 0b00000, 0b00000, 0b11111, 0b11111,
+    ],
+    // middle and lower bars
+    [
+        0b11111, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111, 0b11111, 0b11111,
+    ],
+];
+
+// cells of a big digit, the CGRAM slot of a building block, or a byte in character ROM
+#[derive(Clone, Copy)]
+pub(super) enum BigDigitCell {
+    Glyph(u8),
+    Byte(u8),
+}
+
+// a full filled rectangle in character ROM
+const FULL: BigDigitCell = Byte(0xFF);
+const BLANK: BigDigitCell = Byte(b' ');
+
+// [top row, bottom row] of each digit
+pub(super) const BIG_DIGITS: [[[BigDigitCell; 3]; 2]; 10] = [
+    [
+        [Glyph(0), Glyph(1), Glyph(2)],
+        [Glyph(3), Glyph(4), Glyph(5)],
+    ],
+    [[Glyph(1), Glyph(2), BLANK], [Glyph(4), FULL, Glyph(4)]],
+    [
+        [Glyph(6), Glyph(6), Glyph(2)],
+        [Glyph(3), Glyph(4), Glyph(4)],
+    ],
+    [
+        [Glyph(6), Glyph(6), Glyph(2)],
+        [Glyph(7), Glyph(7), Glyph(5)],
+    ],
+    [[Glyph(3), Glyph(4), FULL], [BLANK, BLANK, FULL]],
+    [
+        [Glyph(3), Glyph(6), Glyph(6)],
+        [Glyph(7), Glyph(7), Glyph(5)],
+    ],
+    [
+        [Glyph(0), Glyph(6), Glyph(6)],
+        [Glyph(3), Glyph(4), Glyph(5)],
+    ],
+    [[Glyph(1), Glyph(1), Glyph(2)], [BLANK, BLANK, FULL]],
+    [
+        [Glyph(0), Glyph(6), Glyph(2)],
+        [Glyph(3), Glyph(4), Glyph(5)],
+    ],
+    [[Glyph(0), Glyph(6), Glyph(2)], [BLANK, BLANK, FULL]],
+];
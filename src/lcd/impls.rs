@@ -1,10 +1,218 @@
 use embedded_hal::delay::DelayNs;
 
-use crate::command::{DataWidth, Font, LineMode, MoveDirection, RAMType, ShiftType};
+use crate::charset::CharRom;
+use crate::command::{
+    Bits, Command, Font, LineMode, MoveDirection, RAMType, ReadWriteOp, RegisterSelection,
+    ShiftType,
+};
 use crate::sender::SendCommand;
 use crate::{command::CommandSet, lcd::State};
 
-use super::{Anim, Basic, Ext, Lcd};
+use super::{
+    init, Anim, BacklightLevel, Basic, Ext, GlyphSlot, Lcd, ModeCommand, PendingModes, Pos,
+    TimingMode, Timings, VerifyError, DIM_BACKLIGHT_CYCLES, DIM_BACKLIGHT_HALF_PERIOD_US,
+};
+
+impl<'a, 'b, Sender, Delayer> Lcd<'a, 'b, Sender, Delayer>
+where
+    Sender: SendCommand<Delayer>,
+    Delayer: DelayNs,
+{
+    // send a command to LCD, with respect to current timing mode
+    pub(super) fn send_command(&mut self, command: CommandSet) -> Option<u8> {
+        match self.timing_mode {
+            TimingMode::BusyFlag => match self.busy_fallback_after_us {
+                None => {
+                    self.sender
+                        .wait_and_send(command.into(), self.delayer, self.poll_interval_us)
+                }
+                // a sender cannot read busy flag always waits the worst case execution time, no fallback is needed
+                Some(_) if !self.sender.can_read() => {
+                    self.sender
+                        .wait_and_send(command.into(), self.delayer, self.poll_interval_us)
+                }
+                Some(after_us) => {
+                    // estimate polling time with poll interval, since there is no clock
+                    let mut polled_us: u32 = 0;
+                    while self.sender.check_busy() {
+                        if polled_us >= after_us {
+                            // LCD is stuck in busy, stop polling for the rest of the session
+                            self.timing_mode = TimingMode::FixedDelay;
+                            self.busy_fallback_triggered = true;
+                            return self.send_command(command);
+                        }
+                        self.delayer.delay_us(self.poll_interval_us);
+                        polled_us = polled_us.saturating_add(self.poll_interval_us.max(1));
+                    }
+                    self.sender.send(command.into())
+                }
+            },
+            TimingMode::FixedDelay => {
+                let data = self.sender.send(command.into());
+                // we cannot read busy flag, wait for the worst case execution time
+                self.delayer.delay_us(command.get_execution_time_us());
+                data
+            }
+        }
+    }
+
+    /// Whether LCD was stuck in busy, and the driver has fallen back to [`TimingMode::FixedDelay`],
+    /// see [`Config::set_busy_fallback_after_us()`](super::Config::set_busy_fallback_after_us)
+    pub fn is_busy_fallback_triggered(&self) -> bool {
+        self.busy_fallback_triggered
+    }
+
+    /// Upload a custom graph into the next free CGRAM slot (see [`CgramAllocator`](super::CgramAllocator)),
+    /// and return the slot index, [`None`] if all slots are in use
+    ///
+    /// The slot stays in use until [`Lcd::free_graph()`], cursor position is kept
+    pub fn write_graph_alloc(&mut self, graph_data: &[u8; 8]) -> Option<u8> {
+        // with 5x11 font, only even indices are valid
+        let slot_step = 8 / self.max_custom_glyphs();
+        let index = self.cgram_allocator.alloc_step(slot_step)?;
+
+        // uploading CGRAM will move AC into CGRAM, move it back
+        let cursor_pos = self.is_in_ddram().then(|| self.get_cursor_pos());
+        self.write_graph_to_cgram(index, graph_data);
+        if let Some(cursor_pos) = cursor_pos {
+            self.set_cursor_pos(cursor_pos);
+        }
+
+        Some(index)
+    }
+
+    /// Give back a CGRAM slot taken by [`Lcd::write_graph_alloc()`], the graph in CGRAM is kept
+    pub fn free_graph(&mut self, index: u8) {
+        self.cgram_allocator.free(index);
+    }
+
+    /// Get the [`CgramAllocator`](super::CgramAllocator) of the [`Lcd`],
+    /// e.g. to reserve slots used by [`Basic::write_graph_to_cgram()`] directly
+    pub fn get_cgram_allocator(&mut self) -> &mut super::CgramAllocator {
+        &mut self.cgram_allocator
+    }
+
+    // record a byte written to `pos` of DDRAM
+    pub(super) fn record_shadow(&mut self, pos: (u8, u8), byte: u8) {
+        let addr = self.state.pos_to_ddram_addr(pos);
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow[addr as usize] = byte;
+        }
+    }
+
+    // clean display fills DDRAM with white space
+    pub(super) fn clear_shadow(&mut self) {
+        if let Some(shadow) = self.shadow.as_mut() {
+            shadow.fill(b' ');
+        }
+    }
+
+    // update the state after writing `byte` to DDRAM, without sending command
+    pub(super) fn step_after_write(&mut self, byte: u8) {
+        self.record_shadow(self.get_cursor_pos(), byte);
+
+        // since AC of UT7066U will automaticlly increase, we only need to update LCD struct
+        let raw_pos = self
+            .state
+            .next_cursor_pos(self.get_cursor_pos(), self.get_direction());
+        self.state.set_cursor_pos(raw_pos);
+
+        // in display shift entry mode, display content moves opposite to the writing direction
+        if self.get_shift_type() == ShiftType::CursorAndDisplay {
+            let content_dir = match self.get_direction() {
+                MoveDirection::LeftToRight => MoveDirection::RightToLeft,
+                MoveDirection::RightToLeft => MoveDirection::LeftToRight,
+            };
+            self.state
+                .shift_cursor_or_display(ShiftType::CursorAndDisplay, content_dir);
+        }
+    }
+
+    // Return Home (and Clear Display) moves cursor to home, and resets display window
+    pub(super) fn after_return_home(&mut self) {
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos((0, 0));
+        self.state.set_display_offset(0);
+    }
+
+    // Clear Display also fills DDRAM with white space, and resets direction of LCD to left to right,
+    // return the direction to set back, if it should be preserved
+    pub(super) fn after_clear_display(
+        &mut self,
+        direction: MoveDirection,
+    ) -> Option<MoveDirection> {
+        self.clear_shadow();
+        self.after_return_home();
+        self.state.set_direction(MoveDirection::LeftToRight);
+
+        (self.preserve_direction_on_clear && direction != MoveDirection::LeftToRight)
+            .then_some(direction)
+    }
+
+    // if DDRAM is write from right to left, then when we change to CGRAM, graph will be accessed from lower to upper
+    // we will change it to left to right during `f`, to make accessing correct
+    // (it's sent directly, since it should not be deferred by a batch)
+    pub(super) fn with_cgram_direction<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let direction_fliped = self.get_direction() == MoveDirection::RightToLeft;
+        if direction_fliped {
+            self.send_command(CommandSet::EntryModeSet(
+                MoveDirection::LeftToRight,
+                self.get_shift_type(),
+            ));
+        }
+
+        let result = f(self);
+
+        // if writing direction is changed, then change it back
+        if direction_fliped {
+            self.send_command(CommandSet::EntryModeSet(
+                MoveDirection::RightToLeft,
+                self.get_shift_type(),
+            ));
+        }
+
+        result
+    }
+
+    // check whether a custom graph index is valid with current font
+    fn assert_glyph_index(&self, index: u8) {
+        self.state.assert_glyph_index(index);
+    }
+
+    // whether a mode setter should skip sending command, since the mode is `unchanged`
+    fn skip_mode(&self, unchanged: bool) -> bool {
+        unchanged && !self.force_mode_commands
+    }
+
+    // send a mode command built from current state, or defer it if in a batch
+    fn sync_mode(&mut self, mode: ModeCommand) {
+        match self.pending_modes.as_mut() {
+            Some(pending_modes) => match mode {
+                ModeCommand::FunctionSet => pending_modes.function_set = true,
+                ModeCommand::DisplayOnOff => pending_modes.display_on_off = true,
+                ModeCommand::EntryModeSet => pending_modes.entry_mode_set = true,
+            },
+            None => {
+                let command = match mode {
+                    ModeCommand::FunctionSet => CommandSet::FunctionSet(
+                        self.state.get_data_width(),
+                        self.get_line_mode(),
+                        self.get_font(),
+                    ),
+                    ModeCommand::DisplayOnOff => CommandSet::DisplayOnOff {
+                        display: self.get_display_state(),
+                        cursor: self.get_cursor_state(),
+                        cursor_blink: self.get_cursor_blink_state(),
+                    },
+                    ModeCommand::EntryModeSet => {
+                        CommandSet::EntryModeSet(self.get_direction(), self.get_shift_type())
+                    }
+                };
+                self.send_command(command);
+            }
+        }
+    }
+}
 
 impl<'a, 'b, Sender, Delayer> Basic for Lcd<'a, 'b, Sender, Delayer>
 where
@@ -16,18 +224,32 @@ where
         self.state.set_backlight(backlight);
     }
 
-    fn get_backlight(self) -> State {
+    fn set_backlight_level(&mut self, level: BacklightLevel) {
+        match level {
+            BacklightLevel::Off => self.set_backlight(State::Off),
+            BacklightLevel::On => self.set_backlight(State::On),
+            BacklightLevel::Dim => {
+                if !self.sender.dim_backlight() {
+                    // the backlight can only be on or off, toggle it for a while
+                    (0..DIM_BACKLIGHT_CYCLES).for_each(|_| {
+                        self.sender.set_backlight(State::Off);
+                        self.delayer.delay_us(DIM_BACKLIGHT_HALF_PERIOD_US);
+                        self.sender.set_backlight(State::On);
+                        self.delayer.delay_us(DIM_BACKLIGHT_HALF_PERIOD_US);
+                    });
+                }
+                self.state.set_backlight(State::On);
+            }
+        }
+    }
+
+    fn get_backlight(&self) -> State {
         self.state.get_backlight()
     }
 
     fn read_u8_from_cur(&mut self) -> u8 {
-        self.sender
-            .wait_and_send(
-                CommandSet::ReadDataFromRAM.into(),
-                self.delayer,
-                self.poll_interval_us,
-            )
-            .unwrap()
+        assert!(self.can_read(), "LCD cannot be read");
+        self.send_command(CommandSet::ReadDataFromRAM).unwrap()
     }
 
     fn write_u8_to_cur(&mut self, byte: u8) {
@@ -36,124 +258,222 @@ where
             "Current in CGRAM, use .set_cursor_pos() to change to DDRAM"
         );
 
-        self.sender.wait_and_send(
-            CommandSet::WriteDataToRAM(byte).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.send_command(CommandSet::WriteDataToRAM(byte));
+        self.step_after_write(byte);
 
-        // since AC of UT7066U will automaticlly increase, we only need to update LCD struct
         // since RAM of UT7066U is looped, we need to mimic it
-        let last_pos = self.get_cursor_pos();
-        let line_capacity = self.get_line_capacity();
-
-        let raw_pos = match self.get_direction() {
-            MoveDirection::RightToLeft => match self.get_line_mode() {
-                LineMode::OneLine => {
-                    if last_pos.0 == 0 {
-                        (line_capacity - 1, 0)
-                    } else {
-                        (last_pos.0 - 1, 0)
-                    }
-                }
-                LineMode::TwoLine => {
-                    if last_pos.0 == 0 {
-                        if last_pos.1 == 1 {
-                            (line_capacity - 1, 0)
-                        } else {
-                            (line_capacity - 1, 1)
-                        }
-                    } else {
-                        (last_pos.0 - 1, last_pos.1)
-                    }
-                }
-            },
-            MoveDirection::LeftToRight => match self.get_line_mode() {
-                LineMode::OneLine => {
-                    if last_pos.0 == line_capacity - 1 {
-                        (0, 0)
-                    } else {
-                        (last_pos.0 + 1, 0)
-                    }
+        self.set_cursor_pos(self.get_cursor_pos());
+    }
+
+    fn write_bytes_to_cur(&mut self, bytes: &[u8]) {
+        if !(self.batch_data_writes && self.sender.can_batch()) {
+            bytes.iter().for_each(|&byte| self.write_u8_to_cur(byte));
+            return;
+        }
+
+        assert!(
+            self.get_ram_type() == RAMType::DDRam,
+            "Current in CGRAM, use .set_cursor_pos() to change to DDRAM"
+        );
+
+        let mut rest = bytes;
+        while let Some(&first) = rest.first() {
+            // the first byte waits for LCD, like a single write
+            self.send_command(CommandSet::WriteDataToRAM(first));
+            let mut last_addr = self.state.pos_to_ddram_addr(self.get_cursor_pos());
+            self.step_after_write(first);
+
+            // AC of LCD only steps 1 address per write, the batch ends where cursor jumps (e.g. to the next row)
+            let mut commands = [Command::from(CommandSet::WriteDataToRAM(0)); 16];
+            let mut len = 0;
+            for &byte in rest[1..].iter().take(commands.len()) {
+                let addr = self.state.pos_to_ddram_addr(self.get_cursor_pos());
+                let stepped_addr = match self.get_direction() {
+                    MoveDirection::LeftToRight => last_addr.wrapping_add(1),
+                    MoveDirection::RightToLeft => last_addr.wrapping_sub(1),
+                };
+                if addr != stepped_addr {
+                    break;
                 }
-                LineMode::TwoLine => {
-                    if last_pos.0 == line_capacity - 1 {
-                        if last_pos.1 == 0 {
-                            (0, 1)
-                        } else {
-                            (0, 0)
-                        }
-                    } else {
-                        (last_pos.0 + 1, last_pos.1)
-                    }
+
+                commands[len] = CommandSet::WriteDataToRAM(byte).into();
+                len += 1;
+                last_addr = addr;
+                self.step_after_write(byte);
+            }
+
+            if len > 0 {
+                self.sender.send_batch(&commands[..len]);
+                if self.timing_mode == TimingMode::FixedDelay {
+                    self.delayer
+                        .delay_us(CommandSet::WriteDataToRAM(0).get_execution_time_us());
                 }
-            },
-        };
-        self.set_cursor_pos(raw_pos);
+            }
+
+            // since RAM of UT7066U is looped, we need to mimic it
+            self.set_cursor_pos(self.get_cursor_pos());
+
+            rest = &rest[1 + len..];
+        }
     }
 
-    fn write_graph_to_cgram(&mut self, index: u8, graph_data: &[u8; 8]) {
-        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+    fn write_graph_to_cgram(&mut self, index: impl Into<GlyphSlot>, graph_data: &[u8; 8]) {
+        let index = index.into().index();
+        self.assert_glyph_index(index);
 
         assert!(
             graph_data.iter().all(|&line| line < 2u8.pow(5)),
             "Only lower 5 bits use to construct display"
         );
 
-        // if DDRAM is write from right to left, then when we change to CGRAM, graph will write from lower to upper
-        // we will change it to left to right, to make writing correct
-        let mut direction_fliped = false;
-        if self.get_direction() == MoveDirection::RightToLeft {
-            self.set_direction(MoveDirection::LeftToRight);
-            direction_fliped = true;
-        }
+        let cgram_data_addr_start = index.checked_shl(3).unwrap();
+
+        self.with_cgram_direction(|lcd| {
+            lcd.set_cgram_addr(cgram_data_addr_start);
+            graph_data.iter().for_each(|&line_data| {
+                lcd.send_command(CommandSet::WriteDataToRAM(line_data));
+            });
+        });
+
+        self.state.set_cgram_graph(index, Some(*graph_data));
+    }
+
+    fn write_graph_to_cgram_5x11(&mut self, index: u8, graph_data: &[u8; 11]) {
+        assert!(
+            self.get_font() == Font::Font5x11,
+            "Only available with 5x11 font, use .write_graph_to_cgram() with 5x8 font"
+        );
+        assert!(index < 4, "Only 4 graphs allowed in CGRAM with 5x11 font");
+        assert!(
+            graph_data.iter().all(|&line| line < 2u8.pow(5)),
+            "Only lower 5 bits use to construct display"
+        );
+
+        // a graph takes 16 bytes, rows after the graph are filled with 0
+        let mut cgram_data = [0u8; 16];
+        cgram_data[..11].copy_from_slice(graph_data);
+
+        self.with_cgram_direction(|lcd| {
+            lcd.set_cgram_addr(index.checked_shl(4).unwrap());
+            cgram_data.iter().for_each(|&line_data| {
+                lcd.send_command(CommandSet::WriteDataToRAM(line_data));
+            });
+        });
+
+        // CGRAM state is tracked in 8 bytes slots
+        cgram_data
+            .chunks(8)
+            .zip(index * 2..)
+            .for_each(|(slot_data, slot)| {
+                self.state
+                    .set_cgram_graph(slot, Some(slot_data.try_into().unwrap()));
+            });
+    }
+
+    fn write_graph_to_cgram_verified(
+        &mut self,
+        index: impl Into<GlyphSlot>,
+        graph_data: &[u8; 8],
+    ) -> Result<(), VerifyError> {
+        let index = index.into().index();
+        self.write_graph_to_cgram(index, graph_data);
 
         let cgram_data_addr_start = index.checked_shl(3).unwrap();
 
-        self.set_cgram_addr(cgram_data_addr_start);
-        graph_data.iter().for_each(|&line_data| {
-            self.sender.wait_and_send(
-                CommandSet::WriteDataToRAM(line_data).into(),
-                self.delayer,
-                self.poll_interval_us,
-            );
+        let mut graph_read = [0u8; 8];
+        self.with_cgram_direction(|lcd| {
+            lcd.set_cgram_addr(cgram_data_addr_start);
+            graph_read
+                .iter_mut()
+                .for_each(|line| *line = lcd.read_u8_from_cur());
         });
 
-        // if writing direction is changed, then change it back
-        if direction_fliped {
-            self.set_direction(MoveDirection::RightToLeft)
+        // only lower 5 bits of a CGRAM row is meaningful
+        match graph_data
+            .iter()
+            .zip(graph_read.iter())
+            .position(|(&expected, &actual)| expected != actual & 0b1_1111)
+        {
+            None => Ok(()),
+            Some(row) => Err(VerifyError {
+                row: row as u8,
+                expected: graph_data[row],
+                actual: graph_read[row],
+            }),
         }
     }
 
-    fn write_graph_to_cur(&mut self, index: u8) {
+    fn shadow_row(&self, row: u8) -> &[u8] {
+        match self.shadow.as_ref() {
+            Some(shadow) => {
+                assert!(row < self.get_rows(), "Row out of range");
+                let base = self.state.pos_to_ddram_addr((0, row)) as usize;
+                &shadow[base..base + self.state.get_row_capacity(row) as usize]
+            }
+            None => &[],
+        }
+    }
+
+    fn get_cgram_graph(&self, index: u8) -> Option<[u8; 8]> {
         assert!(index < 8, "Only 8 graphs allowed in CGRAM");
-        self.write_u8_to_cur(index);
+        self.state.get_cgram_graph(index)
     }
 
-    fn clean_display(&mut self) {
-        self.sender.wait_and_send(
-            CommandSet::ClearDisplay.into(),
-            self.delayer,
-            self.poll_interval_us,
+    fn clear_cgram(&mut self) {
+        // with 5x11 font, a graph takes 2 slots, so clear the whole CGRAM byte by byte
+        self.with_cgram_direction(|lcd| {
+            lcd.set_cgram_addr(0);
+            (0..64).for_each(|_| {
+                lcd.send_command(CommandSet::WriteDataToRAM(0));
+            });
+        });
+        self.state.reset_cgram();
+        self.cgram_allocator = Default::default();
+    }
+
+    fn write_graph_to_cur(&mut self, index: impl Into<GlyphSlot>) {
+        let index = index.into().index();
+        self.assert_glyph_index(index);
+        self.write_u8_to_cur(self.state.get_cgram_display_base() + index);
+    }
+
+    fn write_cgram_row(&mut self, index: u8, row: u8, line_data: u8) {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+        assert!(row < 8, "Only 8 rows in a graph");
+        assert!(
+            line_data < 2u8.pow(5),
+            "Only lower 5 bits use to construct display"
         );
+
+        // only 1 byte to write, AC direction doesn't matter
+        self.set_cgram_addr(index.checked_shl(3).unwrap() + row);
+        self.send_command(CommandSet::WriteDataToRAM(line_data));
+
+        self.state.set_cgram_row(index, row, line_data);
+    }
+
+    fn clean_display(&mut self) {
+        let direction = self.get_direction();
+
+        self.send_command(CommandSet::ClearDisplay);
+        if let Some(direction) = self.after_clear_display(direction) {
+            self.set_direction(direction);
+        }
     }
 
     fn return_home(&mut self) {
-        self.sender.wait_and_send(
-            CommandSet::ReturnHome.into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.send_command(CommandSet::ReturnHome);
+        self.after_return_home();
     }
 
     fn set_line_mode(&mut self, line: LineMode) {
+        if self.skip_mode(self.get_line_mode() == line) {
+            return;
+        }
+
         self.state.set_line_mode(line);
 
-        self.sender.wait_and_send(
-            CommandSet::FunctionSet(DataWidth::Bit4, self.get_line_mode(), self.get_font()).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::FunctionSet);
     }
 
     fn get_line_mode(&self) -> LineMode {
@@ -161,47 +481,40 @@ where
     }
 
     fn set_font(&mut self, font: Font) {
+        if self.skip_mode(self.get_font() == font) {
+            return;
+        }
+
         self.state.set_font(font);
 
-        self.sender.wait_and_send(
-            CommandSet::FunctionSet(DataWidth::Bit4, self.get_line_mode(), self.get_font()).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::FunctionSet);
+    }
+    fn get_char_rom(&self) -> CharRom {
+        self.state.get_char_rom()
     }
     fn get_font(&self) -> Font {
         self.state.get_font()
     }
     fn set_display_state(&mut self, display: State) {
+        if self.skip_mode(self.get_display_state() == display) {
+            return;
+        }
+
         self.state.set_display_state(display);
 
-        self.sender.wait_and_send(
-            CommandSet::DisplayOnOff {
-                display: self.get_display_state(),
-                cursor: self.get_cursor_state(),
-                cursor_blink: self.get_cursor_blink_state(),
-            }
-            .into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::DisplayOnOff);
     }
     fn get_display_state(&self) -> State {
         self.state.get_display_state()
     }
     fn set_cursor_state(&mut self, cursor: State) {
+        if self.skip_mode(self.get_cursor_state() == cursor) {
+            return;
+        }
+
         self.state.set_cursor_state(cursor);
 
-        self.sender.wait_and_send(
-            CommandSet::DisplayOnOff {
-                display: self.get_display_state(),
-                cursor: self.get_cursor_state(),
-                cursor_blink: self.get_cursor_blink_state(),
-            }
-            .into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::DisplayOnOff);
     }
     fn get_cursor_state(&self) -> State {
         self.state.get_cursor_state()
@@ -210,70 +523,78 @@ where
         self.state.get_ram_type()
     }
     fn set_cursor_blink_state(&mut self, blink: State) {
+        if self.skip_mode(self.get_cursor_blink_state() == blink) {
+            return;
+        }
+
         self.state.set_cursor_blink(blink);
 
-        self.sender.wait_and_send(
-            CommandSet::DisplayOnOff {
-                display: self.get_display_state(),
-                cursor: self.get_cursor_state(),
-                cursor_blink: self.get_cursor_blink_state(),
-            }
-            .into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::DisplayOnOff);
     }
     fn get_cursor_blink_state(&self) -> State {
         self.state.get_cursor_blink()
     }
     fn set_direction(&mut self, dir: MoveDirection) {
+        if self.skip_mode(self.get_direction() == dir) {
+            return;
+        }
+
         self.state.set_direction(dir);
 
-        self.sender.wait_and_send(
-            CommandSet::EntryModeSet(self.get_direction(), self.get_shift_type()).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::EntryModeSet);
     }
     fn get_direction(&self) -> MoveDirection {
         self.state.get_direction()
     }
     fn set_shift_type(&mut self, shift: ShiftType) {
+        if self.skip_mode(self.get_shift_type() == shift) {
+            return;
+        }
+
         self.state.set_shift_type(shift);
 
-        self.sender.wait_and_send(
-            CommandSet::EntryModeSet(self.get_direction(), self.get_shift_type()).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.sync_mode(ModeCommand::EntryModeSet);
     }
     fn get_shift_type(&self) -> ShiftType {
         self.state.get_shift_type()
     }
-    fn set_cursor_pos(&mut self, pos: (u8, u8)) {
+    fn enable_autoscroll(&mut self) {
+        if self.skip_mode(
+            self.get_direction() == MoveDirection::LeftToRight
+                && self.get_shift_type() == ShiftType::CursorAndDisplay,
+        ) {
+            return;
+        }
+
+        self.state.set_direction(MoveDirection::LeftToRight);
+        self.state.set_shift_type(ShiftType::CursorAndDisplay);
+
+        self.sync_mode(ModeCommand::EntryModeSet);
+    }
+    fn disable_autoscroll(&mut self) {
+        self.set_shift_type(ShiftType::CursorOnly);
+    }
+    fn set_cursor_pos(&mut self, pos: impl Into<Pos>) {
+        let pos: (u8, u8) = pos.into().into();
         self.state.set_ram_type(RAMType::DDRam);
         self.state.set_cursor_pos(pos);
 
-        // in one line mode, pos.1 will always keep at 0
-        // in two line mode, the second line start at 0x40
-        let raw_pos: u8 = pos.1 * 0x40 + pos.0;
+        let raw_pos = self.state.pos_to_ddram_addr(pos);
 
-        self.sender.wait_and_send(
-            CommandSet::SetDDRAM(raw_pos).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.send_command(CommandSet::SetDDRAM(raw_pos));
     }
     fn set_cgram_addr(&mut self, addr: u8) {
         assert!(addr < 2u8.pow(6), "CGRAM Address overflow");
 
         self.state.set_ram_type(RAMType::CGRam);
 
-        self.sender.wait_and_send(
-            CommandSet::SetCGRAM(addr).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.send_command(CommandSet::SetCGRAM(addr));
+    }
+    fn set_ac(&mut self, addr: u8, ram: RAMType) {
+        match ram {
+            RAMType::DDRam => self.set_cursor_pos(self.state.ddram_addr_to_pos(addr)),
+            RAMType::CGRam => self.set_cgram_addr(addr),
+        }
     }
     fn get_cursor_pos(&self) -> (u8, u8) {
         self.state.get_cursor_pos()
@@ -281,16 +602,78 @@ where
     fn shift_cursor_or_display(&mut self, shift_type: ShiftType, dir: MoveDirection) {
         self.state.shift_cursor_or_display(shift_type, dir);
 
-        self.sender.wait_and_send(
-            CommandSet::CursorOrDisplayShift(shift_type, dir).into(),
-            self.delayer,
-            self.poll_interval_us,
-        );
+        self.send_command(CommandSet::CursorOrDisplayShift(shift_type, dir));
     }
     fn get_display_offset(&self) -> u8 {
         self.state.get_display_offset()
     }
 
+    fn begin_batch(&mut self) {
+        if self.pending_modes.is_none() {
+            self.pending_modes = Some(PendingModes::default());
+        }
+    }
+
+    fn commit_batch(&mut self) {
+        if let Some(pending_modes) = self.pending_modes.take() {
+            if pending_modes.function_set {
+                self.sync_mode(ModeCommand::FunctionSet);
+            }
+            if pending_modes.display_on_off {
+                self.sync_mode(ModeCommand::DisplayOnOff);
+            }
+            if pending_modes.entry_mode_set {
+                self.sync_mode(ModeCommand::EntryModeSet);
+            }
+        }
+    }
+
+    fn resync_modes(&mut self) {
+        self.sync_mode(ModeCommand::FunctionSet);
+        self.sync_mode(ModeCommand::DisplayOnOff);
+        self.sync_mode(ModeCommand::EntryModeSet);
+    }
+
+    fn set_clear_trailing(&mut self, clear: bool) {
+        self.clear_trailing = clear;
+    }
+
+    fn get_clear_trailing(&self) -> bool {
+        self.clear_trailing
+    }
+
+    fn get_timing_mode(&self) -> TimingMode {
+        self.timing_mode
+    }
+
+    fn timings(&self) -> Timings {
+        Timings {
+            timing_mode: self.timing_mode,
+            poll_interval_us: self.poll_interval_us,
+            busy_fallback_after_us: self.busy_fallback_after_us,
+            init_delay_us: init::init_delay_us(
+                &self.state,
+                self.resync_nibbles_on_init,
+                self.timing_mode,
+            ),
+            clear_display_us: CommandSet::ClearDisplay.get_execution_time_us(),
+            return_home_us: CommandSet::ReturnHome.get_execution_time_us(),
+            command_us: CommandSet::SetDDRAM(0).get_execution_time_us(),
+        }
+    }
+
+    fn can_read(&self) -> bool {
+        // in fixed delay mode, RW pin is supposed to be tied to ground
+        self.timing_mode == TimingMode::BusyFlag && self.sender.can_read()
+    }
+
+    fn is_ready(&mut self) -> bool {
+        match self.timing_mode {
+            TimingMode::BusyFlag if self.can_read() => !self.sender.check_busy(),
+            _ => true,
+        }
+    }
+
     fn set_poll_interval(&mut self, interval_us: u32) {
         self.poll_interval_us = interval_us;
     }
@@ -303,10 +686,34 @@ where
         self.state.get_line_capacity()
     }
 
+    fn get_row_capacity(&self, row: u8) -> u8 {
+        assert!(row < self.get_rows(), "Row out of range");
+        self.state.get_row_capacity(row)
+    }
+
+    fn get_columns(&self) -> u8 {
+        self.state.get_columns()
+    }
+
+    fn get_rows(&self) -> u8 {
+        self.state.get_rows()
+    }
+
     fn calculate_pos_by_offset(&self, start: (u8, u8), offset: (i8, i8)) -> (u8, u8) {
         self.state.calculate_pos_by_offset(start, offset)
     }
 
+    fn run_raw_sequence(&mut self, steps: &[(RegisterSelection, u8, u32)]) {
+        steps.iter().for_each(|&(register, byte, delay_us)| {
+            self.sender.send(Command::new(
+                register,
+                ReadWriteOp::Write,
+                Some(Bits::Bit8(byte)),
+            ));
+            self.delayer.delay_us(delay_us);
+        });
+    }
+
     fn delay_ms(&mut self, ms: u32) {
         self.delayer.delay_ms(ms);
     }
@@ -329,3 +736,23 @@ where
     Sender: SendCommand<Delayer>,
 {
 }
+
+// it makes `write!()` works on Lcd,
+// a '\n' moves cursor to the start of next row, and it wraps to home after the last row
+impl<'a, 'b, Sender, Delayer> core::fmt::Write for Lcd<'a, 'b, Sender, Delayer>
+where
+    Delayer: DelayNs,
+    Sender: SendCommand<Delayer>,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        s.split('\n').enumerate().for_each(|(index, line)| {
+            if index > 0 {
+                let next_row = (self.get_cursor_pos().1 + 1) % self.get_rows();
+                self.set_cursor_pos((0, next_row));
+            }
+            self.write_str_to_cur(line);
+        });
+
+        Ok(())
+    }
+}
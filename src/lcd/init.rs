@@ -1,16 +1,28 @@
 use embedded_hal::delay::DelayNs;
 
 use crate::{
+    charset::CharRom,
     command::{CommandSet, DataWidth, Font, LineMode, MoveDirection, RAMType, ShiftType, State},
-    lcd::Lcd,
-    sender::SendCommand,
+    lcd::{BacklightLevel, Basic, Lcd, Pos, TimingMode},
+    sender::{Contrast, RgbBacklight, SendCommand},
     state::LcdState,
 };
 
 /// [`Config`] is the init config of a [`Lcd`]
 #[derive(Default)]
 pub struct Config {
-    state: LcdState,
+    pub(super) state: LcdState,
+    pub(super) initial_glyphs: [[u8; 8]; 8],
+    pub(super) initial_glyph_cnt: u8,
+    pub(super) timing_mode: TimingMode,
+    pub(super) resync_nibbles_on_init: bool,
+    pub(super) busy_fallback_after_us: Option<u32>,
+    pub(super) shadow_contents: bool,
+    pub(super) clear_trailing: bool,
+    pub(super) force_mode_commands: bool,
+    pub(super) preserve_direction_on_clear: bool,
+    pub(super) batch_data_writes: bool,
+    pub(super) extended_instruction_set: bool,
 }
 
 #[allow(missing_docs)]
@@ -37,6 +49,11 @@ impl Config {
         self.state.get_line_mode()
     }
 
+    /// Set the controller's line mode bit in Function Set command
+    ///
+    /// Note:
+    /// [`LineMode`] is how the controller organizes DDRAM, it's not the physical rows of the display,
+    /// prefer [`Config::set_rows()`], which sets the line mode accordingly
     pub fn set_line_mode(mut self, line: LineMode) -> Self {
         self.state.set_line_mode(line);
         self
@@ -46,6 +63,75 @@ impl Config {
         self.state.get_line_capacity()
     }
 
+    pub fn get_columns(&self) -> u8 {
+        self.state.get_columns()
+    }
+
+    /// Set the visible column count of the display window, default to 16
+    pub fn set_columns(mut self, columns: u8) -> Self {
+        self.state.set_columns(columns);
+        self
+    }
+
+    pub fn get_rows(&self) -> u8 {
+        self.state.get_rows()
+    }
+
+    /// Set the physical row count of the display, it should be 1, 2 or 4, default to 2
+    ///
+    /// Note:
+    /// The controller only knows 1 line or 2 line ([`LineMode`]), and a 4 rows display (e.g. LCD2004)
+    /// is driven in [`LineMode::TwoLine`], with each line split into 2 rows.
+    /// This method sets [`LineMode::OneLine`] for 1 row, and [`LineMode::TwoLine`] for 2 or more rows.
+    ///
+    /// For 4 rows, the standard layout of row 0 to 3 starting at `0x00`, `0x40`, `columns`, `0x40 + columns`
+    /// (e.g. `0x00`, `0x40`, `0x14`, `0x54` for a 20x4 display) is set, it follows later [`Config::set_columns()`],
+    /// use [`Config::set_row_layout()`] for other layouts.
+    pub fn set_rows(mut self, rows: u8) -> Self {
+        self.state.set_rows(rows);
+        self
+    }
+
+    pub fn get_row_layout(&self) -> Option<&[(u8, u8)]> {
+        self.state.get_row_layout()
+    }
+
+    /// Set (DDRAM base address, usable columns) of each row, for modules with unusual DDRAM layout,
+    /// it also sets rows to `layout.len()`, rows should not overlap in DDRAM
+    ///
+    /// e.g. a standard LCD1602 is `&[(0x00, 40), (0x40, 40)]`
+    ///
+    /// Note:
+    /// Line mode is not changed, set [`LineMode::TwoLine`] if any row is at 0x40 or above.
+    /// [`Config::set_rows()`] will reset the layout to the standard one.
+    pub fn set_row_layout(mut self, layout: &[(u8, u8)]) -> Self {
+        self.state.set_row_layout(layout);
+        self
+    }
+
+    pub fn get_cgram_display_base(&self) -> u8 {
+        self.state.get_cgram_display_base()
+    }
+
+    /// Set the DDRAM byte of CGRAM graph 0, it should be 0x00 or 0x08, default to 0x00
+    ///
+    /// CGRAM graphs are mirrored at 0x00-0x07 and 0x08-0x0F,
+    /// use 0x08 if 0x00 is treated as end of string somewhere.
+    pub fn set_cgram_display_base(mut self, base: u8) -> Self {
+        self.state.set_cgram_display_base(base);
+        self
+    }
+
+    pub fn get_char_rom(&self) -> CharRom {
+        self.state.get_char_rom()
+    }
+
+    /// Set the character ROM of the LCD, default to [`CharRom::A00`]
+    pub fn set_char_rom(mut self, char_rom: CharRom) -> Self {
+        self.state.set_char_rom(char_rom);
+        self
+    }
+
     pub fn get_font(&self) -> Font {
         self.state.get_font()
     }
@@ -104,7 +190,8 @@ impl Config {
         self.state.get_cursor_pos()
     }
 
-    pub fn set_cursor_pos(mut self, pos: (u8, u8)) -> Self {
+    pub fn set_cursor_pos(mut self, pos: impl Into<Pos>) -> Self {
+        let pos: (u8, u8) = pos.into().into();
         self.state.set_cursor_pos(pos);
         self
     }
@@ -118,6 +205,148 @@ impl Config {
         self
     }
 
+    pub fn get_initial_glyphs(&self) -> &[[u8; 8]] {
+        &self.initial_glyphs[..self.initial_glyph_cnt as usize]
+    }
+
+    /// Set custom graphs to be uploaded into CGRAM slot 0 to `glyphs.len() - 1` at init
+    pub fn set_initial_glyphs(mut self, glyphs: &[[u8; 8]]) -> Self {
+        assert!(
+            (1..=8).contains(&glyphs.len()),
+            "Only 1 to 8 graphs allowed in CGRAM"
+        );
+
+        assert!(
+            glyphs.iter().flatten().all(|&line| line < 2u8.pow(5)),
+            "Only lower 5 bits use to construct display"
+        );
+
+        self.initial_glyphs[..glyphs.len()].copy_from_slice(glyphs);
+        self.initial_glyph_cnt = glyphs.len() as u8;
+        self
+    }
+
+    pub fn get_timing_mode(&self) -> TimingMode {
+        self.timing_mode
+    }
+
+    /// Set how the driver waits for LCD to finish a command, see [`TimingMode`]
+    pub fn set_timing_mode(mut self, timing_mode: TimingMode) -> Self {
+        self.timing_mode = timing_mode;
+        self
+    }
+
+    pub fn get_resync_nibbles_on_init(&self) -> bool {
+        self.resync_nibbles_on_init
+    }
+
+    /// Send three 0b0011 nibbles before 4 pin mode init, default to false
+    ///
+    /// Note:
+    /// If MCU is reset while LCD is in the middle of a 4 pin command (e.g. a warm reset),
+    /// LCD may be out of nibble phase, the three nibbles put LCD into 8 pin mode from any state,
+    /// then 4 pin mode init can be done correctly.
+    /// It has no effect in 8 pin mode.
+    pub fn set_resync_nibbles_on_init(mut self, resync: bool) -> Self {
+        self.resync_nibbles_on_init = resync;
+        self
+    }
+
+    pub fn get_busy_fallback_after_us(&self) -> Option<u32> {
+        self.busy_fallback_after_us
+    }
+
+    /// Give up busy flag polling if LCD keeps busy for `after_us` (in microseconds),
+    /// and switch to [`TimingMode::FixedDelay`] for the rest of the session, default to [`None`] (poll forever)
+    ///
+    /// Note:
+    /// It's for a borderline board (e.g. a floating RW pin) which reports busy forever,
+    /// the polling time is estimated by poll interval, so it should be longer than the longest command (1.52 ms).
+    pub fn set_busy_fallback_after_us(mut self, after_us: Option<u32>) -> Self {
+        self.busy_fallback_after_us = after_us;
+        self
+    }
+
+    pub fn get_shadow_contents(&self) -> bool {
+        self.shadow_contents
+    }
+
+    /// Keep a copy of every byte written to DDRAM in MCU's memory, default to false,
+    /// it can be read back by [`Basic::shadow_row()`] without reading LCD
+    ///
+    /// Note:
+    /// It takes 128 bytes of RAM, and it works with a sender that cannot read
+    pub fn set_shadow_contents(mut self, shadow: bool) -> Self {
+        self.shadow_contents = shadow;
+        self
+    }
+
+    pub fn get_clear_trailing(&self) -> bool {
+        self.clear_trailing
+    }
+
+    /// Fill the rest of the row in display window with white space in [`Ext::write_str_to_pos()`](crate::lcd::Ext::write_str_to_pos), default to false,
+    /// thus a shorter string won't leave characters of a longer one behind
+    pub fn set_clear_trailing(mut self, clear: bool) -> Self {
+        self.clear_trailing = clear;
+        self
+    }
+
+    pub fn get_force_mode_commands(&self) -> bool {
+        self.force_mode_commands
+    }
+
+    /// Always send the command in mode setters (e.g. [`Basic::set_direction()`]), default to false
+    ///
+    /// Note:
+    /// By default, a mode setter doesn't send command if the mode is unchanged,
+    /// force it if LCD may lose its mode (e.g. a noisy environment)
+    pub fn set_force_mode_commands(mut self, force: bool) -> Self {
+        self.force_mode_commands = force;
+        self
+    }
+
+    pub fn get_preserve_direction_on_clear(&self) -> bool {
+        self.preserve_direction_on_clear
+    }
+
+    /// Set direction back after [`Basic::clean_display()`], default to false
+    ///
+    /// Note:
+    /// Clear Display command resets direction of LCD to [`MoveDirection::LeftToRight`],
+    /// by default the driver follows it, set it to true to send the direction again if it was [`MoveDirection::RightToLeft`]
+    pub fn set_preserve_direction_on_clear(mut self, preserve: bool) -> Self {
+        self.preserve_direction_on_clear = preserve;
+        self
+    }
+
+    pub fn get_batch_data_writes(&self) -> bool {
+        self.batch_data_writes
+    }
+
+    /// Send consecutive bytes in [`Basic::write_bytes_to_cur()`] (and string writing) with [`SendCommand::send_batch()`],
+    /// default to false
+    ///
+    /// Note:
+    /// Busy flag is only checked before the first byte of a batch,
+    /// thus it only takes effect with a sender whose bus is slow enough that LCD finishes a write before the next one
+    /// (see [`SendCommand::can_batch()`]), bytes are written one by one with other senders
+    pub fn set_batch_data_writes(mut self, batch: bool) -> Self {
+        self.batch_data_writes = batch;
+        self
+    }
+
+    pub fn get_extended_instruction_set(&self) -> bool {
+        self.extended_instruction_set
+    }
+
+    /// Whether the controller has the extended instruction set of AIP31068 / ST7032 alike controllers, default to false,
+    /// [`Lcd::set_contrast()`] sends the contrast commands of extended instruction set if it's true
+    pub fn set_extended_instruction_set(mut self, extended: bool) -> Self {
+        self.extended_instruction_set = extended;
+        self
+    }
+
     pub fn get_ram_type(&self) -> RAMType {
         self.state.get_ram_type()
     }
@@ -140,90 +369,545 @@ where
         config: Config,
         poll_interval_us: u32,
     ) -> Self {
-        let state = config.state;
+        Self::new_with_init(sender, delayer, config, poll_interval_us, |_| {})
+    }
+
+    /// Create a [`Lcd`] driver, and init LCD hardware,
+    /// also return the total deliberate delay (in microseconds) issued by hardware init
+    ///
+    /// Note:
+    /// The delay is summed from the known delays (e.g. the 40 ms power on delay), rather than measured,
+    /// busy flag polling and uploading initial glyphs are not included.
+    pub fn new_timed(
+        sender: &'a mut Sender,
+        delayer: &'b mut Delayer,
+        config: Config,
+        poll_interval_us: u32,
+    ) -> (Self, u32) {
+        Self::new_with_init_timed(sender, delayer, config, poll_interval_us, |_| {})
+    }
+
+    /// Create a [`Lcd`] driver, init LCD hardware, then call `on_init` once with the [`Lcd`],
+    /// to finish custom setup (e.g. write a splash screen) in one construction call
+    pub fn new_with_init(
+        sender: &'a mut Sender,
+        delayer: &'b mut Delayer,
+        config: Config,
+        poll_interval_us: u32,
+        on_init: impl FnOnce(&mut Self),
+    ) -> Self {
+        Self::new_with_init_timed(sender, delayer, config, poll_interval_us, on_init).0
+    }
 
+    // create a Lcd, also return the total deliberate delay issued by hardware init
+    fn new_with_init_timed(
+        sender: &'a mut Sender,
+        delayer: &'b mut Delayer,
+        config: Config,
+        poll_interval_us: u32,
+        on_init: impl FnOnce(&mut Self),
+    ) -> (Self, u32) {
+        let mut lcd = Lcd {
+            sender,
+            delayer,
+            state: config.state,
+            poll_interval_us,
+            timing_mode: config.timing_mode,
+            resync_nibbles_on_init: config.resync_nibbles_on_init,
+            busy_fallback_after_us: config.busy_fallback_after_us,
+            busy_fallback_triggered: false,
+            cgram_allocator: Default::default(),
+            pending_modes: None,
+            shadow: config.shadow_contents.then_some([b' '; 0x80]),
+            clear_trailing: config.clear_trailing,
+            force_mode_commands: config.force_mode_commands,
+            preserve_direction_on_clear: config.preserve_direction_on_clear,
+            batch_data_writes: config.batch_data_writes,
+            extended_instruction_set: config.extended_instruction_set,
+        };
+
+        let init_delay_us = lcd.init_hardware();
+
+        if config.initial_glyph_cnt > 0 {
+            let cursor_pos = lcd.get_cursor_pos();
+
+            // graphs already in CGRAM (known from a snapshot config) won't be uploaded again
+            config.initial_glyphs[..config.initial_glyph_cnt as usize]
+                .iter()
+                .enumerate()
+                .for_each(|(index, graph_data)| {
+                    if lcd.state.get_cgram_graph(index as u8) != Some(*graph_data) {
+                        lcd.write_graph_to_cgram(index as u8, graph_data);
+                    }
+                });
+
+            // uploading CGRAM will move AC into CGRAM, move it back
+            lcd.set_cursor_pos(cursor_pos);
+        }
+
+        on_init(&mut lcd);
+
+        (lcd, init_delay_us)
+    }
+
+    /// Capture current state into a [`Config`], to rebuild a [`Lcd`] later (e.g. after the bus is shared with other devices)
+    ///
+    /// If `read_cgram` is true, CGRAM will be read back, so that the rebuilt [`Lcd`] knows all custom graphs,
+    /// and initial glyphs already in CGRAM won't be uploaded again.
+    ///
+    /// Note:
+    /// 1. Reading CGRAM requires LCD to be readable (RW pin connected, and not in [`TimingMode::FixedDelay`])
+    /// 2. The config is only valid for a warm rebuild, CGRAM is lost if LCD lost power
+    pub fn snapshot_config(&mut self, read_cgram: bool) -> Config {
+        if read_cgram {
+            let cursor_pos = self.is_in_ddram().then(|| self.get_cursor_pos());
+
+            let mut cgram = [[0u8; 8]; 8];
+            self.with_cgram_direction(|lcd| {
+                lcd.set_cgram_addr(0);
+                cgram
+                    .iter_mut()
+                    .flatten()
+                    .for_each(|line| *line = lcd.read_u8_from_cur() & 0b1_1111);
+            });
+
+            cgram.iter().enumerate().for_each(|(index, &graph)| {
+                self.state.set_cgram_graph(index as u8, Some(graph));
+            });
+
+            if let Some(cursor_pos) = cursor_pos {
+                self.set_cursor_pos(cursor_pos);
+            }
+        }
+
+        let mut state = self.state.clone();
+
+        // init will clean the display, which moves cursor to home, and resets display window
+        state.set_ram_type(RAMType::DDRam);
+        state.set_cursor_pos((0, 0));
+        state.set_display_offset(0);
+
+        Config {
+            state,
+            timing_mode: self.timing_mode,
+            resync_nibbles_on_init: self.resync_nibbles_on_init,
+            busy_fallback_after_us: self.busy_fallback_after_us,
+            shadow_contents: self.shadow.is_some(),
+            clear_trailing: self.clear_trailing,
+            force_mode_commands: self.force_mode_commands,
+            preserve_direction_on_clear: self.preserve_direction_on_clear,
+            batch_data_writes: self.batch_data_writes,
+            extended_instruction_set: self.extended_instruction_set,
+            ..Default::default()
+        }
+    }
+
+    /// Init LCD hardware again with current state, e.g. after LCD lost power
+    ///
+    /// Note:
+    /// Custom graphs in CGRAM will be treated as lost, and all CGRAM slots are freed, they should be uploaded again
+    pub fn reinit(&mut self) {
+        self.init_hardware();
+
+        // Clear Display will move cursor to home, and reset display window
+        self.state.set_ram_type(RAMType::DDRam);
+        self.state.set_cursor_pos((0, 0));
+        self.state.set_display_offset(0);
+        self.state.reset_cgram();
+        self.cgram_allocator = Default::default();
+    }
+
+    /// Consume the [`Lcd`], and get the sender and delayer back, e.g. to use the bus for another device,
+    /// no command is sent to LCD
+    ///
+    /// Note:
+    /// LCD keeps showing its content, call [`Lcd::snapshot_config()`] before release to keep the state
+    pub fn release(self) -> (&'a mut Sender, &'b mut Delayer) {
+        (self.sender, self.delayer)
+    }
+
+    /// Set the color of RGB backlight, see [`RgbBacklight`],
+    /// backlight state is [`State::Off`] if all channels are `0`
+    pub fn set_backlight_rgb(&mut self, r: u8, g: u8, b: u8)
+    where
+        Sender: RgbBacklight,
+    {
+        self.sender.set_backlight_rgb(r, g, b);
+        self.state.set_backlight(match (r, g, b) {
+            (0, 0, 0) => State::Off,
+            _ => State::On,
+        });
+    }
+
+    /// Set backlight to [`BacklightLevel`] with a RGB (PWM) backlight, see [`RgbBacklight`],
+    /// white at full duty for [`BacklightLevel::On`], and at half duty for [`BacklightLevel::Dim`]
+    pub fn set_backlight_level_rgb(&mut self, level: BacklightLevel)
+    where
+        Sender: RgbBacklight,
+    {
+        let duty = match level {
+            BacklightLevel::Off => 0,
+            BacklightLevel::Dim => 128,
+            BacklightLevel::On => 255,
+        };
+        self.set_backlight_rgb(duty, duty, duty);
+    }
+
+    /// Set contrast of LCD, `level` in `0..=63`
+    ///
+    /// With [`Config::set_extended_instruction_set()`] enabled, the contrast commands of extended instruction set are sent,
+    /// otherwise it's passed to [`Contrast::set_contrast()`] of the sender (which does nothing for built-in senders)
+    pub fn set_contrast(&mut self, level: u8)
+    where
+        Sender: Contrast,
+    {
+        assert!(level < 64, "contrast level should be less than 64");
+
+        if !self.extended_instruction_set {
+            self.sender.set_contrast(level);
+            return;
+        }
+
+        let data_width = self.state.get_data_width();
+        self.send_command(CommandSet::ExtendedFunctionSet(
+            data_width,
+            self.get_line_mode(),
+        ));
+        self.send_command(CommandSet::ContrastSet(level));
+        self.send_command(CommandSet::PowerIconContrastSet(level));
+        // back to normal instruction set
+        self.send_command(CommandSet::FunctionSet(
+            data_width,
+            self.get_line_mode(),
+            self.get_font(),
+        ));
+
+        // a controller without extended instruction set takes the commands as Set CGRAM Address, set AC back
+        if self.get_ram_type() == RAMType::DDRam {
+            self.set_cursor_pos(self.get_cursor_pos());
+        }
+    }
+
+    fn init_hardware(&mut self) -> u32 {
         // in initialization process, we'd better use "raw command", to strictly follow datasheet
+        let raw_commands = init_raw_commands(&self.state, self.resync_nibbles_on_init);
+
+        let mut total_delay_us = raw_commands
+            .iter()
+            .flatten()
+            .map(|&(command, delay_us)| {
+                self.sender
+                    .delay_and_send(command.into(), self.delayer, delay_us);
+                delay_us
+            })
+            .sum();
+
+        // in fixed delay mode, we cannot poll busy flag, wait for the last Function Set to finish
+        if self.timing_mode == TimingMode::FixedDelay {
+            self.delayer.delay_us(40);
+            total_delay_us += 40;
+        }
 
-        // only first 2 or 3 commands are different between 4 pin and 8 pin mode
-        match state.get_data_width() {
-            DataWidth::Bit4 => {
-                sender.delay_and_send(CommandSet::HalfFunctionSet.into(), delayer, 40_000);
+        let mode_commands = init_mode_commands(&self.state);
 
-                sender.delay_and_send(
-                    CommandSet::FunctionSet(
-                        DataWidth::Bit4,
-                        state.get_line_mode(),
-                        state.get_font(),
-                    )
-                    .into(),
-                    delayer,
-                    40,
-                );
-
-                sender.delay_and_send(
-                    CommandSet::FunctionSet(
-                        DataWidth::Bit4,
-                        state.get_line_mode(),
-                        state.get_font(),
-                    )
-                    .into(),
-                    delayer,
-                    40,
-                );
+        mode_commands.iter().for_each(|&command| {
+            self.send_command(command);
+
+            // in busy flag mode, LCD is polled rather than delayed
+            if self.timing_mode == TimingMode::FixedDelay {
+                total_delay_us += command.get_execution_time_us();
             }
+        });
 
-            DataWidth::Bit8 => {
-                sender.delay_and_send(
-                    CommandSet::FunctionSet(
-                        DataWidth::Bit8,
-                        state.get_line_mode(),
-                        state.get_font(),
-                    )
-                    .into(),
-                    delayer,
-                    40_000,
-                );
-
-                sender.delay_and_send(
-                    CommandSet::FunctionSet(
-                        DataWidth::Bit8,
-                        state.get_line_mode(),
-                        state.get_font(),
-                    )
-                    .into(),
-                    delayer,
-                    40,
-                );
+        // set backlight after LCD init
+        self.sender.set_backlight(self.state.get_backlight());
+
+        // init cleans the display
+        self.clear_shadow();
+
+        total_delay_us
+    }
+}
+
+// the raw commands (with the delay before each of them) to init LCD hardware,
+// shared by blocking and async driver
+pub(super) fn init_raw_commands(
+    state: &LcdState,
+    resync_nibbles_on_init: bool,
+) -> [Option<(CommandSet, u32)>; 6] {
+    let line_mode = state.get_line_mode();
+    let font = state.get_font();
+
+    // (command, delay before the command)
+    let mut raw_commands: [Option<(CommandSet, u32)>; 6] = [None; 6];
+
+    // only first 2 or 3 commands are different between 4 pin and 8 pin mode
+    match state.get_data_width() {
+        DataWidth::Bit4 => {
+            // delay before first command is 40 ms
+            let mut half_function_set_delay_us = 40_000;
+
+            // datasheet "initializing by instruction": 0b0011 three times, wait > 4.1 ms, > 100 us, then 0b0010
+            if resync_nibbles_on_init {
+                raw_commands[0] = Some((CommandSet::ResyncNibble, 40_000));
+                raw_commands[1] = Some((CommandSet::ResyncNibble, 4_100));
+                raw_commands[2] = Some((CommandSet::ResyncNibble, 100));
+                half_function_set_delay_us = 100;
             }
+
+            raw_commands[3] = Some((CommandSet::HalfFunctionSet, half_function_set_delay_us));
+            raw_commands[4] = Some((
+                CommandSet::FunctionSet(DataWidth::Bit4, line_mode, font),
+                40,
+            ));
+            raw_commands[5] = Some((
+                CommandSet::FunctionSet(DataWidth::Bit4, line_mode, font),
+                40,
+            ));
         }
 
-        sender.wait_and_send(
-            CommandSet::DisplayOnOff {
-                display: state.get_display_state(),
-                cursor: state.get_cursor_state(),
-                cursor_blink: state.get_cursor_blink(),
-            }
-            .into(),
-            delayer,
-            poll_interval_us,
-        );
+        DataWidth::Bit8 => {
+            raw_commands[0] = Some((
+                CommandSet::FunctionSet(DataWidth::Bit8, line_mode, font),
+                40_000,
+            ));
+            raw_commands[1] = Some((
+                CommandSet::FunctionSet(DataWidth::Bit8, line_mode, font),
+                40,
+            ));
+        }
+    }
 
-        sender.wait_and_send(CommandSet::ClearDisplay.into(), delayer, poll_interval_us);
+    raw_commands
+}
 
-        sender.wait_and_send(
-            CommandSet::EntryModeSet(state.get_direction(), state.get_shift_type()).into(),
-            delayer,
-            poll_interval_us,
+// the total deliberate delay of hardware init, it should match the delay issued by `init_hardware()`
+pub(super) fn init_delay_us(
+    state: &LcdState,
+    resync_nibbles_on_init: bool,
+    timing_mode: TimingMode,
+) -> u32 {
+    let raw_delay_us: u32 = init_raw_commands(state, resync_nibbles_on_init)
+        .iter()
+        .flatten()
+        .map(|&(_, delay_us)| delay_us)
+        .sum();
+
+    match timing_mode {
+        TimingMode::BusyFlag => raw_delay_us,
+        TimingMode::FixedDelay => {
+            raw_delay_us
+                + 40
+                + init_mode_commands(state)
+                    .iter()
+                    .map(|command| command.get_execution_time_us())
+                    .sum::<u32>()
+        }
+    }
+}
+
+// the mode commands to send after Function Set in init process
+pub(super) fn init_mode_commands(state: &LcdState) -> [CommandSet; 3] {
+    [
+        CommandSet::DisplayOnOff {
+            display: state.get_display_state(),
+            cursor: state.get_cursor_state(),
+            cursor_blink: state.get_cursor_blink(),
+        },
+        CommandSet::ClearDisplay,
+        CommandSet::EntryModeSet(state.get_direction(), state.get_shift_type()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{Bits, DataWidth, LineMode, RegisterSelection},
+        lcd::{Basic, Config, Ext, Lcd, TimingMode},
+        sender::{MockDelay, MockSender},
+    };
+
+    const GLYPH: [u8; 8] = [0b11111; 8];
+
+    #[test]
+    fn initial_glyphs_leave_other_slots_untouched() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_initial_glyphs(&[GLYPH; 3]);
+        let lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        assert_eq!(lcd.get_cgram_graph(2), Some(GLYPH));
+        assert_eq!(lcd.get_cgram_graph(3), None);
+        let (sender, _) = lcd.release();
+
+        let cgram = sender.get_cgram();
+        assert!(cgram[..3 * 8].iter().all(|&line| line == 0b11111));
+        assert!(cgram[3 * 8..].iter().all(|&line| line == 0));
+    }
+
+    #[test]
+    fn four_rows_use_two_line_function_set() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default()
+            .set_line_mode(LineMode::OneLine)
+            .set_columns(20)
+            .set_rows(4);
+        let lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        assert_eq!(lcd.get_rows(), 4);
+        assert_eq!(lcd.get_line_mode(), LineMode::TwoLine);
+        let (sender, _) = lcd.release();
+
+        let last_function_set = sender
+            .get_commands()
+            .iter()
+            .filter(|command| command.get_register_selection() == RegisterSelection::Command)
+            .filter_map(|command| match command.get_data() {
+                Some(Bits::Bit8(data @ 0b0010_0000..=0b0011_1111)) => Some(data),
+                _ => None,
+            })
+            .next_back()
+            .unwrap();
+        // N bit of Function Set is 2 line
+        assert_eq!(last_function_set & 0b0000_1000, 0b0000_1000);
+    }
+
+    #[test]
+    fn on_init_runs_once_after_init_commands() {
+        let mut plain_sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+        Lcd::new(&mut plain_sender, &mut delayer, Config::default(), 0);
+        let init_commands = plain_sender.get_commands();
+
+        let mut sender = MockSender::new();
+        let mut calls = 0;
+        Lcd::new_with_init(&mut sender, &mut delayer, Config::default(), 0, |lcd| {
+            calls += 1;
+            lcd.write_u8_to_cur(b'S');
+        });
+
+        assert_eq!(calls, 1);
+        let (before, after) = sender.get_commands().split_at(init_commands.len());
+        assert_eq!(before, init_commands);
+        assert!(after.iter().any(|command| {
+            command.get_register_selection() == RegisterSelection::Data
+                && command.get_data() == Some(Bits::Bit8(b'S'))
+        }));
+    }
+
+    #[test]
+    fn resync_nibbles_are_prepended_to_4_bit_init() {
+        // the first 4 commands, and all delays (in ns) of init
+        let init_of = |resync: bool| {
+            let mut sender = MockSender::new();
+            let mut delayer = MockDelay::new();
+
+            let config = Config::default()
+                .set_data_width(DataWidth::Bit4)
+                .set_resync_nibbles_on_init(resync);
+            Lcd::new(&mut sender, &mut delayer, config, 0);
+
+            let nibbles: [Option<Bits>; 4] =
+                core::array::from_fn(|index| sender.get_commands()[index].get_data());
+            (nibbles, delayer.get_delays_ns().to_vec())
+        };
+
+        let (nibbles, delays_ns) = init_of(true);
+        assert_eq!(
+            nibbles,
+            [
+                Some(Bits::Bit4(0b0011)),
+                Some(Bits::Bit4(0b0011)),
+                Some(Bits::Bit4(0b0011)),
+                Some(Bits::Bit4(0b0010)),
+            ]
         );
+        assert_eq!(delays_ns[..4], [40_000_000, 4_100_000, 100_000, 100_000]);
 
-        // set backlight after LCD init
-        sender.set_backlight(state.get_backlight());
+        let (nibbles, delays_ns) = init_of(false);
+        assert_eq!(nibbles[0], Some(Bits::Bit4(0b0010)));
+        assert_eq!(delays_ns[0], 40_000_000);
+    }
 
-        Lcd {
-            sender,
-            delayer,
-            state,
-            poll_interval_us,
+    #[test]
+    fn new_timed_reports_init_delays() {
+        for timing_mode in [TimingMode::BusyFlag, TimingMode::FixedDelay] {
+            for data_width in [DataWidth::Bit4, DataWidth::Bit8] {
+                let mut sender = MockSender::new();
+                let mut delayer = MockDelay::new();
+
+                let config = Config::default()
+                    .set_timing_mode(timing_mode)
+                    .set_data_width(data_width);
+                let (_, total_us) = Lcd::new_timed(&mut sender, &mut delayer, config, 0);
+
+                // all delays issued by init are deliberate, since the mock is never busy
+                assert_eq!(total_us as u64 * 1_000, delayer.get_total_ns());
+            }
         }
+
+        // 8 bit Function Set twice, 40 ms before the first one, and 40 us before the second one
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+        let config = Config::default().set_data_width(DataWidth::Bit8);
+        let (_, total_us) = Lcd::new_timed(&mut sender, &mut delayer, config, 0);
+        assert_eq!(total_us, 40_000 + 40);
+    }
+
+    #[test]
+    fn snapshot_rebuild_skips_uploaded_glyphs() {
+        const GLYPHS: [[u8; 8]; 2] = [[0b10101; 8], [0b01010; 8]];
+
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_graph_to_cgram(0, &GLYPHS[0]);
+        lcd.write_graph_to_cgram(1, &GLYPHS[1]);
+        let config = lcd.snapshot_config(true).set_initial_glyphs(&GLYPHS);
+        let (sender, delayer) = lcd.release();
+
+        sender.clear_commands();
+        let lcd = Lcd::new(sender, delayer, config, 0);
+        assert_eq!(lcd.get_cgram_graph(1), Some(GLYPHS[1]));
+
+        let cgram_addr_sets = lcd
+            .sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Command
+                    && matches!(
+                        command.get_data(),
+                        Some(Bits::Bit8(0b0100_0000..=0b0111_1111))
+                    )
+            })
+            .count();
+        assert_eq!(cgram_addr_sets, 0);
+        assert_eq!(lcd.sender.get_cgram()[8..16], GLYPHS[1]);
+    }
+
+    #[test]
+    fn custom_row_layout_addresses_rows() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_row_layout(&[(0x00, 16), (0x40, 16)]);
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        assert_eq!(lcd.get_rows(), 2);
+        assert_eq!(lcd.get_row_capacity(1), 16);
+        lcd.write_str_to_pos("ab", (14, 1));
+        lcd.write_byte_to_pos(b'c', (15, 0));
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[0x4E..0x50], *b"ab");
+        assert_eq!(sender.get_ddram()[0x0F], b'c');
+    }
+
+    #[test]
+    #[should_panic(expected = "rows should not overlap in DDRAM")]
+    fn overlapped_row_layout_panics() {
+        Config::default().set_row_layout(&[(0x00, 20), (0x10, 20)]);
     }
 }
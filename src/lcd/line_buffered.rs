@@ -0,0 +1,145 @@
+//! Buffer text per line, and write a whole line at once, e.g. for log output
+
+use super::Ext;
+
+// max columns a row can have
+const MAX_COLUMNS: usize = 40;
+
+/// [`LineBufferedLcd`] keeps characters in a line buffer, and writes the whole line on `'\n'`
+/// or when the line is full, then moves to the next row (wrap to the first row after the last one)
+///
+/// A line is written with one cursor set and a burst of bytes, padded with white space to the width of display window,
+/// thus the row doesn't flicker in the middle of a line.
+/// Enable [`Config::set_batch_data_writes()`](super::Config::set_batch_data_writes) to send the bytes back to back.
+pub struct LineBufferedLcd<L: Ext> {
+    lcd: L,
+    line: [u8; MAX_COLUMNS],
+    len: u8,
+    row: u8,
+}
+
+impl<L: Ext> LineBufferedLcd<L> {
+    /// Create a [`LineBufferedLcd`], the first line goes to the first row
+    pub fn new(lcd: L) -> Self {
+        assert!(
+            lcd.get_columns() as usize <= MAX_COLUMNS,
+            "Only 40 columns allowed in a row"
+        );
+
+        Self {
+            lcd,
+            line: [b' '; MAX_COLUMNS],
+            len: 0,
+            row: 0,
+        }
+    }
+
+    /// Put a character into the line buffer, `'\n'` writes current line and moves to the next row
+    pub fn write_char(&mut self, char: char) {
+        if char == '\n' {
+            self.end_line();
+            return;
+        }
+
+        let char_rom = self.lcd.get_char_rom();
+        self.line[self.len as usize] = char_rom
+            .map_char(char)
+            .unwrap_or(char_rom.replacement_byte());
+        self.len += 1;
+
+        if self.len == self.lcd.get_columns() {
+            self.end_line();
+        }
+    }
+
+    /// Put characters of a string into the line buffer, see [`LineBufferedLcd::write_char()`]
+    pub fn write_str(&mut self, str: &str) {
+        str.chars().for_each(|char| self.write_char(char));
+    }
+
+    /// Write the unfinished line to current row, and stay on the row,
+    /// the following characters are appended to the same line
+    pub fn flush(&mut self) {
+        let columns = self.lcd.get_columns();
+        let pos = self.lcd.visible_to_ddram(0, self.row);
+
+        self.lcd.set_cursor_pos(pos);
+        self.lcd.write_bytes_to_cur(&self.line[..columns as usize]);
+    }
+
+    /// Get the row current line will be written to
+    pub fn get_row(&self) -> u8 {
+        self.row
+    }
+
+    /// Flush the unfinished line, and get the LCD back
+    pub fn into_inner(mut self) -> L {
+        if self.len > 0 {
+            self.flush();
+        }
+        self.lcd
+    }
+
+    // write current line, then start a new line on the next row
+    fn end_line(&mut self) {
+        self.flush();
+
+        self.line = [b' '; MAX_COLUMNS];
+        self.len = 0;
+        self.row = (self.row + 1) % self.lcd.get_rows();
+    }
+}
+
+impl<L: Ext> core::fmt::Write for LineBufferedLcd<L> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        LineBufferedLcd::write_str(self, s);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{Bits, ReadWriteOp, RegisterSelection},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::LineBufferedLcd;
+
+    #[test]
+    fn newline_writes_line_in_one_burst() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let config = Config::default().set_batch_data_writes(true);
+        let lcd = Lcd::new(&mut sender, &mut delayer, config, 0);
+        lcd.sender.clear_commands();
+        let mut buffered = LineBufferedLcd::new(lcd);
+
+        buffered.write_str("abc");
+        assert!(buffered.lcd.sender.get_commands().is_empty());
+        buffered.write_str("\n");
+        assert_eq!(buffered.get_row(), 1);
+
+        let commands = buffered.lcd.sender.get_commands();
+        let is_data_write = |index: &usize| {
+            commands[*index].get_register_selection() == RegisterSelection::Data
+                && commands[*index].get_read_write_op() == ReadWriteOp::Write
+        };
+        let first_write = (0..commands.len()).find(is_data_write).unwrap();
+        let last_write = (0..commands.len()).rfind(is_data_write).unwrap();
+
+        // one cursor set, then 16 bytes back to back, without waiting for LCD in between
+        let cursor_sets = commands[..first_write]
+            .iter()
+            .filter(|command| command.get_data() == Some(Bits::Bit8(0b1000_0000)))
+            .count();
+        assert_eq!(cursor_sets, 1);
+        assert_eq!(last_write - first_write + 1, 16);
+        assert!((first_write..=last_write).all(|index| is_data_write(&index)));
+
+        let (sender, _) = buffered.into_inner().release();
+        assert_eq!(sender.get_ddram()[..4], *b"abc ");
+    }
+}
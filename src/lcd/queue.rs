@@ -0,0 +1,168 @@
+//! A fixed capacity queue of commands, to decouple producers (e.g. interrupt handlers) from the blocking bus
+
+use embedded_hal::delay::DelayNs;
+use heapless::Deque;
+
+use crate::{command::State, sender::SendCommand};
+
+use super::{Basic, GlyphSlot, Lcd};
+
+/// [`QueuedCommand`] is an operation waiting in a [`CommandQueue`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QueuedCommand {
+    /// Write a byte to current position
+    WriteByte(u8),
+    /// Write a byte to specific position
+    WriteByteToPos(u8, (u8, u8)),
+    /// Write a custom graph to current position
+    WriteGraph(GlyphSlot),
+    #[allow(missing_docs)]
+    SetCursorPos((u8, u8)),
+    #[allow(missing_docs)]
+    CleanDisplay,
+    #[allow(missing_docs)]
+    ReturnHome,
+    #[allow(missing_docs)]
+    SetDisplayState(State),
+    #[allow(missing_docs)]
+    SetCursorState(State),
+    #[allow(missing_docs)]
+    SetCursorBlinkState(State),
+    #[allow(missing_docs)]
+    SetBacklight(State),
+}
+
+/// [`CommandQueue`] holds up to `N` [`QueuedCommand`]s, which are sent later by [`Lcd::drain_queue()`]
+///
+/// Enqueueing doesn't touch the bus, so it can be done from an interrupt handler
+/// (with the queue guarded by a critical section), and drained from the main loop.
+pub struct CommandQueue<const N: usize> {
+    commands: Deque<QueuedCommand, N>,
+}
+
+impl<const N: usize> Default for CommandQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CommandQueue<N> {
+    /// Create an empty [`CommandQueue`]
+    pub const fn new() -> Self {
+        Self {
+            commands: Deque::new(),
+        }
+    }
+
+    /// Put a command at the end of the queue,
+    /// if the queue is full, the command is given back
+    pub fn enqueue(&mut self, command: QueuedCommand) -> Result<(), QueuedCommand> {
+        self.commands.push_back(command)
+    }
+
+    #[allow(missing_docs)]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_full(&self) -> bool {
+        self.commands.is_full()
+    }
+
+    /// Drop all queued commands
+    pub fn clear(&mut self) {
+        self.commands.clear()
+    }
+
+    fn dequeue(&mut self) -> Option<QueuedCommand> {
+        self.commands.pop_front()
+    }
+}
+
+impl<'a, 'b, Sender, Delayer> Lcd<'a, 'b, Sender, Delayer>
+where
+    Sender: SendCommand<Delayer>,
+    Delayer: DelayNs,
+{
+    /// Send up to `max` commands from the front of `queue`,
+    /// it stops early if the queue is empty, or LCD is not ready (see [`Basic::is_ready()`])
+    ///
+    /// Return the count of commands sent
+    pub fn drain_queue<const N: usize>(
+        &mut self,
+        queue: &mut CommandQueue<N>,
+        max: usize,
+    ) -> usize {
+        let mut sent = 0;
+
+        while sent < max && !queue.is_empty() && self.is_ready() {
+            match queue.dequeue().unwrap() {
+                QueuedCommand::WriteByte(byte) => self.write_u8_to_cur(byte),
+                QueuedCommand::WriteByteToPos(byte, pos) => {
+                    self.set_cursor_pos(pos);
+                    self.write_u8_to_cur(byte);
+                }
+                QueuedCommand::WriteGraph(index) => self.write_graph_to_cur(index),
+                QueuedCommand::SetCursorPos(pos) => self.set_cursor_pos(pos),
+                QueuedCommand::CleanDisplay => self.clean_display(),
+                QueuedCommand::ReturnHome => self.return_home(),
+                QueuedCommand::SetDisplayState(display) => self.set_display_state(display),
+                QueuedCommand::SetCursorState(cursor) => self.set_cursor_state(cursor),
+                QueuedCommand::SetCursorBlinkState(blink) => self.set_cursor_blink_state(blink),
+                QueuedCommand::SetBacklight(backlight) => self.set_backlight(backlight),
+            }
+
+            sent += 1;
+        }
+
+        sent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::{CommandQueue, QueuedCommand};
+
+    #[test]
+    fn drain_stops_at_max() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut queue = CommandQueue::<4>::new();
+        b"abc".iter().for_each(|&byte| {
+            queue.enqueue(QueuedCommand::WriteByte(byte)).unwrap();
+        });
+
+        assert_eq!(lcd.drain_queue(&mut queue, 2), 2);
+        assert_eq!(queue.len(), 1);
+        let (sender, _) = lcd.release();
+
+        assert_eq!(sender.get_ddram()[..3], *b"ab ");
+    }
+
+    #[test]
+    fn drain_waits_for_idle_lcd() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut queue = CommandQueue::<4>::new();
+        queue.enqueue(QueuedCommand::ReturnHome).unwrap();
+
+        lcd.sender.seed_status(0b1000_0000);
+        assert_eq!(lcd.drain_queue(&mut queue, 2), 0);
+        assert_eq!(queue.len(), 1);
+    }
+}
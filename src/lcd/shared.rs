@@ -0,0 +1,64 @@
+//! Share a LCD between interrupt handlers and tasks with a critical section
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+/// [`SharedLcd`] guards a LCD (e.g. a [`Lcd`](super::Lcd)) with a critical section,
+/// so it can be placed in a `static` and used from multiple tasks
+pub struct SharedLcd<L> {
+    lcd: Mutex<RefCell<L>>,
+}
+
+impl<L> SharedLcd<L> {
+    /// Create a [`SharedLcd`]
+    pub const fn new(lcd: L) -> Self {
+        Self {
+            lcd: Mutex::new(RefCell::new(lcd)),
+        }
+    }
+
+    /// Access the LCD in a critical section
+    ///
+    /// Note:
+    /// Interrupts are blocked while `f` is running, keep `f` short
+    /// (e.g. write a few characters, rather than running an animation).
+    ///
+    /// Calling [`SharedLcd::with()`] inside `f` on the same [`SharedLcd`] panics, since the LCD is already borrowed.
+    pub fn with<R>(&self, f: impl FnOnce(&mut L) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.lcd.borrow_ref_mut(cs)))
+    }
+
+    /// Consume the [`SharedLcd`], and get the LCD back
+    pub fn into_inner(self) -> L {
+        self.lcd.into_inner().into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Basic, Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::SharedLcd;
+
+    #[test]
+    fn with_gives_access_to_lcd() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let shared = SharedLcd::new(Lcd::new(&mut sender, &mut delayer, Config::default(), 0));
+        shared.with(|lcd| lcd.set_cursor_pos((3, 1)));
+        assert_eq!(shared.with(|lcd| lcd.get_cursor_pos()), (3, 1));
+        assert_eq!(shared.into_inner().get_cursor_pos(), (3, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "already")]
+    fn nested_with_panics() {
+        let shared = SharedLcd::new(0u8);
+        shared.with(|_| shared.with(|_| ()));
+    }
+}
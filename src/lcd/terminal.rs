@@ -0,0 +1,144 @@
+//! A tiny scrolling terminal, new lines push old lines up
+
+use heapless::String;
+
+use super::Ext;
+
+// max rows and columns a screen can have
+const MAX_ROWS: usize = 4;
+const MAX_COLUMNS: usize = 40;
+
+/// [`TerminalView`] keeps the last lines in a ring buffer of `heapless::String<N>`, one line per row,
+/// a new line goes to the row below the last line, or pushes all lines up one row when the screen is full
+///
+/// Lines longer than the width of display window (or `N` bytes) are wrapped into multiple lines.
+/// Only the cells whose content changed are rewritten, thus the screen doesn't flicker on scrolling.
+pub struct TerminalView<L: Ext, const N: usize> {
+    lcd: L,
+    lines: [String<N>; MAX_ROWS],
+    // index of the oldest line in `lines`
+    start: u8,
+    len: u8,
+    // bytes last drawn to each row, [`None`] if the row is not drawn yet
+    drawn: [Option<[u8; MAX_COLUMNS]>; MAX_ROWS],
+}
+
+impl<L: Ext, const N: usize> TerminalView<L, N> {
+    /// Create a [`TerminalView`] with no line, the first line goes to the first row
+    pub fn new(lcd: L) -> Self {
+        assert!(
+            lcd.get_rows() as usize <= MAX_ROWS,
+            "Only 4 rows allowed in a screen"
+        );
+        assert!(
+            lcd.get_columns() as usize <= MAX_COLUMNS,
+            "Only 40 columns allowed in a row"
+        );
+        assert!(N > 0, "A line should hold at least 1 byte");
+
+        Self {
+            lcd,
+            lines: Default::default(),
+            start: 0,
+            len: 0,
+            drawn: [None; MAX_ROWS],
+        }
+    }
+
+    /// Print a string as new lines, then redraw the screen
+    ///
+    /// The string is split into lines at each `'\n'`, and each line is wrapped to the width of display window.
+    pub fn println(&mut self, str: &str) {
+        let columns = self.lcd.get_columns() as usize;
+
+        str.split('\n').for_each(|segment| {
+            let mut line = String::new();
+            let mut width = 0;
+
+            segment.chars().for_each(|char| {
+                if width == columns || line.push(char).is_err() {
+                    self.push_line(core::mem::take(&mut line));
+                    width = 0;
+                    // a char longer than `N` bytes doesn't fit in any line, drop it
+                    if line.push(char).is_err() {
+                        return;
+                    }
+                }
+                width += 1;
+            });
+
+            self.push_line(line);
+        });
+
+        self.redraw();
+    }
+
+    /// Drop all lines, and blank the screen
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+        self.redraw();
+    }
+
+    /// Get the line shown on `row`, [`None`] if the row has no line yet
+    pub fn get_line(&self, row: u8) -> Option<&str> {
+        (row < self.len)
+            .then(|| self.lines[((self.start + row) % self.lcd.get_rows()) as usize].as_str())
+    }
+
+    /// Get the LCD back
+    pub fn into_inner(self) -> L {
+        self.lcd
+    }
+
+    // append a line, the oldest line is dropped when the screen is full
+    fn push_line(&mut self, line: String<N>) {
+        let rows = self.lcd.get_rows();
+
+        match self.len < rows {
+            true => {
+                self.lines[((self.start + self.len) % rows) as usize] = line;
+                self.len += 1;
+            }
+            false => {
+                self.lines[self.start as usize] = line;
+                self.start = (self.start + 1) % rows;
+            }
+        }
+    }
+
+    // rewrite the cells whose content differs from last drawn
+    fn redraw(&mut self) {
+        let columns = self.lcd.get_columns() as usize;
+        let char_rom = self.lcd.get_char_rom();
+
+        (0..self.lcd.get_rows()).for_each(|row| {
+            let mut bytes = [b' '; MAX_COLUMNS];
+            if let Some(line) = self.get_line(row) {
+                bytes.iter_mut().zip(line.chars()).for_each(|(byte, char)| {
+                    *byte = char_rom
+                        .map_char(char)
+                        .unwrap_or(char_rom.replacement_byte())
+                });
+            }
+
+            let drawn = self.drawn[row as usize];
+            // cursor moves to next cell after a write, only set cursor at the start of a run of changed cells
+            let mut cursor_at = None;
+            (0..columns).for_each(|col| {
+                if drawn.is_some_and(|drawn| drawn[col] == bytes[col]) {
+                    return;
+                }
+
+                if cursor_at != Some(col) {
+                    let pos = self.lcd.visible_to_ddram(col as u8, row);
+                    self.lcd.set_cursor_pos(pos);
+                }
+                self.lcd.write_u8_to_cur(bytes[col]);
+                cursor_at = Some(col + 1);
+            });
+
+            self.drawn[row as usize] = Some(bytes);
+        });
+    }
+}
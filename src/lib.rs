@@ -4,24 +4,33 @@
 Basic Usage:
 
 1. Initialize a "sender" <br/>
-    This crate include 2 drvier:
-    * 4-pin/8-pin parallel driver [`sender::ParallelSender`]
-    * I2C driver with a seperate adapter board [`sender::I2cSender`]
+   This crate include 3 drvier:
+   * 4-pin/8-pin parallel driver [`sender::ParallelSender`]
+   * I2C driver with a seperate adapter board [`sender::I2cSender`]
+   * SPI driver with a 74HC595 shift register board [`sender::SpiSender`]
 
-    You can choose either of it, or you can use any driver implemented [`sender::SendCommand`].
-<br/>
-<br/>
+   You can choose either of it, or you can use any driver implemented [`sender::SendCommand`].
+   <br/>
+   <br/>
 2. Use [`lcd::Lcd::new()`] to create a [`lcd::Lcd`], and initialize LCD1602 hardware
-<br/>
-<br/>
+   <br/>
+   <br/>
 3. use any methods provide by [`lcd::Lcd`] to control LCD1602
 */
 
 #![no_std]
 #![warn(missing_docs)]
 
+#[cfg(any(test, feature = "mock"))]
+extern crate alloc;
+
+pub mod charset;
 pub mod command;
 pub mod lcd;
 pub mod sender;
 mod state;
+#[cfg(feature = "test-util")]
+pub use state::LcdState;
+pub mod text;
 pub mod utils;
+pub mod widgets;
@@ -4,15 +4,59 @@
 use embedded_hal::delay::DelayNs;
 
 use crate::{
-    command::{Command, CommandSet, State},
+    command::{Bits, Command, CommandSet, RegisterSelection, State},
     utils::BitOps,
 };
 
+mod decorator;
 mod i2c_sender;
+#[cfg(feature = "async")]
+mod i2c_sender_async;
+#[cfg(any(test, feature = "mock"))]
+mod mock_sender;
 mod parallel_sender;
+mod pwm_backlight;
+mod spi_sender;
 
+pub use decorator::{Logging, Retrying, SenderExt, Throttled};
 pub use i2c_sender::I2cSender;
+#[cfg(feature = "async")]
+pub use i2c_sender_async::I2cSenderAsync;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_sender::{MockDelay, MockError, MockSender};
 pub use parallel_sender::ParallelSender;
+pub use pwm_backlight::PwmBacklight;
+pub use spi_sender::{ShiftRegisterPins, SpiSender};
+
+/// [`Status`] is the result of reading busy flag and Address Counter
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Status {
+    /// LCD is busy, it cannot accept next command
+    pub busy: bool,
+    /// Current Address Counter, it's a DDRAM or CGRAM address, depends on last address set command
+    pub address: u8,
+}
+
+impl Status {
+    // parse the byte read by Read Busy Flag and Address command
+    fn from_raw(raw_status: u8) -> Self {
+        use crate::utils::BitState;
+
+        Self {
+            busy: matches!(raw_status.check_bit(7), BitState::Set),
+            address: raw_status & 0b0111_1111,
+        }
+    }
+}
+
+// the worst case execution time of a command from datasheet (in microseconds)
+fn worst_execution_time_us(command: &Command) -> u32 {
+    match (command.get_register_selection(), command.get_data()) {
+        // Clear Display and Return Home
+        (RegisterSelection::Command, Some(Bits::Bit8(0b0000_0001..=0b0000_0011))) => 1_520,
+        _ => 37,
+    }
+}
 
 /// [`SendCommand`] is the trait a sender should implement to communicate with the hardware
 pub trait SendCommand<Delayer: DelayNs> {
@@ -20,6 +64,34 @@ pub trait SendCommand<Delayer: DelayNs> {
     /// and return the result value when [`Command`] is a [`ReadWriteOp::Read`](crate::command::ReadWriteOp::Read) command
     fn send(&mut self, command: Command) -> Option<u8>;
 
+    /// Whether the sender can read from LCD, default to true
+    ///
+    /// A sender that cannot read (e.g. RW pin tied to ground) should return false,
+    /// and [`Lcd`](crate::lcd::Lcd) should be used in [`TimingMode::FixedDelay`](crate::lcd::TimingMode::FixedDelay)
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    /// Whether data writes can be sent back to back with [`SendCommand::send_batch()`], default to false
+    ///
+    /// A sender should return true only if its bus is slow enough that LCD finishes a write before the next one arrives
+    /// (e.g. an I2C adapter board), otherwise [`Lcd`](crate::lcd::Lcd) writes bytes one by one even if
+    /// [`Config::set_batch_data_writes()`](crate::lcd::Config::set_batch_data_writes) is enabled
+    fn can_batch(&self) -> bool {
+        false
+    }
+
+    /// Send commands back to back, and return the result of the last command,
+    /// default to send them one by one, a sender may override it to send them in fewer bus transactions
+    ///
+    /// Note:
+    /// Busy flag is not checked and no delay is issued between commands,
+    /// only commands LCD finishes before the next one arrives (e.g. data writes on a slow bus) should be batched,
+    /// commands need a long wait (e.g. clear display and return home) must not be batched
+    fn send_batch(&mut self, commands: &[Command]) -> Option<u8> {
+        commands.iter().fold(None, |_, &command| self.send(command))
+    }
+
     /// Wait specific duration, and send command
     fn delay_and_send(
         &mut self,
@@ -32,12 +104,22 @@ pub trait SendCommand<Delayer: DelayNs> {
     }
 
     /// Check LCD busy state, when LCD is idle, send the command
+    ///
+    /// If the sender cannot read (see [`SendCommand::can_read()`]), busy flag is not read,
+    /// the command is sent, then the worst case execution time of the command is waited
+    /// (37 µs for most commands, 1.52 ms for clear display and return home)
     fn wait_and_send(
         &mut self,
         command: Command,
         delayer: &mut Delayer,
         poll_interval_us: u32,
     ) -> Option<u8> {
+        if !self.can_read() {
+            let data = self.send(command);
+            delayer.delay_us(worst_execution_time_us(&command));
+            return data;
+        }
+
         self.wait_for_idle(delayer, poll_interval_us);
         self.send(command)
     }
@@ -49,14 +131,23 @@ pub trait SendCommand<Delayer: DelayNs> {
         }
     }
 
+    /// Read busy flag and Address Counter of LCD in one read
+    fn read_status(&mut self) -> Status {
+        let raw_status = self
+            .send(CommandSet::ReadBusyFlagAndAddress.into())
+            .unwrap();
+
+        Status::from_raw(raw_status)
+    }
+
     /// Check LCD busy state
     fn check_busy(&mut self) -> bool {
-        use crate::utils::BitState;
+        self.read_status().busy
+    }
 
-        let busy_state = self
-            .send(CommandSet::ReadBusyFlagAndAddress.into())
-            .unwrap();
-        matches!(busy_state.check_bit(7), BitState::Set)
+    /// Read current Address Counter of LCD
+    fn read_address_counter(&mut self) -> u8 {
+        self.read_status().address
     }
 
     /// Get the current backlight
@@ -73,4 +164,163 @@ pub trait SendCommand<Delayer: DelayNs> {
     /// If a driver doesn't support change backlight, just silently bypass it
     #[allow(unused_variables)]
     fn set_backlight(&mut self, backlight: State) {}
+
+    /// Set the backlight to half brightness, return false if the driver cannot dim backlight by itself (default),
+    /// then [`Lcd`](crate::lcd::Lcd) dims it by toggling, see [`Basic::set_backlight_level()`](crate::lcd::Basic::set_backlight_level)
+    fn dim_backlight(&mut self) -> bool {
+        false
+    }
+}
+
+/// [`RgbBacklight`] is the optional trait for senders driving a RGB backlight, e.g. [`PwmBacklight`]
+pub trait RgbBacklight {
+    /// Set the color of backlight, each channel in `0..=255`
+    ///
+    /// Note:
+    /// If a driver doesn't support RGB backlight, just silently bypass it
+    #[allow(unused_variables)]
+    fn set_backlight_rgb(&mut self, r: u8, g: u8, b: u8) {}
+}
+
+/// [`Contrast`] is the optional trait for senders which can change contrast of LCD by themselves,
+/// e.g. with a digital potentiometer on V0 pin
+///
+/// Classic HD44780 has no contrast register, the default does nothing.
+/// For AIP31068 / ST7032 alike controllers, enable [`Config::set_extended_instruction_set()`](crate::lcd::Config::set_extended_instruction_set),
+/// [`Lcd::set_contrast()`](crate::lcd::Lcd::set_contrast) will send the extended instructions instead.
+pub trait Contrast {
+    /// Set contrast of LCD, `level` in `0..=63`
+    #[allow(unused_variables)]
+    fn set_contrast(&mut self, level: u8) {}
+}
+
+/// [`TrySendCommand`] is the fallible version of [`SendCommand`],
+/// errors of the hardware (pin or bus) are returned rather than panicking
+///
+/// It backs the `try_*` methods of [`Lcd`](crate::lcd::Lcd)
+pub trait TrySendCommand<Delayer: DelayNs>: SendCommand<Delayer> {
+    /// The error of the underlying hardware
+    type Error;
+
+    /// Fallible version of [`SendCommand::send()`]
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error>;
+
+    /// Fallible version of [`SendCommand::set_backlight()`]
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error>;
+
+    /// Fallible version of [`SendCommand::read_status()`]
+    fn try_read_status(&mut self) -> Result<Status, Self::Error> {
+        let raw_status = self
+            .try_send(CommandSet::ReadBusyFlagAndAddress.into())?
+            .unwrap();
+
+        Ok(Status::from_raw(raw_status))
+    }
+
+    /// Fallible version of [`SendCommand::check_busy()`]
+    fn try_check_busy(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.try_read_status()?.busy)
+    }
+}
+
+/// [`SendCommandAsync`] is the async version of [`SendCommand`], for senders driven by an async executor
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait SendCommandAsync<Delayer: embedded_hal_async::delay::DelayNs> {
+    /// Parse a [`Command`] and sending data to hardware,
+    /// and return the result value when [`Command`] is a [`ReadWriteOp::Read`](crate::command::ReadWriteOp::Read) command
+    async fn send(&mut self, command: Command) -> Option<u8>;
+
+    /// Whether the sender can read from LCD, default to true
+    fn can_read(&self) -> bool {
+        true
+    }
+
+    /// Wait specific duration, and send command
+    async fn delay_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        delay_us: u32,
+    ) -> Option<u8> {
+        delayer.delay_us(delay_us).await;
+        self.send(command).await
+    }
+
+    /// Check LCD busy state, when LCD is idle, send the command
+    ///
+    /// If the sender cannot read (see [`SendCommandAsync::can_read()`]), busy flag is not read,
+    /// the command is sent, then the worst case execution time of the command is waited
+    async fn wait_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        poll_interval_us: u32,
+    ) -> Option<u8> {
+        if !self.can_read() {
+            let data = self.send(command).await;
+            delayer.delay_us(worst_execution_time_us(&command)).await;
+            return data;
+        }
+
+        self.wait_for_idle(delayer, poll_interval_us).await;
+        self.send(command).await
+    }
+
+    /// Wait until LCD is idle, the executor is yielded to between polls
+    async fn wait_for_idle(&mut self, delayer: &mut Delayer, poll_interval_us: u32) {
+        while self.check_busy().await {
+            delayer.delay_us(poll_interval_us).await;
+        }
+    }
+
+    /// Read busy flag and Address Counter of LCD in one read
+    async fn read_status(&mut self) -> Status {
+        let raw_status = self
+            .send(CommandSet::ReadBusyFlagAndAddress.into())
+            .await
+            .unwrap();
+
+        Status::from_raw(raw_status)
+    }
+
+    /// Check LCD busy state
+    async fn check_busy(&mut self) -> bool {
+        self.read_status().await.busy
+    }
+
+    /// Get the current backlight
+    ///
+    /// Note:
+    /// If a driver doesn't support read backlight state, just silently bypass it
+    async fn get_backlight(&mut self) -> State {
+        State::default()
+    }
+
+    /// Set the backlight
+    ///
+    /// Note:
+    /// If a driver doesn't support change backlight, just silently bypass it
+    #[allow(unused_variables)]
+    async fn set_backlight(&mut self, backlight: State) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockDelay, MockSender, SendCommand, Status};
+
+    #[test]
+    fn read_status_splits_busy_and_address() {
+        let mut sender = MockSender::new();
+        sender.seed_status(0b1010_0101);
+
+        let status = SendCommand::<MockDelay>::read_status(&mut sender);
+        assert_eq!(
+            status,
+            Status {
+                busy: true,
+                address: 0x25
+            }
+        );
+    }
 }
@@ -0,0 +1,389 @@
+//! Decorators wrap a sender and add behavior to it, they are senders too, so they can be stacked
+//!
+//! Use [`SenderExt`] to stack them like iterator adapters, e.g. `sender.retrying(3).throttled(50).logging(f)`
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
+
+use crate::command::{Command, State};
+
+use super::{Contrast, I2cSender, ParallelSender, SendCommand, SpiSender, Status, TrySendCommand};
+
+/// [`SenderExt`] provides adapters to wrap a sender with decorators
+pub trait SenderExt: Sized {
+    /// Wrap the sender with [`Throttled`]
+    fn throttled(self, interval_us: u32) -> Throttled<Self> {
+        Throttled {
+            inner: self,
+            interval_us,
+        }
+    }
+
+    /// Wrap the sender with [`Retrying`]
+    fn retrying(self, retries: u8) -> Retrying<Self> {
+        Retrying {
+            inner: self,
+            retries,
+        }
+    }
+
+    /// Wrap the sender with [`Logging`]
+    fn logging<F: FnMut(&Command)>(self, log: F) -> Logging<Self, F> {
+        Logging { inner: self, log }
+    }
+}
+
+impl<I2cLcd, A> SenderExt for I2cSender<I2cLcd, A>
+where
+    I2cLcd: embedded_hal::i2c::I2c<A>,
+    A: embedded_hal::i2c::AddressMode + Clone,
+{
+}
+
+impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize> SenderExt
+    for ParallelSender<ControlPin, DBPin, BLPin, PIN_CNT>
+where
+    ControlPin: OutputPin,
+    DBPin: OutputPin + embedded_hal::digital::InputPin,
+    BLPin: OutputPin,
+{
+}
+
+impl<Spi: SpiDevice, LatchPin: OutputPin> SenderExt for SpiSender<Spi, LatchPin> {}
+
+impl<S> SenderExt for Throttled<S> {}
+
+impl<S> SenderExt for Retrying<S> {}
+
+impl<S, F: FnMut(&Command)> SenderExt for Logging<S, F> {}
+
+#[cfg(any(test, feature = "mock"))]
+impl SenderExt for super::MockSender {}
+
+/// [`Throttled`] waits an extra interval before each command, e.g. for a slow or long wired LCD
+///
+/// Note:
+/// The interval is only added to commands sent with a delayer (busy flag mode and initialization),
+/// in [`TimingMode::FixedDelay`](crate::lcd::TimingMode::FixedDelay) the [`Lcd`](crate::lcd::Lcd) waits by itself
+pub struct Throttled<S> {
+    inner: S,
+    interval_us: u32,
+}
+
+impl<S> Throttled<S> {
+    /// Create a [`Throttled`] sender
+    pub fn new(inner: S, interval_us: u32) -> Self {
+        Self { inner, interval_us }
+    }
+
+    /// Get the wrapped sender back
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: SendCommand<Delayer>, Delayer: DelayNs> SendCommand<Delayer> for Throttled<S> {
+    fn send(&mut self, command: Command) -> Option<u8> {
+        self.inner.send(command)
+    }
+
+    fn send_batch(&mut self, commands: &[Command]) -> Option<u8> {
+        self.inner.send_batch(commands)
+    }
+
+    fn can_batch(&self) -> bool {
+        self.inner.can_batch()
+    }
+
+    fn can_read(&self) -> bool {
+        self.inner.can_read()
+    }
+
+    fn delay_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        delay_us: u32,
+    ) -> Option<u8> {
+        delayer.delay_us(self.interval_us);
+        self.inner.delay_and_send(command, delayer, delay_us)
+    }
+
+    fn wait_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        poll_interval_us: u32,
+    ) -> Option<u8> {
+        delayer.delay_us(self.interval_us);
+        self.inner.wait_and_send(command, delayer, poll_interval_us)
+    }
+
+    fn read_status(&mut self) -> Status {
+        self.inner.read_status()
+    }
+
+    fn check_busy(&mut self) -> bool {
+        self.inner.check_busy()
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.inner.get_backlight()
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        self.inner.set_backlight(backlight)
+    }
+
+    fn dim_backlight(&mut self) -> bool {
+        self.inner.dim_backlight()
+    }
+}
+
+impl<S: Contrast> Contrast for Throttled<S> {
+    fn set_contrast(&mut self, level: u8) {
+        self.inner.set_contrast(level)
+    }
+}
+
+impl<S: TrySendCommand<Delayer>, Delayer: DelayNs> TrySendCommand<Delayer> for Throttled<S> {
+    type Error = S::Error;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        self.inner.try_send(command)
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        self.inner.try_set_backlight(backlight)
+    }
+}
+
+/// [`Retrying`] sends a command again if the hardware reports an error, up to `retries` times,
+/// it panics if the command still fails, like other senders do
+///
+/// The wrapped sender should implement [`TrySendCommand`]
+///
+/// Note:
+/// Commands are sent with [`TrySendCommand::try_send()`] of the wrapped sender,
+/// thus delays added by a wrapped [`Throttled`] are skipped, put [`Retrying`] inside [`Throttled`]
+pub struct Retrying<S> {
+    inner: S,
+    retries: u8,
+}
+
+impl<S> Retrying<S> {
+    /// Create a [`Retrying`] sender
+    pub fn new(inner: S, retries: u8) -> Self {
+        Self { inner, retries }
+    }
+
+    /// Get the wrapped sender back
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: TrySendCommand<Delayer>, Delayer: DelayNs> SendCommand<Delayer> for Retrying<S>
+where
+    S::Error: core::fmt::Debug,
+{
+    fn send(&mut self, command: Command) -> Option<u8> {
+        TrySendCommand::<Delayer>::try_send(self, command).unwrap()
+    }
+
+    fn can_read(&self) -> bool {
+        self.inner.can_read()
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.inner.get_backlight()
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        TrySendCommand::<Delayer>::try_set_backlight(self, backlight).unwrap()
+    }
+
+    fn dim_backlight(&mut self) -> bool {
+        self.inner.dim_backlight()
+    }
+}
+
+impl<S: Contrast> Contrast for Retrying<S> {
+    fn set_contrast(&mut self, level: u8) {
+        self.inner.set_contrast(level)
+    }
+}
+
+impl<S: TrySendCommand<Delayer>, Delayer: DelayNs> TrySendCommand<Delayer> for Retrying<S>
+where
+    Retrying<S>: SendCommand<Delayer>,
+{
+    type Error = S::Error;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        let mut result = self.inner.try_send(command);
+        for _ in 0..self.retries {
+            if result.is_ok() {
+                break;
+            }
+            result = self.inner.try_send(command);
+        }
+        result
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        let mut result = self.inner.try_set_backlight(backlight);
+        for _ in 0..self.retries {
+            if result.is_ok() {
+                break;
+            }
+            result = self.inner.try_set_backlight(backlight);
+        }
+        result
+    }
+}
+
+/// [`Logging`] calls `log` with each command before it's sent, e.g. to print commands for debugging
+///
+/// Note:
+/// Reads of busy flag issued by the wrapped sender itself (when waiting for LCD) are not logged
+pub struct Logging<S, F: FnMut(&Command)> {
+    inner: S,
+    log: F,
+}
+
+impl<S, F: FnMut(&Command)> Logging<S, F> {
+    /// Create a [`Logging`] sender
+    pub fn new(inner: S, log: F) -> Self {
+        Self { inner, log }
+    }
+
+    /// Get the wrapped sender back
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, F, Delayer> SendCommand<Delayer> for Logging<S, F>
+where
+    S: SendCommand<Delayer>,
+    F: FnMut(&Command),
+    Delayer: DelayNs,
+{
+    fn send(&mut self, command: Command) -> Option<u8> {
+        (self.log)(&command);
+        self.inner.send(command)
+    }
+
+    fn send_batch(&mut self, commands: &[Command]) -> Option<u8> {
+        commands.iter().for_each(|command| (self.log)(command));
+        self.inner.send_batch(commands)
+    }
+
+    fn can_batch(&self) -> bool {
+        self.inner.can_batch()
+    }
+
+    fn can_read(&self) -> bool {
+        self.inner.can_read()
+    }
+
+    fn delay_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        delay_us: u32,
+    ) -> Option<u8> {
+        (self.log)(&command);
+        self.inner.delay_and_send(command, delayer, delay_us)
+    }
+
+    fn wait_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        poll_interval_us: u32,
+    ) -> Option<u8> {
+        (self.log)(&command);
+        self.inner.wait_and_send(command, delayer, poll_interval_us)
+    }
+
+    fn read_status(&mut self) -> Status {
+        self.inner.read_status()
+    }
+
+    fn check_busy(&mut self) -> bool {
+        self.inner.check_busy()
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.inner.get_backlight()
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        self.inner.set_backlight(backlight)
+    }
+
+    fn dim_backlight(&mut self) -> bool {
+        self.inner.dim_backlight()
+    }
+}
+
+impl<S: Contrast, F: FnMut(&Command)> Contrast for Logging<S, F> {
+    fn set_contrast(&mut self, level: u8) {
+        self.inner.set_contrast(level)
+    }
+}
+
+impl<S, F, Delayer> TrySendCommand<Delayer> for Logging<S, F>
+where
+    S: TrySendCommand<Delayer>,
+    F: FnMut(&Command),
+    Delayer: DelayNs,
+{
+    type Error = S::Error;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        (self.log)(&command);
+        self.inner.try_send(command)
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        self.inner.try_set_backlight(backlight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::{
+        command::{Bits, CommandSet},
+        sender::{MockDelay, MockSender, SendCommand},
+    };
+
+    use super::SenderExt;
+
+    #[test]
+    fn chain_delegates_through_all_layers() {
+        let logged = Cell::new(0);
+        let mut mock = MockSender::new();
+        mock.fail_on_command(1);
+
+        let mut sender = mock
+            .retrying(2)
+            .throttled(5)
+            .logging(|_| logged.set(logged.get() + 1));
+        let mut delayer = MockDelay::new();
+
+        let command = CommandSet::WriteDataToRAM(b'A').into();
+        sender.delay_and_send(command, &mut delayer, 40);
+
+        // logged once, throttled once, and the failed command is sent again
+        assert_eq!(logged.get(), 1);
+        assert_eq!(delayer.get_delays_ns(), [5_000, 40_000]);
+        let mock = sender.into_inner().into_inner().into_inner();
+        assert_eq!(mock.get_commands().len(), 1);
+        assert_eq!(mock.get_commands()[0].get_data(), Some(Bits::Bit8(b'A')));
+        assert_eq!(mock.get_ddram()[0], b'A');
+    }
+}
@@ -12,41 +12,217 @@ Since there are only 4 pin for DB pin, so it only support 4 bit data width comma
 
 use embedded_hal::{
     delay::DelayNs,
-    i2c::{AddressMode, I2c},
+    i2c::{AddressMode, Error, ErrorKind, I2c},
 };
 
 use crate::{
     command::{Bits, Command, ReadWriteOp, RegisterSelection, State},
-    utils::{BitOps, BitState},
+    utils::BitOps,
 };
 
-use super::SendCommand;
+use super::{Contrast, SendCommand, TrySendCommand};
+
+// bytes of an I2C write in a batch, 6 bytes per data write
+const BATCH_BUF_LEN: usize = 16 * 6;
 
 /// [`I2cSender`] is the I2C interface with an adapter board to drive LCD1602
-pub struct I2cSender<'a, I2cLcd: I2c<A>, A: AddressMode + Clone> {
-    i2c: &'a mut I2cLcd,
+///
+/// It accepts any [`I2c`] implementation, a `&mut` of an I2C bus,
+/// or a device handle of a shared bus (e.g. `RefCellDevice` from `embedded-hal-bus`)
+pub struct I2cSender<I2cLcd: I2c<A>, A: AddressMode + Clone> {
+    i2c: I2cLcd,
     addr: A,
-    first_command: bool,
+    seq_builder: I2cSeqBuilder,
 }
 
-impl<'a, I2cLcd: I2c<A>, A: AddressMode + Clone> I2cSender<'a, I2cLcd, A> {
+impl<I2cLcd: I2c<A>, A: AddressMode + Clone> I2cSender<I2cLcd, A> {
     /// Create a [`I2cSender`] driver
-    pub fn new(i2c: &'a mut I2cLcd, addr: A) -> Self {
+    pub fn new(i2c: I2cLcd, addr: A) -> Self {
         Self {
             i2c,
             addr,
-            first_command: true,
+            seq_builder: I2cSeqBuilder::default(),
+        }
+    }
+
+    /// Find the address of the adapter board in `candidate_addrs` (e.g. `&[0x27, 0x3F]`),
+    /// return the first address that ACKs, or [`None`] if none of them does
+    ///
+    /// Each address is checked with a zero-length write, no command is sent to LCD.
+    ///
+    /// Note:
+    /// The HAL of the I2C bus should support zero-length write
+    pub fn probe(i2c: &mut I2cLcd, candidate_addrs: &[A]) -> Option<A> {
+        candidate_addrs
+            .iter()
+            .find(|addr| i2c.write((*addr).clone(), &[]).is_ok())
+            .cloned()
+    }
+}
+
+impl<I2cLcd: I2c<A>, A: AddressMode + Clone> I2cSender<I2cLcd, A> {
+    fn set_backlight_fallible(&mut self, state: State) -> Result<(), ErrorKind> {
+        let seq = self.seq_builder.backlight_seq(state);
+        self.i2c
+            .write(self.addr.clone(), &seq)
+            .map_err(|e| e.kind())
+    }
+
+    // send runs of data writes in one I2C write each, other commands one by one
+    fn send_batch_fallible(&mut self, commands: &[Command]) -> Result<Option<u8>, ErrorKind> {
+        let mut buf = [0u8; BATCH_BUF_LEN];
+        let mut len = 0;
+        let mut data = None;
+
+        for &command in commands {
+            let is_data_write = command.get_register_selection() == RegisterSelection::Data
+                && command.get_read_write_op() == ReadWriteOp::Write;
+
+            if !is_data_write {
+                self.flush_batch(&buf[..len])?;
+                len = 0;
+                data = self.send_fallible(command)?;
+                continue;
+            }
+
+            let I2cOp::Write(I2cSeq(seq_len, seq)) = self.seq_builder.build(command) else {
+                unreachable!("a write command always builds a write sequence")
+            };
+
+            if len + seq_len > buf.len() {
+                self.flush_batch(&buf[..len])?;
+                len = 0;
+            }
+            buf[len..len + seq_len].copy_from_slice(&seq[..seq_len]);
+            len += seq_len;
+            data = None;
+        }
+
+        self.flush_batch(&buf[..len])?;
+        Ok(data)
+    }
+
+    fn flush_batch(&mut self, seq: &[u8]) -> Result<(), ErrorKind> {
+        if seq.is_empty() {
+            return Ok(());
+        }
+
+        self.i2c.write(self.addr.clone(), seq).map_err(|e| e.kind())
+    }
+
+    // the shared part of .send() and .try_send()
+    fn send_fallible(&mut self, command: Command) -> Result<Option<u8>, ErrorKind> {
+        match self.seq_builder.build(command) {
+            I2cOp::Write(I2cSeq(len, seq)) => {
+                self.i2c
+                    .write(self.addr.clone(), &seq[..len])
+                    .map_err(|e| e.kind())?;
+                Ok(None)
+            }
+
+            I2cOp::Read(I2cSeq(_, seq)) => {
+                let mut concat_buf = [0u8; 2];
+                let mut buf = [0u8];
+
+                self.i2c
+                    .write_read(self.addr.clone(), &seq[0..2], &mut buf)
+                    .map_err(|e| e.kind())?;
+                concat_buf[0] = buf[0];
+                self.i2c
+                    .write_read(self.addr.clone(), &seq[2..5], &mut buf)
+                    .map_err(|e| e.kind())?;
+                self.i2c
+                    .write(self.addr.clone(), &seq[5..6])
+                    .map_err(|e| e.kind())?;
+                concat_buf[1] = buf[0];
+
+                Ok(Some(concat_nibbles(concat_buf)))
+            }
         }
     }
 }
 
-impl<'a, I2cLcd, A, Delayer> SendCommand<Delayer> for I2cSender<'a, I2cLcd, A>
+impl<I2cLcd, A, Delayer> SendCommand<Delayer> for I2cSender<I2cLcd, A>
 where
     I2cLcd: I2c<A>,
     A: AddressMode + Clone,
     Delayer: DelayNs,
 {
     fn set_backlight(&mut self, state: State) {
+        self.set_backlight_fallible(state).unwrap();
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.seq_builder.backlight
+    }
+
+    fn send(&mut self, command: Command) -> Option<u8> {
+        self.send_fallible(command).unwrap()
+    }
+
+    /// An I2C write of a data byte takes longer than LCD executes it
+    fn can_batch(&self) -> bool {
+        true
+    }
+
+    /// Consecutive data writes are sent in one I2C write, up to 16 bytes per write
+    fn send_batch(&mut self, commands: &[Command]) -> Option<u8> {
+        self.send_batch_fallible(commands).unwrap()
+    }
+}
+
+impl<I2cLcd, A, Delayer> TrySendCommand<Delayer> for I2cSender<I2cLcd, A>
+where
+    I2cLcd: I2c<A>,
+    A: AddressMode + Clone,
+    Delayer: DelayNs,
+{
+    type Error = ErrorKind;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        self.send_fallible(command)
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        self.set_backlight_fallible(backlight)
+    }
+}
+
+impl<I2cLcd: I2c<A>, A: AddressMode + Clone> Contrast for I2cSender<I2cLcd, A> {}
+
+// what should be done on I2C bus to send a command
+pub(super) enum I2cOp {
+    Write(I2cSeq),
+    // write seq[0..2] and read high nibble, write seq[2..5] and read low nibble, then write seq[5..6]
+    Read(I2cSeq),
+}
+
+// combine the bytes read from PCF8574 (data at high nibble) into a byte
+pub(super) fn concat_nibbles(concat_buf: [u8; 2]) -> u8 {
+    (concat_buf[0] & 0b1111_0000) | (concat_buf[1] >> 4)
+}
+
+// the bus independent part of I2C adapter board driver,
+// it builds byte sequences for commands, shared by blocking and async sender
+pub(super) struct I2cSeqBuilder {
+    first_command: bool,
+    // PCF8574 cannot report the backlight state reliably, so we cache the last written one
+    pub(super) backlight: State,
+}
+
+impl Default for I2cSeqBuilder {
+    fn default() -> Self {
+        Self {
+            first_command: true,
+            // PCF8574 pins are high after power on, thus the backlight is on
+            backlight: State::On,
+        }
+    }
+}
+
+impl I2cSeqBuilder {
+    // the sequence to change backlight, it also updates cached backlight
+    pub(super) fn backlight_seq(&mut self, state: State) -> [u8; 3] {
         let mut disabled_command: u8 = 0b1111_0010;
 
         if state == State::On {
@@ -56,22 +232,12 @@ where
         let mut enabled_command = disabled_command;
         enabled_command.set_bit(2);
 
-        let seq = [disabled_command, enabled_command, disabled_command];
-
-        self.i2c.write(self.addr.clone(), &seq).unwrap();
-    }
+        self.backlight = state;
 
-    fn get_backlight(&mut self) -> State {
-        let mut buf = [0u8];
-        // just a read is sufficient get backlight state
-        self.i2c.read(self.addr.clone(), &mut buf).unwrap();
-        match buf[0].check_bit(3) {
-            BitState::Clear => State::Off,
-            BitState::Set => State::On,
-        }
+        [disabled_command, enabled_command, disabled_command]
     }
 
-    fn send(&mut self, command: Command) -> Option<u8> {
+    pub(super) fn build(&mut self, command: Command) -> I2cOp {
         if self.first_command {
             assert!(
                 command.get_data().is_some(),
@@ -85,72 +251,62 @@ where
                     let i2c_data = I2cRawData::from(command);
 
                     // TODO: make backlight check more sensible
+                    // (high nibble 0b0011 is the resync nibble, 0b0010 is the half Function Set)
                     assert!(
-                        *i2c_data.0.as_ref().unwrap() == 0b0010_0000
-                            || *i2c_data.0.as_ref().unwrap() == 0b0010_1000,
+                        matches!(
+                            *i2c_data.0.as_ref().unwrap() & 0b1111_0111,
+                            0b0010_0000 | 0b0011_0000
+                        ),
                         "first command should be Function set, and should set to 4 bit mode"
                     );
 
-                    let I2cSeq(_, seq) = i2c_data.into();
+                    self.first_command = false;
 
-                    self.i2c.write(self.addr.clone(), &seq[0..3]).unwrap();
+                    return I2cOp::Write(self.apply_backlight(i2c_data.into()));
                 }
             }
+        }
 
-            self.first_command = false;
-        } else {
-            // if not first command, then all command should have 8 bit length
-            // though we send it as 4 bit per group
-            if let Some(Bits::Bit4(_)) = command.get_data() {
-                panic!("Only first command is 4 bit long, other command should be 8 bit long")
-            }
-
-            match command.get_read_write_op() {
-                ReadWriteOp::Write => {
-                    assert!(
-                        command.get_data().is_some(),
-                        "first command should has some data to write"
-                    );
+        // if not first command, then most command should have 8 bit length
+        // though we send it as 4 bit per group,
+        // 4 bit command is only used when LCD is initialized again
 
-                    if command.get_register_selection() == RegisterSelection::Command {
-                        match command.get_data().unwrap() {
-                            Bits::Bit8(command_data) => {
-                                if (command_data >> 4) == 0b0011 {
-                                    panic!("This I2C driver doesn't support 8 bit Data Width Mode")
-                                }
+        match command.get_read_write_op() {
+            ReadWriteOp::Write => {
+                assert!(
+                    command.get_data().is_some(),
+                    "first command should has some data to write"
+                );
+
+                if command.get_register_selection() == RegisterSelection::Command {
+                    match command.get_data().unwrap() {
+                        Bits::Bit8(command_data) => {
+                            if (command_data >> 4) == 0b0011 {
+                                panic!("This I2C driver doesn't support 8 bit Data Width Mode")
                             }
-                            _ => unreachable!(),
                         }
+                        // half commands only show up in (re)initialization
+                        Bits::Bit4(_) => (),
                     }
-
-                    let i2c_data = I2cRawData::from(command);
-                    let I2cSeq(_, seq) = i2c_data.into();
-                    self.i2c.write(self.addr.clone(), &seq).unwrap();
                 }
 
-                ReadWriteOp::Read => {
-                    let mut concat_buf = [0u8; 2];
-                    let mut buf = [0u8];
+                I2cOp::Write(self.apply_backlight(I2cRawData::from(command).into()))
+            }
 
-                    let i2c_data = I2cRawData::from(command);
-                    let I2cSeq(_, seq) = i2c_data.into();
-
-                    self.i2c
-                        .write_read(self.addr.clone(), &seq[0..2], &mut buf)
-                        .unwrap();
-                    concat_buf[0] = buf[0];
-                    self.i2c
-                        .write_read(self.addr.clone(), &seq[2..5], &mut buf)
-                        .unwrap();
-                    self.i2c.write(self.addr.clone(), &seq[5..6]).unwrap();
-                    concat_buf[1] = buf[0];
-
-                    return Some((concat_buf[0] & 0b1111_0000) | (concat_buf[1] >> 4));
-                }
-            };
+            ReadWriteOp::Read => {
+                I2cOp::Read(self.apply_backlight(I2cRawData::from(command).into()))
+            }
         }
+    }
 
-        None
+    // I2cRawData always turns backlight on, turn it off if cached backlight is off
+    fn apply_backlight(&self, mut i2c_seq: I2cSeq) -> I2cSeq {
+        if self.backlight == State::Off {
+            i2c_seq.1.iter_mut().for_each(|byte| {
+                byte.clear_bit(3);
+            });
+        }
+        i2c_seq
     }
 }
 
@@ -195,7 +351,7 @@ impl From<Command> for I2cRawData {
     }
 }
 
-struct I2cSeq(u8, [u8; 6]);
+pub(super) struct I2cSeq(pub(super) usize, pub(super) [u8; 6]);
 
 impl From<I2cRawData> for I2cSeq {
     fn from(raw_data: I2cRawData) -> Self {
@@ -211,8 +367,8 @@ impl From<I2cRawData> for I2cSeq {
         seq[1] = enable_0;
         seq[2] = disable_0;
 
-        if raw_data.1.is_some() {
-            let mut disable_1 = raw_data.1.unwrap();
+        if let Some(raw_data_1) = raw_data.1 {
+            let mut disable_1 = raw_data_1;
             disable_1.clear_bit(2);
             let mut enable_1 = disable_1;
             enable_1.set_bit(2);
@@ -227,3 +383,62 @@ impl From<I2cRawData> for I2cSeq {
         I2cSeq(len, seq)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+
+    use crate::{
+        command::State,
+        sender::{MockDelay, SendCommand},
+    };
+
+    use super::I2cSender;
+
+    // an I2C bus counts its reads, and keeps the last written byte
+    #[derive(Default)]
+    struct MockI2c {
+        reads: u32,
+        last_written: Option<u8>,
+    }
+
+    impl ErrorType for MockI2c {
+        type Error = ErrorKind;
+    }
+
+    impl I2c<SevenBitAddress> for MockI2c {
+        fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            operations.iter_mut().for_each(|operation| match operation {
+                Operation::Read(buf) => {
+                    self.reads += 1;
+                    buf.fill(0);
+                }
+                Operation::Write(bytes) => self.last_written = bytes.last().copied(),
+            });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn backlight_is_cached_without_i2c_read() {
+        let mut sender = I2cSender::new(MockI2c::default(), 0x27);
+
+        SendCommand::<MockDelay>::set_backlight(&mut sender, State::Off);
+        assert_eq!(
+            SendCommand::<MockDelay>::get_backlight(&mut sender),
+            State::Off
+        );
+        assert_eq!(sender.i2c.last_written.map(|byte| byte & 0b1000), Some(0));
+
+        SendCommand::<MockDelay>::set_backlight(&mut sender, State::On);
+        assert_eq!(
+            SendCommand::<MockDelay>::get_backlight(&mut sender),
+            State::On
+        );
+        assert_eq!(sender.i2c.reads, 0);
+    }
+}
@@ -0,0 +1,76 @@
+//! Async version of I2C adapter board driver, see [`I2cSender`](super::I2cSender)
+
+use embedded_hal::i2c::AddressMode;
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::command::{Command, State};
+
+use super::{
+    i2c_sender::{concat_nibbles, I2cOp, I2cSeq, I2cSeqBuilder},
+    SendCommandAsync,
+};
+
+/// [`I2cSenderAsync`] is the async I2C interface with an adapter board to drive LCD1602
+pub struct I2cSenderAsync<I2cLcd: I2c<A>, A: AddressMode + Clone> {
+    i2c: I2cLcd,
+    addr: A,
+    seq_builder: I2cSeqBuilder,
+}
+
+impl<I2cLcd: I2c<A>, A: AddressMode + Clone> I2cSenderAsync<I2cLcd, A> {
+    /// Create a [`I2cSenderAsync`] driver
+    pub fn new(i2c: I2cLcd, addr: A) -> Self {
+        Self {
+            i2c,
+            addr,
+            seq_builder: I2cSeqBuilder::default(),
+        }
+    }
+}
+
+impl<I2cLcd, A, Delayer> SendCommandAsync<Delayer> for I2cSenderAsync<I2cLcd, A>
+where
+    I2cLcd: I2c<A>,
+    A: AddressMode + Clone,
+    Delayer: DelayNs,
+{
+    async fn set_backlight(&mut self, state: State) {
+        let seq = self.seq_builder.backlight_seq(state);
+        self.i2c.write(self.addr.clone(), &seq).await.unwrap();
+    }
+
+    async fn get_backlight(&mut self) -> State {
+        self.seq_builder.backlight
+    }
+
+    async fn send(&mut self, command: Command) -> Option<u8> {
+        match self.seq_builder.build(command) {
+            I2cOp::Write(I2cSeq(len, seq)) => {
+                self.i2c
+                    .write(self.addr.clone(), &seq[..len])
+                    .await
+                    .unwrap();
+                None
+            }
+
+            I2cOp::Read(I2cSeq(_, seq)) => {
+                let mut concat_buf = [0u8; 2];
+                let mut buf = [0u8];
+
+                self.i2c
+                    .write_read(self.addr.clone(), &seq[0..2], &mut buf)
+                    .await
+                    .unwrap();
+                concat_buf[0] = buf[0];
+                self.i2c
+                    .write_read(self.addr.clone(), &seq[2..5], &mut buf)
+                    .await
+                    .unwrap();
+                self.i2c.write(self.addr.clone(), &seq[5..6]).await.unwrap();
+                concat_buf[1] = buf[0];
+
+                Some(concat_nibbles(concat_buf))
+            }
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! A host side sender for unit testing, it needs `alloc`
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+use embedded_hal::delay::DelayNs;
+
+use crate::command::{Bits, Command, ReadWriteOp, RegisterSelection, State};
+
+use super::{Contrast, SendCommand, TrySendCommand};
+
+// DDRAM address space of the controller
+const DDRAM_SIZE: usize = 0x80;
+// CGRAM address space of the controller
+const CGRAM_SIZE: usize = 0x40;
+
+/// [`MockSender`] records every [`Command`] it receives, and simulates DDRAM and CGRAM of LCD
+///
+/// Reads return pre-seeded bytes first (see [`MockSender::seed_data()`] and [`MockSender::seed_status()`]),
+/// then the simulated RAM (or an idle status with current address).
+///
+/// A command can be set to fail with [`MockSender::fail_on_command()`],
+/// [`TrySendCommand::try_send()`] returns [`MockError`] for it, and [`SendCommand::send()`] panics like other built-in senders.
+///
+/// Note:
+/// Only 8 bit commands are simulated, 4 bit commands (sent in initialization) are only recorded
+pub struct MockSender {
+    commands: Vec<Command>,
+    data_reads: VecDeque<u8>,
+    status_reads: VecDeque<u8>,
+    ddram: [u8; DDRAM_SIZE],
+    cgram: [u8; CGRAM_SIZE],
+    in_cgram: bool,
+    address: u8,
+    increment: bool,
+    display_shift: bool,
+    display_offset: u8,
+    backlight: State,
+    backlight_writes: Vec<State>,
+    can_batch: bool,
+    // commands left before the failing one
+    fail_countdown: Option<usize>,
+}
+
+impl Default for MockSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockSender {
+    /// Create a [`MockSender`], with DDRAM filled with white space
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            data_reads: VecDeque::new(),
+            status_reads: VecDeque::new(),
+            ddram: [b' '; DDRAM_SIZE],
+            cgram: [0; CGRAM_SIZE],
+            in_cgram: false,
+            address: 0,
+            increment: true,
+            display_shift: false,
+            display_offset: 0,
+            backlight: State::default(),
+            backlight_writes: Vec::new(),
+            can_batch: true,
+            fail_countdown: None,
+        }
+    }
+
+    /// Get all commands received, in order
+    pub fn get_commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Drop all recorded commands
+    pub fn clear_commands(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Queue a byte to be returned by next Read Data from RAM command
+    pub fn seed_data(&mut self, byte: u8) {
+        self.data_reads.push_back(byte);
+    }
+
+    /// Queue a byte to be returned by next Read Busy Flag and Address command,
+    /// bit 7 is the busy flag
+    pub fn seed_status(&mut self, status: u8) {
+        self.status_reads.push_back(status);
+    }
+
+    /// Let the `n`th command from now on (counting from 1) fail, the failed command is not recorded,
+    /// later commands succeed again
+    pub fn fail_on_command(&mut self, n: usize) {
+        assert!(n > 0, "n should count from 1");
+        self.fail_countdown = Some(n - 1);
+    }
+
+    /// Get all backlight states set, in order
+    pub fn get_backlight_writes(&self) -> &[State] {
+        &self.backlight_writes
+    }
+
+    /// Set whether the sender accepts batched data writes (see [`SendCommand::can_batch()`]), default to true,
+    /// since the simulated LCD finishes a command instantly
+    pub fn set_can_batch(&mut self, can_batch: bool) {
+        self.can_batch = can_batch;
+    }
+
+    /// Get the simulated DDRAM
+    pub fn get_ddram(&self) -> &[u8] {
+        &self.ddram
+    }
+
+    /// Get the simulated CGRAM
+    pub fn get_cgram(&self) -> &[u8] {
+        &self.cgram
+    }
+
+    /// Render the display window of the simulated DDRAM into a grid of characters,
+    /// with the standard layout of a `COLS` x `ROWS` display (`ROWS` should be 1, 2 or 4)
+    ///
+    /// Bytes out of printable ASCII are rendered as `'?'`
+    pub fn render<const COLS: usize, const ROWS: usize>(&self) -> [[char; COLS]; ROWS] {
+        assert!(
+            ROWS == 1 || ROWS == 2 || ROWS == 4,
+            "rows of display should be 1, 2 or 4"
+        );
+
+        let line_capacity = match ROWS {
+            1 => 80,
+            _ => 40,
+        };
+
+        let mut grid = [[' '; COLS]; ROWS];
+
+        grid.iter_mut().enumerate().for_each(|(row, cells)| {
+            cells.iter_mut().enumerate().for_each(|(col, cell)| {
+                let addr = match ROWS {
+                    // 4 rows display splits each line into 2 rows
+                    4 => (row % 2) * 0x40 + (row / 2) * COLS + col,
+                    _ => row * 0x40 + (self.display_offset as usize + col) % line_capacity,
+                };
+
+                let byte = self.ddram[addr % DDRAM_SIZE];
+                *cell = match byte {
+                    0x20..=0x7E => byte as char,
+                    _ => '?',
+                };
+            });
+        });
+
+        grid
+    }
+
+    // move AC after a RAM access
+    fn step_address(&mut self) {
+        let size = match self.in_cgram {
+            true => CGRAM_SIZE,
+            false => DDRAM_SIZE,
+        } as u8;
+
+        self.address = match self.increment {
+            true => (self.address + 1) % size,
+            false => (self.address + size - 1) % size,
+        };
+
+        if self.display_shift && !self.in_cgram {
+            self.shift_display(!self.increment);
+        }
+    }
+
+    // content moves right if `right` is true
+    fn shift_display(&mut self, right: bool) {
+        self.display_offset = match right {
+            true => (self.display_offset + 39) % 40,
+            false => (self.display_offset + 1) % 40,
+        };
+    }
+
+    fn simulate_command(&mut self, byte: u8) {
+        match byte {
+            // Set DDRAM address
+            0b1000_0000..=0xFF => {
+                self.in_cgram = false;
+                self.address = byte & 0b0111_1111;
+            }
+            // Set CGRAM address
+            0b0100_0000..=0b0111_1111 => {
+                self.in_cgram = true;
+                self.address = byte & 0b0011_1111;
+            }
+            // Function Set
+            0b0010_0000..=0b0011_1111 => (),
+            // Cursor or Display Shift
+            0b0001_0000..=0b0001_1111 if byte & 0b1000 != 0 => {
+                self.shift_display(byte & 0b100 != 0);
+            }
+            // Display On/Off Control
+            0b0000_1000..=0b0000_1111 => (),
+            // Entry Mode Set
+            0b0000_0100..=0b0000_0111 => {
+                self.increment = byte & 0b10 != 0;
+                self.display_shift = byte & 0b1 != 0;
+            }
+            // Return Home
+            0b0000_0010..=0b0000_0011 => {
+                self.in_cgram = false;
+                self.address = 0;
+                self.display_offset = 0;
+            }
+            // Clear Display
+            0b0000_0001 => {
+                self.ddram = [b' '; DDRAM_SIZE];
+                self.in_cgram = false;
+                self.address = 0;
+                self.display_offset = 0;
+                self.increment = true;
+            }
+            _ => (),
+        }
+    }
+
+    // record a command and simulate it
+    fn receive(&mut self, command: Command) -> Option<u8> {
+        self.commands.push(command);
+
+        match (
+            command.get_register_selection(),
+            command.get_read_write_op(),
+            command.get_data(),
+        ) {
+            (RegisterSelection::Command, ReadWriteOp::Write, Some(Bits::Bit8(byte))) => {
+                self.simulate_command(byte);
+                None
+            }
+
+            (RegisterSelection::Data, ReadWriteOp::Write, Some(Bits::Bit8(byte))) => {
+                match self.in_cgram {
+                    true => self.cgram[self.address as usize] = byte,
+                    false => self.ddram[self.address as usize] = byte,
+                }
+                self.step_address();
+                None
+            }
+
+            (RegisterSelection::Command, ReadWriteOp::Read, _) => {
+                Some(self.status_reads.pop_front().unwrap_or(self.address))
+            }
+
+            (RegisterSelection::Data, ReadWriteOp::Read, _) => {
+                let byte = self.data_reads.pop_front().unwrap_or(match self.in_cgram {
+                    true => self.cgram[self.address as usize],
+                    false => self.ddram[self.address as usize],
+                });
+                self.step_address();
+                Some(byte)
+            }
+
+            // 4 bit commands only show up in initialization
+            _ => None,
+        }
+    }
+}
+
+impl<Delayer: DelayNs> SendCommand<Delayer> for MockSender {
+    fn send(&mut self, command: Command) -> Option<u8> {
+        TrySendCommand::<Delayer>::try_send(self, command).unwrap()
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.backlight
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        self.backlight = backlight;
+        self.backlight_writes.push(backlight);
+    }
+
+    fn can_batch(&self) -> bool {
+        self.can_batch
+    }
+}
+
+impl Contrast for MockSender {}
+
+impl<Delayer: DelayNs> TrySendCommand<Delayer> for MockSender {
+    type Error = MockError;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        match self.fail_countdown {
+            Some(0) => {
+                self.fail_countdown = None;
+                Err(MockError)
+            }
+            countdown => {
+                self.fail_countdown = countdown.map(|countdown| countdown - 1);
+                Ok(self.receive(command))
+            }
+        }
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        self.backlight = backlight;
+        self.backlight_writes.push(backlight);
+        Ok(())
+    }
+}
+
+/// [`MockError`] is the error injected by [`MockSender::fail_on_command()`]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MockError;
+
+/// [`MockDelay`] records every delay instead of waiting, to be used with [`MockSender`]
+#[derive(Default)]
+pub struct MockDelay {
+    delays_ns: Vec<u32>,
+}
+
+impl MockDelay {
+    /// Create a [`MockDelay`] with no delay recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get all delays in nanoseconds, in order
+    pub fn get_delays_ns(&self) -> &[u32] {
+        &self.delays_ns
+    }
+
+    /// Get the sum of all delays in nanoseconds
+    pub fn get_total_ns(&self) -> u64 {
+        self.delays_ns.iter().map(|&ns| ns as u64).sum()
+    }
+
+    /// Drop all recorded delays
+    pub fn clear_delays(&mut self) {
+        self.delays_ns.clear();
+    }
+}
+
+impl DelayNs for MockDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delays_ns.push(ns);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delays_ns.push(us.saturating_mul(1_000));
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delays_ns.push(ms.saturating_mul(1_000_000));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{CommandSet, RegisterSelection},
+        lcd::{Config, Ext, Lcd},
+        sender::{SendCommand, TrySendCommand},
+    };
+
+    use super::{MockDelay, MockError, MockSender};
+
+    #[test]
+    fn records_and_simulates_writes() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.write_str_to_pos("Hi", (3, 1));
+        let (sender, _) = lcd.release();
+
+        let grid = sender.render::<16, 2>();
+        assert_eq!(grid[1][3..5], ['H', 'i']);
+        let data_writes = sender
+            .get_commands()
+            .iter()
+            .filter(|command| command.get_register_selection() == RegisterSelection::Data)
+            .count();
+        assert_eq!(data_writes, 2);
+    }
+
+    #[test]
+    fn seeded_reads_come_first() {
+        let mut sender = MockSender::new();
+        sender.seed_status(0b1010_0101);
+        sender.seed_data(b'Z');
+
+        let status = SendCommand::<MockDelay>::read_status(&mut sender);
+        assert!(status.busy);
+        assert_eq!(status.address, 0x25);
+        assert_eq!(
+            SendCommand::<MockDelay>::send(&mut sender, CommandSet::ReadDataFromRAM.into()),
+            Some(b'Z')
+        );
+        // then the simulated RAM
+        assert_eq!(
+            SendCommand::<MockDelay>::send(&mut sender, CommandSet::ReadDataFromRAM.into()),
+            Some(b' ')
+        );
+    }
+
+    #[test]
+    fn fails_on_nth_command_once() {
+        let mut sender = MockSender::new();
+        sender.fail_on_command(2);
+
+        let command = CommandSet::WriteDataToRAM(b'A').into();
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Ok(None)
+        );
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Err(MockError)
+        );
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Ok(None)
+        );
+        // the failed command is not recorded
+        assert_eq!(sender.get_commands().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn blocking_send_panics_on_injected_error() {
+        let mut sender = MockSender::new();
+        sender.fail_on_command(1);
+
+        SendCommand::<MockDelay>::send(&mut sender, CommandSet::ReturnHome.into());
+    }
+}
@@ -2,7 +2,7 @@
 
 use embedded_hal::{
     delay::DelayNs,
-    digital::{InputPin, OutputPin, StatefulOutputPin},
+    digital::{Error, ErrorKind, InputPin, OutputPin},
 };
 
 use crate::{
@@ -10,27 +10,53 @@ use crate::{
     utils::{BitOps, BitState},
 };
 
-use super::SendCommand;
+use super::{Contrast, SendCommand, TrySendCommand};
 
 /// [`ParallelSender`] is the parallel interface to drive LCD1602
+///
+/// Only 4-pin and 8-pin senders can be constructed, with [`ParallelSender::new_4pin()`] and [`ParallelSender::new_8pin()`],
+/// a sender with other pin count fails to compile:
+///
+/// ```compile_fail
+/// # use core::convert::Infallible;
+/// # use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+/// # use lcd1602_driver::sender::ParallelSender;
+/// # struct Pin;
+/// # impl ErrorType for Pin {
+/// #     type Error = Infallible;
+/// # }
+/// # impl OutputPin for Pin {
+/// #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+/// # }
+/// # impl InputPin for Pin {
+/// #     fn is_high(&mut self) -> Result<bool, Infallible> { Ok(true) }
+/// #     fn is_low(&mut self) -> Result<bool, Infallible> { Ok(false) }
+/// # }
+/// let sender: ParallelSender<Pin, Pin, Pin, 5> =
+///     ParallelSender::new_4pin(Pin, Pin, Pin, Pin, Pin, Pin, Pin, None);
+/// ```
 pub struct ParallelSender<ControlPin, DBPin, BLPin, const PIN_CNT: usize>
 where
     ControlPin: OutputPin,
     DBPin: OutputPin + InputPin,
-    BLPin: StatefulOutputPin,
+    BLPin: OutputPin,
 {
     rs_pin: ControlPin,
     rw_pin: ControlPin,
     en_pin: ControlPin,
     db_pins: [DBPin; PIN_CNT],
     bl_pin: Option<BLPin>,
+    // backlight pin doesn't need to be read back, we remember the last state set
+    backlight: State,
+    write_only: bool,
 }
 
 impl<ControlPin, DBPin, BLPin> ParallelSender<ControlPin, DBPin, BLPin, 4>
 where
     ControlPin: OutputPin,
     DBPin: OutputPin + InputPin,
-    BLPin: StatefulOutputPin,
+    BLPin: OutputPin,
 {
     /// Create 4-pin parallel driver, will need other 3 pins to control LCD,  
     /// and a optional pin to control backlight (better connect the pin to a transistor)
@@ -45,13 +71,7 @@ where
         db7: DBPin,
         bl: Option<BLPin>,
     ) -> Self {
-        Self {
-            rs_pin: rs,
-            rw_pin: rw,
-            en_pin: en,
-            db_pins: [db4, db5, db6, db7],
-            bl_pin: bl,
-        }
+        Self::from_pins(rs, rw, en, [db4, db5, db6, db7], bl)
     }
 }
 
@@ -59,7 +79,7 @@ impl<ControlPin, DBPin, BLPin> ParallelSender<ControlPin, DBPin, BLPin, 8>
 where
     ControlPin: OutputPin,
     DBPin: OutputPin + InputPin,
-    BLPin: StatefulOutputPin,
+    BLPin: OutputPin,
 {
     /// Create 8-pin parallel driver, will need other 3 pins to control LCD,  
     /// and a optional pin to control backlight (better connect the pin to a transistor)
@@ -78,13 +98,7 @@ where
         db7: DBPin,
         bl: Option<BLPin>,
     ) -> Self {
-        Self {
-            rs_pin: rs,
-            rw_pin: rw,
-            en_pin: en,
-            db_pins: [db0, db1, db2, db3, db4, db5, db6, db7],
-            bl_pin: bl,
-        }
+        Self::from_pins(rs, rw, en, [db0, db1, db2, db3, db4, db5, db6, db7], bl)
     }
 }
 
@@ -93,95 +107,104 @@ impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize>
 where
     ControlPin: OutputPin,
     DBPin: OutputPin + InputPin,
-    BLPin: StatefulOutputPin,
+    BLPin: OutputPin,
 {
-    fn push_bits(&mut self, raw_bits: u8) {
+    /// Mark the sender as write only, e.g. RW pin is tied to ground, default to false
+    ///
+    /// Note:
+    /// A write only sender cannot read busy flag, use it with [`TimingMode::FixedDelay`](crate::lcd::TimingMode::FixedDelay)
+    pub fn set_write_only(mut self, write_only: bool) -> Self {
+        self.write_only = write_only;
+        self
+    }
+
+    // only 4 pin and 8 pin senders can be constructed,
+    // the check is evaluated at compile time where it's referenced
+    const PIN_CNT_CHECK: () = assert!(
+        PIN_CNT == 4 || PIN_CNT == 8,
+        "Pins other than 4 or 8 are not supported"
+    );
+
+    // the only constructor path, thus a sender with other pin count fails to build at construction
+    fn from_pins(
+        rs: ControlPin,
+        rw: ControlPin,
+        en: ControlPin,
+        db_pins: [DBPin; PIN_CNT],
+        bl: Option<BLPin>,
+    ) -> Self {
+        let () = Self::PIN_CNT_CHECK;
+
+        Self {
+            rs_pin: rs,
+            rw_pin: rw,
+            en_pin: en,
+            db_pins,
+            bl_pin: bl,
+            backlight: State::default(),
+            write_only: false,
+        }
+    }
+
+    fn push_bits(&mut self, raw_bits: u8) -> Result<(), ErrorKind> {
         self.db_pins
             .iter_mut()
             .enumerate()
-            .for_each(|(index, pin)| match raw_bits.check_bit(index as u8) {
-                BitState::Set => {
-                    pin.set_high().ok().unwrap();
-                }
-                BitState::Clear => {
-                    pin.set_low().ok().unwrap();
-                }
-            });
+            .try_for_each(|(index, pin)| match raw_bits.check_bit(index as u8) {
+                BitState::Set => pin.set_high().map_err(|e| e.kind()),
+                BitState::Clear => pin.set_low().map_err(|e| e.kind()),
+            })
     }
 
-    fn fetch_bits(&mut self) -> u8 {
+    fn fetch_bits(&mut self) -> Result<u8, ErrorKind> {
         self.db_pins
             .iter_mut()
             .enumerate()
-            // use .fold() to change same value in different iteration
-            .fold(0u8, |mut acc, (index, pin)| {
+            // use .try_fold() to change same value in different iteration
+            .try_fold(0u8, |mut acc, (index, pin)| {
                 // in open drain mode, set pin high to release control
-                pin.set_high().ok().unwrap();
+                pin.set_high().map_err(|e| e.kind())?;
                 // it's incorrect to use .get_state() here, which return what we want to put pin in, rather what pin real state
-                match pin.is_low() {
-                    Ok(val) => match val {
-                        false => acc.set_bit(index as u8),
-                        true => acc.clear_bit(index as u8),
-                    },
-                    Err(_) => panic!("Something wrong when read from pin"),
+                match pin.is_low().map_err(|e| e.kind())? {
+                    false => acc.set_bit(index as u8),
+                    true => acc.clear_bit(index as u8),
                 };
-                acc
+                Ok(acc)
             })
     }
-}
 
-impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize, Delayer> SendCommand<Delayer>
-    for ParallelSender<ControlPin, DBPin, BLPin, PIN_CNT>
-where
-    ControlPin: OutputPin,
-    DBPin: OutputPin + InputPin,
-    BLPin: StatefulOutputPin,
-    Delayer: DelayNs,
-{
-    fn get_backlight(&mut self) -> State {
-        match self.bl_pin.as_mut() {
-            Some(bl_pin) => match bl_pin.is_set_high().unwrap() {
-                true => State::On,
-                false => State::Off,
-            },
-            None => Default::default(),
-        }
+    // latch data on DB pins into LCD
+    fn pulse_en(&mut self) -> Result<(), ErrorKind> {
+        self.en_pin.set_high().map_err(|e| e.kind())?;
+        self.en_pin.set_low().map_err(|e| e.kind())
     }
 
-    fn set_backlight(&mut self, backlight: State) {
+    fn set_backlight_pin(&mut self, backlight: State) -> Result<(), ErrorKind> {
         if let Some(bl_pin) = self.bl_pin.as_mut() {
             match backlight {
-                State::Off => bl_pin.set_low().unwrap(),
-                State::On => bl_pin.set_high().unwrap(),
+                State::Off => bl_pin.set_low().map_err(|e| e.kind())?,
+                State::On => bl_pin.set_high().map_err(|e| e.kind())?,
             }
+            self.backlight = backlight;
         }
+        Ok(())
     }
 
-    fn send(&mut self, command: Command) -> Option<u8> {
-        assert!(
-            PIN_CNT == 4 || PIN_CNT == 8,
-            "Pins other than 4 or 8 are not supported"
-        );
-
-        self.en_pin.set_low().ok().unwrap();
+    // the shared part of .send() and .try_send()
+    fn send_fallible(&mut self, command: Command) -> Result<Option<u8>, ErrorKind> {
+        self.en_pin.set_low().map_err(|e| e.kind())?;
 
         match command.get_register_selection() {
-            RegisterSelection::Command => {
-                self.rs_pin.set_low().ok().unwrap();
-            }
-            RegisterSelection::Data => {
-                self.rs_pin.set_high().ok().unwrap();
-            }
+            RegisterSelection::Command => self.rs_pin.set_low(),
+            RegisterSelection::Data => self.rs_pin.set_high(),
         }
+        .map_err(|e| e.kind())?;
 
         match command.get_read_write_op() {
-            ReadWriteOp::Write => {
-                self.rw_pin.set_low().ok().unwrap();
-            }
-            ReadWriteOp::Read => {
-                self.rw_pin.set_high().ok().unwrap();
-            }
+            ReadWriteOp::Write => self.rw_pin.set_low(),
+            ReadWriteOp::Read => self.rw_pin.set_high(),
         }
+        .map_err(|e| e.kind())?;
 
         match command.get_read_write_op() {
             ReadWriteOp::Write => {
@@ -192,25 +215,21 @@ where
                     4 => match bits {
                         Bits::Bit4(raw_bits) => {
                             assert!(raw_bits < 2u8.pow(4), "data is greater than 4 bits");
-                            self.push_bits(raw_bits);
-                            self.en_pin.set_high().ok().unwrap();
-                            self.en_pin.set_low().ok().unwrap();
+                            self.push_bits(raw_bits)?;
+                            self.pulse_en()?;
                         }
                         Bits::Bit8(raw_bits) => {
-                            self.push_bits(raw_bits >> 4);
-                            self.en_pin.set_high().ok().unwrap();
-                            self.en_pin.set_low().ok().unwrap();
-                            self.push_bits(raw_bits & 0b1111);
-                            self.en_pin.set_high().ok().unwrap();
-                            self.en_pin.set_low().ok().unwrap();
+                            self.push_bits(raw_bits >> 4)?;
+                            self.pulse_en()?;
+                            self.push_bits(raw_bits & 0b1111)?;
+                            self.pulse_en()?;
                         }
                     },
 
                     8 => {
                         if let Bits::Bit8(raw_bits) = bits {
-                            self.push_bits(raw_bits);
-                            self.en_pin.set_high().ok().unwrap();
-                            self.en_pin.set_low().ok().unwrap();
+                            self.push_bits(raw_bits)?;
+                            self.pulse_en()?;
                         } else {
                             panic!("in 8 pin mode, data should always be 8 bit")
                         }
@@ -219,24 +238,24 @@ where
                     _ => unreachable!(),
                 }
 
-                None
+                Ok(None)
             }
             ReadWriteOp::Read => match PIN_CNT {
                 4 => {
-                    self.en_pin.set_high().ok().unwrap();
-                    let high_4_bits = self.fetch_bits().checked_shl(4).unwrap();
-                    self.en_pin.set_low().ok().unwrap();
-                    self.en_pin.set_high().ok().unwrap();
-                    let low_4_bits = self.fetch_bits();
-                    self.en_pin.set_low().ok().unwrap();
-                    Some(high_4_bits + low_4_bits)
+                    self.en_pin.set_high().map_err(|e| e.kind())?;
+                    let high_4_bits = self.fetch_bits()?.checked_shl(4).unwrap();
+                    self.en_pin.set_low().map_err(|e| e.kind())?;
+                    self.en_pin.set_high().map_err(|e| e.kind())?;
+                    let low_4_bits = self.fetch_bits()?;
+                    self.en_pin.set_low().map_err(|e| e.kind())?;
+                    Ok(Some(high_4_bits + low_4_bits))
                 }
 
                 8 => {
-                    self.en_pin.set_high().ok().unwrap();
-                    let bits = self.fetch_bits();
-                    self.en_pin.set_low().ok().unwrap();
-                    Some(bits)
+                    self.en_pin.set_high().map_err(|e| e.kind())?;
+                    let bits = self.fetch_bits()?;
+                    self.en_pin.set_low().map_err(|e| e.kind())?;
+                    Ok(Some(bits))
                 }
 
                 _ => unreachable!(),
@@ -244,3 +263,258 @@ where
         }
     }
 }
+
+impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize, Delayer> SendCommand<Delayer>
+    for ParallelSender<ControlPin, DBPin, BLPin, PIN_CNT>
+where
+    ControlPin: OutputPin,
+    DBPin: OutputPin + InputPin,
+    BLPin: OutputPin,
+    Delayer: DelayNs,
+{
+    fn get_backlight(&mut self) -> State {
+        self.backlight
+    }
+
+    fn can_read(&self) -> bool {
+        !self.write_only
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        self.set_backlight_pin(backlight).unwrap();
+    }
+
+    fn send(&mut self, command: Command) -> Option<u8> {
+        self.send_fallible(command).unwrap()
+    }
+}
+
+impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize, Delayer> TrySendCommand<Delayer>
+    for ParallelSender<ControlPin, DBPin, BLPin, PIN_CNT>
+where
+    ControlPin: OutputPin,
+    DBPin: OutputPin + InputPin,
+    BLPin: OutputPin,
+    Delayer: DelayNs,
+{
+    // pins may have different error types, they are unified into ErrorKind
+    type Error = ErrorKind;
+
+    fn try_send(&mut self, command: Command) -> Result<Option<u8>, Self::Error> {
+        self.send_fallible(command)
+    }
+
+    fn try_set_backlight(&mut self, backlight: State) -> Result<(), Self::Error> {
+        self.set_backlight_pin(backlight)
+    }
+}
+
+impl<ControlPin, DBPin, BLPin, const PIN_CNT: usize> Contrast
+    for ParallelSender<ControlPin, DBPin, BLPin, PIN_CNT>
+where
+    ControlPin: OutputPin,
+    DBPin: OutputPin + InputPin,
+    BLPin: OutputPin,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embedded_hal::digital::{ErrorKind, ErrorType, InputPin, OutputPin};
+
+    use crate::{
+        command::{CommandSet, State},
+        lcd::{Basic, Config, Ext, Lcd, LcdError},
+        sender::{MockDelay, SendCommand, TrySendCommand},
+    };
+
+    use super::ParallelSender;
+
+    // a pin counts its toggles, and fails on the `fail_on`th toggle (0 for never)
+    struct MockPin<'c> {
+        toggles: &'c Cell<u32>,
+        fail_on: &'c Cell<u32>,
+    }
+
+    impl MockPin<'_> {
+        fn toggle(&mut self) -> Result<(), ErrorKind> {
+            self.toggles.set(self.toggles.get() + 1);
+            match self.toggles.get() == self.fail_on.get() {
+                true => Err(ErrorKind::Other),
+                false => Ok(()),
+            }
+        }
+    }
+
+    impl ErrorType for MockPin<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl OutputPin for MockPin<'_> {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.toggle()
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.toggle()
+        }
+    }
+
+    impl InputPin for MockPin<'_> {
+        fn is_high(&mut self) -> Result<bool, Self::Error> {
+            Ok(false)
+        }
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    // a 4 pin sender, only EN pin fails
+    fn new_sender<'c>(
+        pin_cells: &'c (Cell<u32>, Cell<u32>),
+        en_cells: &'c (Cell<u32>, Cell<u32>),
+    ) -> ParallelSender<MockPin<'c>, MockPin<'c>, MockPin<'c>, 4> {
+        let pin = || MockPin {
+            toggles: &pin_cells.0,
+            fail_on: &pin_cells.1,
+        };
+        let en = MockPin {
+            toggles: &en_cells.0,
+            fail_on: &en_cells.1,
+        };
+
+        ParallelSender::new_4pin(pin(), pin(), en, pin(), pin(), pin(), pin(), Some(pin()))
+    }
+
+    #[test]
+    fn try_send_returns_pin_error() {
+        let pin_cells = Default::default();
+        let en_cells = (Cell::new(0), Cell::new(3));
+        let mut sender = new_sender(&pin_cells, &en_cells);
+
+        let command = CommandSet::WriteDataToRAM(b'A').into();
+        // EN is set low, then pulsed high and low for each nibble, the 3rd toggle fails
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Err(ErrorKind::Other)
+        );
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn lcd_try_methods_return_pin_error() {
+        let pin_cells = Default::default();
+        let en_cells = Default::default();
+        let mut sender = new_sender(&pin_cells, &en_cells);
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        en_cells.0.set(0);
+        en_cells.1.set(3);
+
+        assert_eq!(
+            lcd.try_write_u8_to_cur(b'A'),
+            Err(LcdError::Sender(ErrorKind::Other))
+        );
+        assert_eq!(lcd.get_cursor_pos(), (0, 0));
+
+        lcd.try_write_u8_to_cur(b'A').unwrap();
+        assert_eq!(lcd.get_cursor_pos(), (1, 0));
+    }
+
+    #[test]
+    fn backlight_is_tracked_without_stateful_pin() {
+        let pin_cells = Default::default();
+        let en_cells = Default::default();
+        let mut sender = new_sender(&pin_cells, &en_cells);
+
+        // MockPin is a plain OutputPin, the state is never read back from it
+        SendCommand::<MockDelay>::set_backlight(&mut sender, State::Off);
+        assert_eq!(
+            SendCommand::<MockDelay>::get_backlight(&mut sender),
+            State::Off
+        );
+        SendCommand::<MockDelay>::set_backlight(&mut sender, State::On);
+        assert_eq!(
+            SendCommand::<MockDelay>::get_backlight(&mut sender),
+            State::On
+        );
+    }
+
+    #[test]
+    fn write_only_sender_cannot_read() {
+        let pin_cells = Default::default();
+        let en_cells = Default::default();
+
+        let sender = new_sender(&pin_cells, &en_cells);
+        assert!(SendCommand::<MockDelay>::can_read(&sender));
+
+        let sender = new_sender(&pin_cells, &en_cells).set_write_only(true);
+        assert!(!SendCommand::<MockDelay>::can_read(&sender));
+    }
+
+    #[test]
+    #[should_panic(expected = "LCD cannot be read")]
+    fn reading_from_write_only_sender_panics() {
+        let pin_cells = Default::default();
+        let en_cells = Default::default();
+        let mut sender = new_sender(&pin_cells, &en_cells).set_write_only(true);
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.read_byte_from_pos((0, 0));
+    }
+
+    #[test]
+    fn both_pin_counts_send_a_byte() {
+        let pin_cells = <(Cell<u32>, Cell<u32>)>::default();
+        let en_cells = <(Cell<u32>, Cell<u32>)>::default();
+        let pin = || MockPin {
+            toggles: &pin_cells.0,
+            fail_on: &pin_cells.1,
+        };
+        let en = || MockPin {
+            toggles: &en_cells.0,
+            fail_on: &en_cells.1,
+        };
+        let command = CommandSet::WriteDataToRAM(b'A').into();
+
+        // RS, RW and 8 data bits are set, EN is set low, then pulsed once per nibble
+        let mut sender = new_sender(&pin_cells, &en_cells);
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Ok(None)
+        );
+        assert_eq!((pin_cells.0.get(), en_cells.0.get()), (10, 5));
+
+        pin_cells.0.set(0);
+        en_cells.0.set(0);
+
+        // RS, RW and 8 data bits are set, EN is set low, then pulsed once
+        let mut sender = ParallelSender::new_8pin(
+            pin(),
+            pin(),
+            en(),
+            pin(),
+            pin(),
+            pin(),
+            pin(),
+            pin(),
+            pin(),
+            pin(),
+            pin(),
+            Some(pin()),
+        );
+        assert_eq!(
+            TrySendCommand::<MockDelay>::try_send(&mut sender, command),
+            Ok(None)
+        );
+        assert_eq!((pin_cells.0.get(), en_cells.0.get()), (10, 3));
+    }
+}
@@ -0,0 +1,209 @@
+//! A decorator to drive the RGB backlight of a LCD module with 3 PWM channels
+
+use embedded_hal::{delay::DelayNs, pwm::SetDutyCycle};
+
+use crate::command::{Command, State};
+
+use super::{Contrast, RgbBacklight, SendCommand, SenderExt, Status};
+
+/// [`PwmBacklight`] wraps a sender, and drives red, green and blue backlight channels with PWM,
+/// e.g. for the RGB versions of LCD1602 modules
+///
+/// [`SendCommand::set_backlight()`] maps [`State::On`] to full white and [`State::Off`] to dark,
+/// [`SendCommand::dim_backlight()`] to white at half duty, backlight of the wrapped sender is not touched.
+pub struct PwmBacklight<S, R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> {
+    inner: S,
+    red: R,
+    green: G,
+    blue: B,
+    rgb: (u8, u8, u8),
+}
+
+impl<S, R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> PwmBacklight<S, R, G, B> {
+    /// Create a [`PwmBacklight`] sender, the backlight is turned off
+    pub fn new(inner: S, red: R, green: G, blue: B) -> Self {
+        let mut sender = Self {
+            inner,
+            red,
+            green,
+            blue,
+            rgb: (0, 0, 0),
+        };
+        sender.set_backlight_rgb(0, 0, 0);
+        sender
+    }
+
+    /// Get current color of backlight, in `(r, g, b)`
+    pub fn get_backlight_rgb(&self) -> (u8, u8, u8) {
+        self.rgb
+    }
+
+    /// Get the wrapped sender and PWM channels back
+    pub fn into_inner(self) -> (S, R, G, B) {
+        (self.inner, self.red, self.green, self.blue)
+    }
+}
+
+impl<S, R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> RgbBacklight
+    for PwmBacklight<S, R, G, B>
+{
+    fn set_backlight_rgb(&mut self, r: u8, g: u8, b: u8) {
+        self.red.set_duty_cycle_fraction(r as u16, 255).unwrap();
+        self.green.set_duty_cycle_fraction(g as u16, 255).unwrap();
+        self.blue.set_duty_cycle_fraction(b as u16, 255).unwrap();
+        self.rgb = (r, g, b);
+    }
+}
+
+impl<S, R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> SenderExt for PwmBacklight<S, R, G, B> {}
+
+impl<S, R, G, B, Delayer> SendCommand<Delayer> for PwmBacklight<S, R, G, B>
+where
+    S: SendCommand<Delayer>,
+    R: SetDutyCycle,
+    G: SetDutyCycle,
+    B: SetDutyCycle,
+    Delayer: DelayNs,
+{
+    fn send(&mut self, command: Command) -> Option<u8> {
+        self.inner.send(command)
+    }
+
+    fn send_batch(&mut self, commands: &[Command]) -> Option<u8> {
+        self.inner.send_batch(commands)
+    }
+
+    fn can_batch(&self) -> bool {
+        self.inner.can_batch()
+    }
+
+    fn can_read(&self) -> bool {
+        self.inner.can_read()
+    }
+
+    fn delay_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        delay_us: u32,
+    ) -> Option<u8> {
+        self.inner.delay_and_send(command, delayer, delay_us)
+    }
+
+    fn wait_and_send(
+        &mut self,
+        command: Command,
+        delayer: &mut Delayer,
+        poll_interval_us: u32,
+    ) -> Option<u8> {
+        self.inner.wait_and_send(command, delayer, poll_interval_us)
+    }
+
+    fn read_status(&mut self) -> Status {
+        self.inner.read_status()
+    }
+
+    fn check_busy(&mut self) -> bool {
+        self.inner.check_busy()
+    }
+
+    fn get_backlight(&mut self) -> State {
+        match self.rgb {
+            (0, 0, 0) => State::Off,
+            _ => State::On,
+        }
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        match backlight {
+            State::On => self.set_backlight_rgb(255, 255, 255),
+            State::Off => self.set_backlight_rgb(0, 0, 0),
+        }
+    }
+
+    /// White at half duty
+    fn dim_backlight(&mut self) -> bool {
+        self.set_backlight_rgb(128, 128, 128);
+        true
+    }
+}
+
+impl<S: Contrast, R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> Contrast
+    for PwmBacklight<S, R, G, B>
+{
+    fn set_contrast(&mut self, level: u8) {
+        self.inner.set_contrast(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::pwm::{ErrorKind, ErrorType, SetDutyCycle};
+
+    use crate::{
+        command::State,
+        lcd::{BacklightLevel, Basic, Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::PwmBacklight;
+
+    // a PWM channel with 8 bit resolution
+    #[derive(Default)]
+    struct MockChannel {
+        duty: u16,
+    }
+
+    impl ErrorType for MockChannel {
+        type Error = ErrorKind;
+    }
+
+    impl SetDutyCycle for MockChannel {
+        fn max_duty_cycle(&self) -> u16 {
+            255
+        }
+
+        fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+            self.duty = duty;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dim_maps_to_half_duty() {
+        let mut sender = PwmBacklight::new(
+            MockSender::new(),
+            MockChannel::default(),
+            MockChannel::default(),
+            MockChannel::default(),
+        );
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.set_backlight_level_rgb(BacklightLevel::Dim);
+        assert_eq!(lcd.get_backlight(), State::On);
+
+        assert_eq!(sender.get_backlight_rgb(), (128, 128, 128));
+        let (_, red, green, blue) = sender.into_inner();
+        assert_eq!((red.duty, green.duty, blue.duty), (128, 128, 128));
+    }
+
+    #[test]
+    fn dim_through_basic_sets_half_duty() {
+        let mut sender = PwmBacklight::new(
+            MockSender::new(),
+            MockChannel::default(),
+            MockChannel::default(),
+            MockChannel::default(),
+        );
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        lcd.set_backlight_level(BacklightLevel::Dim);
+        assert_eq!(lcd.get_backlight(), State::On);
+
+        assert_eq!(sender.get_backlight_rgb(), (128, 128, 128));
+        let (inner, ..) = sender.into_inner();
+        assert!(inner.get_backlight_writes().is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+/*!
+# SPI 74HC595 shift register board driver
+
+This kind of board has a 74HC595 shift register, data is shifted in by SPI (MOSI and SCK),
+and latched to the 8 outputs of 74HC595 by a latch pin (RCLK).
+
+Which output of 74HC595 connects to which pin of LCD1602 varies between boards, see [`ShiftRegisterPins`].
+
+Since 74HC595 is write only, and there are only 4 pin for DB pin, so it only support 4 bit data width command,
+and LCD cannot be read.
+*/
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
+
+use crate::{
+    command::{Bits, Command, ReadWriteOp, RegisterSelection, State},
+    utils::BitOps,
+};
+
+use super::{Contrast, SendCommand};
+
+/// [`ShiftRegisterPins`] defines which output (0 for QA to 7 for QH) of 74HC595 connects to which pin of LCD1602
+///
+/// Default is the same layout as PCF8574 I2C adapter board: DB7/DB6/DB5/DB4/BL/EN/RW/RS from QH to QA
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShiftRegisterPins {
+    #[allow(missing_docs)]
+    pub rs: u8,
+    #[allow(missing_docs)]
+    pub rw: u8,
+    #[allow(missing_docs)]
+    pub en: u8,
+    #[allow(missing_docs)]
+    pub backlight: u8,
+    /// outputs connect to DB4 to DB7
+    pub db: [u8; 4],
+}
+
+impl Default for ShiftRegisterPins {
+    fn default() -> Self {
+        Self {
+            rs: 0,
+            rw: 1,
+            en: 2,
+            backlight: 3,
+            db: [4, 5, 6, 7],
+        }
+    }
+}
+
+/// [`SpiSender`] is the SPI interface with a 74HC595 shift register board to drive LCD1602
+///
+/// Note:
+/// 74HC595 is write only, busy flag cannot be read,
+/// thus [`SendCommand::check_busy()`] always reports LCD is idle,
+/// and [`SendCommand::wait_and_send()`] falls back to wait for the worst case execution time of the command,
+/// [`TimingMode::FixedDelay`](crate::lcd::TimingMode::FixedDelay) is recommended for this sender.
+pub struct SpiSender<Spi: SpiDevice, LatchPin: OutputPin> {
+    spi: Spi,
+    latch_pin: LatchPin,
+    pins: ShiftRegisterPins,
+    first_command: bool,
+    // outputs of 74HC595 cannot be read back, we remember the last state set
+    backlight: State,
+}
+
+impl<Spi: SpiDevice, LatchPin: OutputPin> SpiSender<Spi, LatchPin> {
+    /// Create a [`SpiSender`] driver
+    pub fn new(spi: Spi, latch_pin: LatchPin, pins: ShiftRegisterPins) -> Self {
+        assert!(
+            [pins.rs, pins.rw, pins.en, pins.backlight]
+                .iter()
+                .chain(pins.db.iter())
+                .all(|&output| output < 8),
+            "74HC595 only has 8 outputs"
+        );
+
+        Self {
+            spi,
+            latch_pin,
+            pins,
+            first_command: true,
+            backlight: State::default(),
+        }
+    }
+
+    // build the output byte of 74HC595, RW is always low (write)
+    fn output_byte(&self, rs: RegisterSelection, nibble: u8, en: bool) -> u8 {
+        let mut byte = 0u8;
+
+        if rs == RegisterSelection::Data {
+            byte.set_bit(self.pins.rs);
+        }
+
+        if en {
+            byte.set_bit(self.pins.en);
+        }
+
+        if self.backlight == State::On {
+            byte.set_bit(self.pins.backlight);
+        }
+
+        self.pins
+            .db
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| (nibble >> index) & 1 == 1)
+            .for_each(|(_, &output)| {
+                byte.set_bit(output);
+            });
+
+        byte
+    }
+
+    // shift a byte into 74HC595, and latch it to the outputs
+    fn shift_out(&mut self, byte: u8) {
+        self.spi.write(&[byte]).unwrap();
+        self.latch_pin.set_high().unwrap();
+        self.latch_pin.set_low().unwrap();
+    }
+
+    // pulse EN pin with a nibble on DB pins
+    fn send_nibble(&mut self, rs: RegisterSelection, nibble: u8) {
+        assert!(nibble < (1 << 4), "data is overflow 4 bit");
+
+        let disabled = self.output_byte(rs, nibble, false);
+        let enabled = self.output_byte(rs, nibble, true);
+
+        self.shift_out(disabled);
+        self.shift_out(enabled);
+        self.shift_out(disabled);
+    }
+}
+
+impl<Spi, LatchPin, Delayer> SendCommand<Delayer> for SpiSender<Spi, LatchPin>
+where
+    Spi: SpiDevice,
+    LatchPin: OutputPin,
+    Delayer: DelayNs,
+{
+    fn can_read(&self) -> bool {
+        false
+    }
+
+    fn check_busy(&mut self) -> bool {
+        false
+    }
+
+    fn get_backlight(&mut self) -> State {
+        self.backlight
+    }
+
+    fn set_backlight(&mut self, backlight: State) {
+        self.backlight = backlight;
+
+        // keep EN low, only backlight output will change
+        let byte = self.output_byte(RegisterSelection::Command, 0, false);
+        self.shift_out(byte);
+    }
+
+    fn send(&mut self, command: Command) -> Option<u8> {
+        if command.get_read_write_op() == ReadWriteOp::Read {
+            // 74HC595 is write only
+            return None;
+        }
+
+        let rs = command.get_register_selection();
+
+        match command.get_data() {
+            None => panic!("Write command should have some data to be send"),
+
+            Some(Bits::Bit4(nibble)) => self.send_nibble(rs, nibble),
+
+            Some(Bits::Bit8(byte)) => {
+                assert!(!self.first_command, "first command should be 4 bit");
+
+                if rs == RegisterSelection::Command && (byte >> 4) == 0b0011 {
+                    panic!("This SPI driver doesn't support 8 bit Data Width Mode")
+                }
+
+                self.send_nibble(rs, byte >> 4);
+                self.send_nibble(rs, byte & 0b1111);
+            }
+        }
+
+        self.first_command = false;
+
+        None
+    }
+}
+
+impl<Spi: SpiDevice, LatchPin: OutputPin> Contrast for SpiSender<Spi, LatchPin> {}
@@ -1,7 +1,12 @@
-use crate::command::{DataWidth, Font, LineMode, MoveDirection, RAMType, ShiftType, State};
-
-#[derive(Default)]
-pub(crate) struct LcdState {
+use crate::{
+    charset::CharRom,
+    command::{DataWidth, Font, LineMode, MoveDirection, RAMType, ShiftType, State},
+};
+
+/// [`LcdState`] is the LCD1602 state simulated in MCU's memory
+#[derive(Clone)]
+#[cfg_attr(feature = "test-util", derive(Debug, PartialEq))]
+pub struct LcdState {
     data_width: DataWidth,
     line: LineMode,
     font: Font,
@@ -14,6 +19,39 @@ pub(crate) struct LcdState {
     display_offset: u8,
     ram_type: RAMType,
     backlight: State,
+    columns: u8,
+    rows: u8,
+    cgram_display_base: u8,
+    char_rom: CharRom,
+    cgram: [Option<[u8; 8]>; 8],
+    // (DDRAM base address, usable columns) of each row, None for the standard layout
+    row_layout: Option<[(u8, u8); 4]>,
+}
+
+impl Default for LcdState {
+    fn default() -> Self {
+        Self {
+            data_width: Default::default(),
+            line: Default::default(),
+            font: Default::default(),
+            display_on: Default::default(),
+            cursor_on: Default::default(),
+            cursor_blink: Default::default(),
+            direction: Default::default(),
+            shift_type: Default::default(),
+            cursor_pos: Default::default(),
+            display_offset: Default::default(),
+            ram_type: Default::default(),
+            backlight: Default::default(),
+            // LCD1602 has 16 visible columns
+            columns: 16,
+            rows: 2,
+            cgram_display_base: 0x00,
+            char_rom: Default::default(),
+            cgram: [None; 8],
+            row_layout: None,
+        }
+    }
 }
 
 impl LcdState {
@@ -39,7 +77,7 @@ impl LcdState {
 
     pub(crate) fn set_line_mode(&mut self, line: LineMode) {
         assert!(
-            (self.get_font() == Font::Font5x11) && (line == LineMode::OneLine),
+            !((self.get_font() == Font::Font5x11) && (line == LineMode::TwoLine)),
             "font is 5x11, line cannot be 2"
         );
 
@@ -53,13 +91,96 @@ impl LcdState {
         }
     }
 
+    pub(crate) fn get_columns(&self) -> u8 {
+        self.columns
+    }
+
+    pub(crate) fn set_columns(&mut self, columns: u8) {
+        assert!(
+            0 < columns && columns <= self.get_line_capacity(),
+            "visible columns should between 1 and line capacity"
+        );
+
+        // the standard 4 rows layout depends on columns, keep it follows columns
+        if self.rows == 4 && self.row_layout == Some(Self::four_row_layout(self.columns)) {
+            assert!(
+                columns <= self.get_line_capacity() / 2,
+                "visible columns of a 4 rows display should not bigger than 20"
+            );
+            self.row_layout = Some(Self::four_row_layout(columns));
+        }
+
+        self.columns = columns;
+    }
+
+    pub(crate) fn get_rows(&self) -> u8 {
+        self.rows
+    }
+
+    pub(crate) fn set_rows(&mut self, rows: u8) {
+        assert!(
+            rows == 1 || rows == 2 || rows == 4,
+            "rows of display should be 1, 2 or 4"
+        );
+
+        // controller only knows 1 or 2 lines, 4 rows display is 2 lines with a special address mapping
+        match rows {
+            1 => self.set_line_mode(LineMode::OneLine),
+            _ => self.set_line_mode(LineMode::TwoLine),
+        }
+
+        self.rows = rows;
+        self.row_layout = None;
+
+        if rows == 4 {
+            assert!(
+                self.columns <= self.get_line_capacity() / 2,
+                "visible columns of a 4 rows display should not bigger than 20"
+            );
+            self.row_layout = Some(Self::four_row_layout(self.columns));
+        }
+    }
+
+    // a 4 rows display splits each line into 2 rows,
+    // row 2 is the continuation of row 0 in DDRAM, and row 3 is the continuation of row 1,
+    // e.g. rows start at 0x00, 0x40, 0x14, 0x54 for a 20x4 display
+    fn four_row_layout(columns: u8) -> [(u8, u8); 4] {
+        [
+            (0x00, columns),
+            (0x40, columns),
+            (columns, columns),
+            (0x40 + columns, columns),
+        ]
+    }
+
+    pub(crate) fn get_cgram_display_base(&self) -> u8 {
+        self.cgram_display_base
+    }
+
+    pub(crate) fn set_cgram_display_base(&mut self, base: u8) {
+        assert!(
+            base == 0x00 || base == 0x08,
+            "CGRAM display base should be 0x00 or 0x08"
+        );
+
+        self.cgram_display_base = base;
+    }
+
+    pub(crate) fn get_char_rom(&self) -> CharRom {
+        self.char_rom
+    }
+
+    pub(crate) fn set_char_rom(&mut self, char_rom: CharRom) {
+        self.char_rom = char_rom;
+    }
+
     pub(crate) fn get_font(&self) -> Font {
         self.font
     }
 
     pub(crate) fn set_font(&mut self, font: Font) {
         assert!(
-            (self.get_line_mode() == LineMode::TwoLine) && (font == Font::Font5x8),
+            !((self.get_line_mode() == LineMode::TwoLine) && (font == Font::Font5x11)),
             "there is 2 line, font cannot be 5x11"
         );
 
@@ -116,6 +237,14 @@ impl LcdState {
     }
 
     pub(crate) fn set_cursor_pos(&mut self, pos: (u8, u8)) {
+        if self.row_layout.is_some() {
+            assert!(pos.1 < self.rows, "y offset too big");
+            assert!(pos.0 < self.get_row_capacity(pos.1), "x offset too big");
+
+            self.cursor_pos = pos;
+            return;
+        }
+
         let line_capacity = self.get_line_capacity();
         match self.line {
             LineMode::OneLine => {
@@ -131,6 +260,125 @@ impl LcdState {
         self.cursor_pos = pos;
     }
 
+    pub(crate) fn get_row_layout(&self) -> Option<&[(u8, u8)]> {
+        self.row_layout
+            .as_ref()
+            .map(|layout| &layout[..self.rows as usize])
+    }
+
+    // set DDRAM base address and usable columns of each row, it also sets rows
+    pub(crate) fn set_row_layout(&mut self, layout: &[(u8, u8)]) {
+        assert!(
+            (1..=4).contains(&layout.len()),
+            "row layout should have 1 to 4 rows"
+        );
+
+        assert!(
+            layout
+                .iter()
+                .all(|&(base, columns)| columns > 0 && base as u16 + columns as u16 <= 0x80),
+            "each row should have some columns, and should be in DDRAM"
+        );
+
+        layout
+            .iter()
+            .enumerate()
+            .for_each(|(index, &(base, columns))| {
+                assert!(
+                    layout[index + 1..]
+                        .iter()
+                        .all(|&(other_base, other_columns)| {
+                            base + columns <= other_base || other_base + other_columns <= base
+                        }),
+                    "rows should not overlap in DDRAM"
+                )
+            });
+
+        let mut row_layout = [(0, 0); 4];
+        row_layout[..layout.len()].copy_from_slice(layout);
+
+        self.row_layout = Some(row_layout);
+        self.rows = layout.len() as u8;
+    }
+
+    // rows the cursor can move through
+    fn get_addressable_rows(&self) -> u8 {
+        match (self.row_layout, self.get_line_mode()) {
+            (Some(_), _) => self.rows,
+            (None, LineMode::OneLine) => 1,
+            (None, LineMode::TwoLine) => 2,
+        }
+    }
+
+    // columns of a row the cursor can move through
+    pub(crate) fn get_row_capacity(&self, row: u8) -> u8 {
+        match self.row_layout {
+            Some(layout) => layout[row as usize].1,
+            None => self.get_line_capacity(),
+        }
+    }
+
+    // the cursor position after moving 1 step in `dir`, wrap to next or previous row at the row end
+    //
+    // Note: on a 4 rows display, AC of LCD continues from row 0 into row 2,
+    // the driver sets AC to the returned position, so the cursor moves to row 1 as displayed
+    pub(crate) fn next_cursor_pos(&self, pos: (u8, u8), dir: MoveDirection) -> (u8, u8) {
+        let row_cnt = self.get_addressable_rows();
+
+        match dir {
+            MoveDirection::LeftToRight => {
+                if pos.0 == self.get_row_capacity(pos.1) - 1 {
+                    (0, (pos.1 + 1) % row_cnt)
+                } else {
+                    (pos.0 + 1, pos.1)
+                }
+            }
+            MoveDirection::RightToLeft => {
+                if pos.0 == 0 {
+                    let prev_row = (pos.1 + row_cnt - 1) % row_cnt;
+                    (self.get_row_capacity(prev_row) - 1, prev_row)
+                } else {
+                    (pos.0 - 1, pos.1)
+                }
+            }
+        }
+    }
+
+    // in one line mode, pos.1 will always keep at 0
+    // in two line mode, the second line start at 0x40
+    // with a custom row layout, each row starts at its base address
+    pub(crate) fn pos_to_ddram_addr(&self, pos: (u8, u8)) -> u8 {
+        match self.row_layout {
+            Some(layout) => layout[pos.1 as usize].0 + pos.0,
+            None => pos.1 * 0x40 + pos.0,
+        }
+    }
+
+    pub(crate) fn ddram_addr_to_pos(&self, addr: u8) -> (u8, u8) {
+        if let Some(layout) = self.get_row_layout() {
+            return layout
+                .iter()
+                .enumerate()
+                .find(|&(_, &(base, columns))| (base..base + columns).contains(&addr))
+                .map(|(row, &(base, _))| (addr - base, row as u8))
+                .expect("DDRAM address out of row layout");
+        }
+
+        let line_capacity = self.get_line_capacity();
+
+        match self.get_line_mode() {
+            LineMode::OneLine => {
+                assert!(addr < line_capacity, "DDRAM address out of range");
+                (addr, 0)
+            }
+            LineMode::TwoLine => match addr {
+                _ if addr < line_capacity => (addr, 0),
+                _ if (0x40..0x40 + line_capacity).contains(&addr) => (addr - 0x40, 1),
+                _ => panic!("DDRAM address out of range"),
+            },
+        }
+    }
+
     pub(crate) fn get_display_offset(&self) -> u8 {
         self.display_offset
     }
@@ -152,48 +400,7 @@ impl LcdState {
         let line_capacity = self.get_line_capacity();
 
         match st {
-            ShiftType::CursorOnly => match dir {
-                MoveDirection::LeftToRight => match self.get_line_mode() {
-                    LineMode::OneLine => {
-                        if cur_cursor_pos.0 == line_capacity - 1 {
-                            self.set_cursor_pos((0, 0));
-                        } else {
-                            self.set_cursor_pos((cur_cursor_pos.0 + 1, 0));
-                        }
-                    }
-                    LineMode::TwoLine => {
-                        if cur_cursor_pos.0 == line_capacity - 1 {
-                            if cur_cursor_pos.1 == 0 {
-                                self.set_cursor_pos((0, 1));
-                            } else {
-                                self.set_cursor_pos((0, 0));
-                            }
-                        } else {
-                            self.set_cursor_pos((cur_cursor_pos.0 + 1, cur_cursor_pos.1));
-                        }
-                    }
-                },
-                MoveDirection::RightToLeft => match self.get_line_mode() {
-                    LineMode::OneLine => {
-                        if cur_cursor_pos.0 == 0 {
-                            self.set_cursor_pos((line_capacity - 1, 0));
-                        } else {
-                            self.set_cursor_pos((cur_cursor_pos.0 - 1, 0));
-                        }
-                    }
-                    LineMode::TwoLine => {
-                        if cur_cursor_pos.0 == 0 {
-                            if cur_cursor_pos.1 == 0 {
-                                self.set_cursor_pos((line_capacity - 1, 1));
-                            } else {
-                                self.set_cursor_pos((line_capacity - 1, 0));
-                            }
-                        } else {
-                            self.set_cursor_pos((cur_cursor_pos.0 - 1, cur_cursor_pos.1));
-                        }
-                    }
-                },
-            },
+            ShiftType::CursorOnly => self.set_cursor_pos(self.next_cursor_pos(cur_cursor_pos, dir)),
             ShiftType::CursorAndDisplay => match dir {
                 MoveDirection::LeftToRight => {
                     if cur_display_offset == line_capacity - 1 {
@@ -221,68 +428,83 @@ impl LcdState {
         self.ram_type = ram_type;
     }
 
+    pub(crate) fn get_cgram_graph(&self, index: u8) -> Option<[u8; 8]> {
+        self.cgram[index as usize]
+    }
+
+    pub(crate) fn set_cgram_graph(&mut self, index: u8, graph: Option<[u8; 8]>) {
+        self.cgram[index as usize] = graph;
+    }
+
+    pub(crate) fn set_cgram_row(&mut self, index: u8, row: u8, line_data: u8) {
+        if let Some(graph) = self.cgram[index as usize].as_mut() {
+            graph[row as usize] = line_data;
+        }
+    }
+
+    pub(crate) fn reset_cgram(&mut self) {
+        self.cgram = [None; 8];
+    }
+
+    // with 5x11 font, a graph takes 2 slots, only even indices are valid
+    pub(crate) fn assert_glyph_index(&self, index: u8) {
+        match self.get_font() {
+            Font::Font5x8 => assert!(index < 8, "Only 8 graphs allowed in CGRAM"),
+            Font::Font5x11 => assert!(
+                index < 8 && index & 1 == 0,
+                "Only 4 graphs allowed in CGRAM with 5x11 font, index should be 0, 2, 4 or 6"
+            ),
+        }
+    }
+
     pub(crate) fn calculate_pos_by_offset(
         &self,
         original_pos: (u8, u8),
         offset: (i8, i8),
     ) -> (u8, u8) {
-        let line_capacity = self.get_line_capacity();
+        let row_cnt = self.get_addressable_rows() as i16;
 
-        match self.get_line_mode() {
-            LineMode::OneLine => {
-                assert!(
-                    offset.0.unsigned_abs() < line_capacity,
-                    "x offset too big, should greater than -80 and less than 80"
-                );
-                assert!(offset.1 == 0, "y offset should always be 0 on OneLine Mode")
-            }
-            LineMode::TwoLine => {
-                assert!(
-                    offset.0.unsigned_abs() < line_capacity,
-                    "x offset too big, should greater than -40 and less than 40"
-                );
-                assert!(
-                    offset.1.abs() < 2,
-                    "y offset too big, should between -1 and 1"
-                )
-            }
-        }
+        assert!(
+            offset.0.unsigned_abs() < self.get_row_capacity(original_pos.1),
+            "x offset too big, should be less than row capacity"
+        );
+        assert!(
+            (offset.1.unsigned_abs() as i16) < row_cnt,
+            "y offset too big, should be less than row count"
+        );
 
-        match self.get_line_mode() {
-            LineMode::OneLine => {
-                let raw_x_pos = (original_pos.0 as i8) + offset.0;
-                if raw_x_pos < 0 {
-                    ((raw_x_pos + line_capacity as i8) as u8, 0)
-                } else if raw_x_pos > line_capacity as i8 {
-                    ((raw_x_pos - line_capacity as i8) as u8, 0)
-                } else {
-                    (raw_x_pos as u8, 0)
-                }
-            }
-            LineMode::TwoLine => {
-                let mut x_overflow: i8 = 0;
+        let wrap_row = |row: i16| row.rem_euclid(row_cnt);
 
-                // this likes a "adder" in logic circuit design
+        // this likes a "adder" in logic circuit design,
+        // x overflow carries into y, and y wraps around the rows
+        let mut raw_y_pos = wrap_row(original_pos.1 as i16 + offset.1 as i16);
+        let mut raw_x_pos = original_pos.0 as i16 + offset.0 as i16;
 
-                let mut raw_x_pos = (original_pos.0 as i8) + offset.0;
+        if raw_x_pos < 0 {
+            raw_y_pos = wrap_row(raw_y_pos - 1);
+            raw_x_pos += self.get_row_capacity(raw_y_pos as u8) as i16;
+        } else if raw_x_pos >= self.get_row_capacity(raw_y_pos as u8) as i16 {
+            raw_x_pos -= self.get_row_capacity(raw_y_pos as u8) as i16;
+            raw_y_pos = wrap_row(raw_y_pos + 1);
+        }
 
-                if raw_x_pos < 0 {
-                    raw_x_pos += 2;
-                    x_overflow = -1;
-                } else if raw_x_pos > line_capacity as i8 {
-                    raw_x_pos -= 2;
-                    x_overflow = 1;
-                }
+        (raw_x_pos as u8, raw_y_pos as u8)
+    }
+}
 
-                let mut raw_y_pos = (original_pos.1 as i8) + offset.1 + x_overflow;
-                if raw_y_pos < 0 {
-                    raw_y_pos += 2
-                } else if raw_y_pos > 2 {
-                    raw_y_pos -= 2
-                };
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use alloc::format;
 
-                (raw_x_pos as u8, raw_y_pos as u8)
-            }
-        }
+    use super::LcdState;
+
+    #[test]
+    fn debug_prints_cursor_and_line_mode() {
+        let mut state = LcdState::default();
+        state.set_cursor_pos((3, 1));
+
+        let debug = format!("{state:?}");
+        assert!(debug.contains("cursor_pos: (3, 1)"));
+        assert!(debug.contains("line: TwoLine"));
     }
 }
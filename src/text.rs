@@ -0,0 +1,191 @@
+//! Text layout helpers
+
+use crate::lcd::Ext;
+
+/// Write `str` into a rectangular region, wrap to the next line at word boundaries
+///
+/// Words longer than the region width are broken at the region width,
+/// white spaces at a soft break are dropped, `'\n'` forces a break,
+/// the rest of each written line is filled with white space.
+///
+/// Return the part of `str` that doesn't fit into the region
+///
+/// # Arguments
+///
+/// * `region` - (column, row, width, height) of the region
+pub fn write_wrapped<'s>(lcd: &mut impl Ext, str: &'s str, region: (u8, u8, u8, u8)) -> &'s str {
+    let (col, row, width, height) = region;
+
+    let mut rest = str;
+
+    for line in 0..height {
+        if rest.is_empty() {
+            break;
+        }
+
+        let (line_str, next) = split_line(rest, width as usize);
+
+        lcd.set_cursor_pos((col, row + line));
+        line_str
+            .chars()
+            .for_each(|char| lcd.write_char_to_cur(char));
+        (line_str.chars().count()..width as usize).for_each(|_| lcd.write_char_to_cur(' '));
+
+        rest = next;
+    }
+
+    rest
+}
+
+// split the first line no wider than `width` from `str`, return the line and the rest
+fn split_line(str: &str, width: usize) -> (&str, &str) {
+    let mut last_space = None;
+
+    for (count, (index, char)) in str.char_indices().enumerate() {
+        if char == '\n' {
+            return (&str[..index], &str[index + 1..]);
+        }
+
+        if count == width {
+            // break at the last space if there is one, otherwise break the word
+            let break_index = match char {
+                ' ' => index,
+                _ => last_space.unwrap_or(index),
+            };
+            return (
+                &str[..break_index],
+                str[break_index..].trim_start_matches(' '),
+            );
+        }
+
+        if char == ' ' {
+            last_space = Some(index);
+        }
+    }
+
+    (str, "")
+}
+
+/// [`MarginWriter`] writes text into a rectangular region,
+/// lines after a wrap start at a margin column, for hanging indent layouts
+pub struct MarginWriter {
+    region: (u8, u8, u8, u8),
+    margin: u8,
+    // next position to write, relative to the region
+    pos: (u8, u8),
+}
+
+impl MarginWriter {
+    /// Create a [`MarginWriter`]
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - (column, row, width, height) of the region
+    /// * `margin` - the column (relative to the region) where wrapped lines start, should be less than the region width
+    pub fn new(region: (u8, u8, u8, u8), margin: u8) -> Self {
+        assert!(margin < region.2, "margin should be less than region width");
+
+        Self {
+            region,
+            margin,
+            pos: (0, 0),
+        }
+    }
+
+    /// Move back to the start of the region
+    pub fn reset(&mut self) {
+        self.pos = (0, 0);
+    }
+
+    /// Write `str` from where last print stopped, wrap to the margin of next line at the region edge or at `'\n'`
+    ///
+    /// Return the part of `str` that doesn't fit into the region
+    pub fn print<'s>(&mut self, lcd: &mut impl Ext, str: &'s str) -> &'s str {
+        let (col, row, width, height) = self.region;
+
+        for (index, char) in str.char_indices() {
+            if char == '\n' {
+                self.pos = (self.margin, self.pos.1 + 1);
+                continue;
+            }
+
+            if self.pos.0 == width {
+                self.pos = (self.margin, self.pos.1 + 1);
+            }
+
+            if self.pos.1 >= height {
+                return &str[index..];
+            }
+
+            lcd.write_char_to_pos(char, (col + self.pos.0, row + self.pos.1));
+            self.pos.0 += 1;
+        }
+
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use crate::{
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::{write_wrapped, MarginWriter};
+
+    #[test]
+    fn wrap_breaks_at_word_boundary() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(write_wrapped(&mut lcd, "hello world", (2, 0, 5, 2)), "");
+        let (sender, _) = lcd.release();
+
+        let rows = sender.render::<16, 2>();
+        assert_eq!(rows[0].iter().collect::<String>(), "  hello         ");
+        assert_eq!(rows[1].iter().collect::<String>(), "  world         ");
+    }
+
+    #[test]
+    fn wrap_returns_the_rest_and_breaks_long_word() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        assert_eq!(
+            write_wrapped(&mut lcd, "hello world", (0, 0, 5, 1)),
+            "world"
+        );
+        assert_eq!(write_wrapped(&mut lcd, "abcdefgh", (0, 1, 5, 1)), "fgh");
+        let (sender, _) = lcd.release();
+
+        let rows = sender.render::<16, 2>();
+        assert_eq!(rows[0].iter().collect::<String>(), "hello           ");
+        assert_eq!(rows[1].iter().collect::<String>(), "abcde           ");
+    }
+
+    #[test]
+    fn wrapped_lines_start_at_margin() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut writer = MarginWriter::new((1, 0, 6, 2), 2);
+        assert_eq!(writer.print(&mut lcd, "log: abcdefgh"), "fgh");
+        let (sender, _) = lcd.release();
+
+        let rows = sender.render::<16, 2>();
+        assert_eq!(rows[0].iter().collect::<String>(), " log: a         ");
+        assert_eq!(rows[1].iter().collect::<String>(), "   bcde         ");
+    }
+
+    #[test]
+    #[should_panic(expected = "margin should be less than region width")]
+    fn margin_out_of_region_panics() {
+        MarginWriter::new((0, 0, 4, 2), 4);
+    }
+}
@@ -1,5 +1,7 @@
 //! Common tools
 
+use embedded_hal::delay::DelayNs;
+
 /// The state of a bit,
 /// It's either [`BitState::Clear`] to represent a 0
 /// or [`BitState::Set`] to represent a 1
@@ -43,3 +45,98 @@ impl BitOps for u8 {
         }
     }
 }
+
+/// A [`DelayNs`] implementation by spinning in a busy loop,
+/// for bringing up LCD without a hardware timer.
+///
+/// Note:
+/// It's imprecise, the real delay depends on CPU clock, compiler optimization and interrupts,
+/// don't use it in production.
+pub struct CycleDelay {
+    loops_per_us: u32,
+    // total spin loops, to check the loop count in tests
+    #[cfg(test)]
+    spun_loops: u64,
+}
+
+impl CycleDelay {
+    /// Create a [`CycleDelay`], `loops_per_us` is the count of spin loops in 1 microsecond,
+    /// a rough start is CPU clock in MHz
+    pub fn new(loops_per_us: u32) -> Self {
+        assert!(
+            loops_per_us > 0,
+            "loops per microsecond should be at least 1"
+        );
+
+        Self {
+            loops_per_us,
+            #[cfg(test)]
+            spun_loops: 0,
+        }
+    }
+
+    /// Get the count of spin loops in 1 microsecond
+    pub fn get_loops_per_us(&self) -> u32 {
+        self.loops_per_us
+    }
+
+    /// Get the count of spin loops of a delay
+    pub fn loops_of_ns(&self, ns: u32) -> u64 {
+        (ns as u64 * self.loops_per_us as u64).div_ceil(1_000)
+    }
+}
+
+impl DelayNs for CycleDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        let loops = self.loops_of_ns(ns);
+        (0..loops).for_each(|_| core::hint::spin_loop());
+
+        #[cfg(test)]
+        {
+            self.spun_loops += loops;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::delay::DelayNs;
+
+    use super::CycleDelay;
+
+    #[test]
+    fn loops_of_ns_rounds_up() {
+        let delayer = CycleDelay::new(16);
+
+        assert_eq!(delayer.loops_of_ns(1_000), 16);
+        assert_eq!(delayer.loops_of_ns(37_000), 16 * 37);
+        // 16 loops per 1000 ns, 1 ns still needs 1 loop
+        assert_eq!(delayer.loops_of_ns(1), 1);
+        assert_eq!(delayer.loops_of_ns(1_001), 17);
+        assert_eq!(delayer.loops_of_ns(0), 0);
+
+        // no overflow with the largest inputs
+        assert_eq!(
+            CycleDelay::new(u32::MAX).loops_of_ns(u32::MAX),
+            (u32::MAX as u64 * u32::MAX as u64).div_ceil(1_000)
+        );
+    }
+
+    #[test]
+    fn delay_us_spins_expected_loops() {
+        let mut delayer = CycleDelay::new(8);
+
+        delayer.delay_us(1_520);
+        assert_eq!(delayer.spun_loops, 8 * 1_520);
+
+        delayer.delay_ms(1);
+        delayer.delay_ns(0);
+        assert_eq!(delayer.spun_loops, 8 * 1_520 + 8 * 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1")]
+    fn zero_loops_per_us_panics() {
+        CycleDelay::new(0);
+    }
+}
@@ -0,0 +1,37 @@
+//! Widgets built on top of [`Basic`](crate::lcd::Basic) commands
+
+#[cfg(feature = "embedded-graphics")]
+mod cgram_canvas;
+mod dial;
+mod live_clock;
+mod marquee;
+mod masked_field;
+mod progress_bar;
+mod screen_writer;
+mod screensaver;
+mod tall_progress_bar;
+
+#[cfg(feature = "embedded-graphics")]
+pub use cgram_canvas::CgramCanvas;
+pub use dial::Dial;
+pub use live_clock::LiveClock;
+pub use marquee::{Marquee, MARQUEE_MIN_STEP_DELAY_US};
+pub use masked_field::MaskedField;
+pub use progress_bar::ProgressBar;
+pub use screen_writer::ScreenWriter;
+pub use screensaver::Screensaver;
+pub use tall_progress_bar::TallProgressBar;
+
+use crate::lcd::graph::{self, CELL_COLUMNS};
+
+// a full filled rectangle in character ROM
+const FULL_BLOCK: u8 = 0xFF;
+
+// filled pixel columns (0 to 5) of a cell, for a bar `cells` long at `percent`,
+// rounded like [`Ext::draw_horizontal_bar()`](crate::lcd::Ext::draw_horizontal_bar)
+fn cell_fill(cells: u8, percent: u8, cell: u8) -> u8 {
+    assert!(cell < cells, "cell out of bar");
+
+    let filled = graph::scale_to_steps(percent, 100, cells as u16 * CELL_COLUMNS as u16);
+    graph::cell_fill(filled, cell, CELL_COLUMNS)
+}
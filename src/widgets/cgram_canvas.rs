@@ -0,0 +1,97 @@
+//! A tiny bitmap made of custom graphs, drawn with embedded-graphics
+
+use core::convert::Infallible;
+
+use embedded_graphics_core::{
+    geometry::{OriginDimensions, Size},
+    pixelcolor::BinaryColor,
+    Pixel,
+};
+
+use crate::lcd::{graph::CELL_COLUMNS, Basic, Pos};
+
+/// [`CgramCanvas`] is a bitmap up to 40x8 pixels, backed by up to 8 custom graphs placed in a row
+///
+/// Drawing only changes the buffer in MCU's memory, [`CgramCanvas::flush()`] uploads the graphs,
+/// and graphs already in CGRAM are not uploaded again.
+/// The canvas consumes CGRAM slot 0 to `chars - 1`.
+pub struct CgramCanvas {
+    chars: u8,
+    pos: (u8, u8),
+    graphs: [[u8; 8]; 8],
+}
+
+impl CgramCanvas {
+    /// Create a [`CgramCanvas`] `chars` cells wide, with its left cell at `pos`
+    pub fn new(chars: u8, pos: impl Into<Pos>) -> Self {
+        let pos: (u8, u8) = pos.into().into();
+        assert!((1..=8).contains(&chars), "Only 8 graphs allowed in CGRAM");
+
+        Self {
+            chars,
+            pos,
+            graphs: [[0; 8]; 8],
+        }
+    }
+
+    /// Get the graphs of the canvas, from left to right
+    pub fn get_graphs(&self) -> &[[u8; 8]] {
+        &self.graphs[..self.chars as usize]
+    }
+
+    /// Upload the changed graphs into CGRAM, and place the graphs on the display
+    pub fn flush(&self, lcd: &mut impl Basic) {
+        assert!(
+            self.chars <= lcd.max_custom_glyphs(),
+            "Not enough CGRAM slots for canvas"
+        );
+
+        self.get_graphs()
+            .iter()
+            .enumerate()
+            .for_each(|(index, graph)| {
+                if lcd.get_cgram_graph(index as u8) != Some(*graph) {
+                    lcd.write_graph_to_cgram(index as u8, graph);
+                }
+            });
+
+        lcd.set_cursor_pos(self.pos);
+        (0..self.chars).for_each(|index| lcd.write_graph_to_cur(index));
+    }
+}
+
+impl OriginDimensions for CgramCanvas {
+    fn size(&self) -> Size {
+        Size::new(self.chars as u32 * CELL_COLUMNS as u32, 8)
+    }
+}
+
+impl embedded_graphics_core::draw_target::DrawTarget for CgramCanvas {
+    type Color = BinaryColor;
+
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let width = self.chars as i32 * CELL_COLUMNS as i32;
+
+        pixels
+            .into_iter()
+            .filter(|Pixel(point, _)| (0..width).contains(&point.x) && (0..8).contains(&point.y))
+            .for_each(|Pixel(point, color)| {
+                let line =
+                    &mut self.graphs[point.x as usize / CELL_COLUMNS as usize][point.y as usize];
+                // the left pixel is the highest bit of 5
+                let mask = 1 << (CELL_COLUMNS - 1 - (point.x as u8 % CELL_COLUMNS));
+
+                match color {
+                    BinaryColor::On => *line |= mask,
+                    BinaryColor::Off => *line &= !mask,
+                }
+            });
+
+        Ok(())
+    }
+}
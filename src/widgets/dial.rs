@@ -0,0 +1,102 @@
+//! A single cell dial, with a needle rotating in 8 directions
+
+use crate::lcd::{Basic, Pos};
+
+// needle glyphs, pivot at (2, 4), start from pointing up, rotating clockwise
+const DIAL_GLYPHS: [[u8; 8]; 8] = [
+    [
+        0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00000, 0b00000,
+    ],
+    [
+        0b00001, 0b00001, 0b00010, 0b00010, 0b00100, 0b00000, 0b00000, 0b00000,
+    ],
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00111, 0b00000, 0b00000, 0b00000,
+    ],
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00100, 0b00010, 0b00010, 0b00001,
+    ],
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00100, 0b00100, 0b00100, 0b00100,
+    ],
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b00100, 0b01000, 0b01000, 0b10000,
+    ],
+    [
+        0b00000, 0b00000, 0b00000, 0b00000, 0b11100, 0b00000, 0b00000, 0b00000,
+    ],
+    [
+        0b10000, 0b10000, 0b01000, 0b01000, 0b00100, 0b00000, 0b00000, 0b00000,
+    ],
+];
+
+/// [`Dial`] shows a needle in one cell, the needle can point to 8 directions
+///
+/// It consumes one CGRAM slot, and rewrites the slot when the angle changes
+pub struct Dial {
+    index: u8,
+    last_angle: Option<u8>,
+}
+
+impl Dial {
+    /// Create a [`Dial`] which uses CGRAM slot `index`
+    pub fn new(index: u8) -> Self {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+
+        Self {
+            index,
+            last_angle: None,
+        }
+    }
+
+    /// Get the needle graph of specific angle
+    pub fn glyph(angle_0_to_7: u8) -> &'static [u8; 8] {
+        assert!(angle_0_to_7 < 8, "angle should between 0 and 7");
+
+        &DIAL_GLYPHS[angle_0_to_7 as usize]
+    }
+
+    /// Show the dial at `pos`
+    ///
+    /// # Arguments
+    ///
+    /// * `pos` - the position of the dial
+    /// * `angle_0_to_7` - the direction of the needle, `0` is pointing up, and rotates clockwise by 45 degrees per step
+    pub fn render(&mut self, lcd: &mut impl Basic, pos: impl Into<Pos>, angle_0_to_7: u8) {
+        let pos: (u8, u8) = pos.into().into();
+        let graph = Self::glyph(angle_0_to_7);
+
+        if self.last_angle != Some(angle_0_to_7) {
+            lcd.write_graph_to_cgram(self.index, graph);
+            self.last_angle = Some(angle_0_to_7);
+        }
+
+        lcd.set_cursor_pos(pos);
+        lcd.write_graph_to_cur(self.index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dial;
+
+    #[test]
+    fn glyph_of_opposite_angles() {
+        // angle 0 points up: column 2 lit above the pivot
+        assert_eq!(
+            Dial::glyph(0),
+            &[0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0, 0, 0]
+        );
+        // angle 4 points down: column 2 lit below the pivot
+        assert_eq!(
+            Dial::glyph(4),
+            &[0, 0, 0, 0, 0b00100, 0b00100, 0b00100, 0b00100]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "angle should between 0 and 7")]
+    fn angle_out_of_range_panics() {
+        Dial::glyph(8);
+    }
+}
@@ -0,0 +1,108 @@
+//! A `HH:MM:SS` clock which only rewrites changed fields
+
+use crate::lcd::{Basic, Pos};
+
+/// [`LiveClock`] shows time as `HH:MM:SS`, and only rewrites the fields changed since last tick
+#[derive(Default)]
+pub struct LiveClock {
+    // last rendered position and (hour, minute, second)
+    last: Option<((u8, u8), [u8; 3])>,
+}
+
+impl LiveClock {
+    /// Create a [`LiveClock`], the first tick will draw the whole clock
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force the next tick to draw the whole clock, e.g. after the display is cleaned
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Show the time at `pos`, only the changed fields will be rewritten
+    pub fn tick(&mut self, lcd: &mut impl Basic, pos: impl Into<Pos>, h: u8, m: u8, s: u8) {
+        let pos: (u8, u8) = pos.into().into();
+        assert!(
+            h < 100 && m < 100 && s < 100,
+            "each field should have 2 digits"
+        );
+
+        let fields = [h, m, s];
+
+        let last_fields = match self.last {
+            Some((last_pos, last_fields)) if last_pos == pos => Some(last_fields),
+            _ => None,
+        };
+
+        // colons only need to be drawn in a full redraw
+        if last_fields.is_none() {
+            [2u8, 5].iter().for_each(|&offset| {
+                lcd.set_cursor_pos((pos.0 + offset, pos.1));
+                lcd.write_u8_to_cur(b':');
+            });
+        }
+
+        fields
+            .iter()
+            .enumerate()
+            .filter(|&(index, &field)| last_fields.map(|last| last[index]) != Some(field))
+            .for_each(|(index, &field)| {
+                lcd.set_cursor_pos((pos.0 + index as u8 * 3, pos.1));
+                lcd.write_u8_to_cur(b'0' + field / 10);
+                lcd.write_u8_to_cur(b'0' + field % 10);
+            });
+
+        self.last = Some((pos, fields));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        command::{Bits, Command, RegisterSelection},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::LiveClock;
+
+    // commands sent by creating a Lcd, then ticking the clock at each time
+    fn commands_of_ticks(times: &[[u8; 3]]) -> Vec<Command> {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut clock = LiveClock::new();
+        times
+            .iter()
+            .for_each(|&[h, m, s]| clock.tick(&mut lcd, (4, 1), h, m, s));
+
+        sender.get_commands().to_vec()
+    }
+
+    #[test]
+    fn only_changed_seconds_are_written() {
+        let first_tick = commands_of_ticks(&[[12, 34, 56]]);
+        let both_ticks = commands_of_ticks(&[[12, 34, 56], [12, 34, 57]]);
+        assert_eq!(both_ticks[..first_tick.len()], first_tick);
+
+        let second_tick = &both_ticks[first_tick.len()..];
+        let written: Vec<u8> = second_tick
+            .iter()
+            .filter(|command| command.get_register_selection() == RegisterSelection::Data)
+            .filter_map(|command| match command.get_data() {
+                Some(Bits::Bit8(byte)) => Some(byte),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(written, b"57");
+
+        // cursor is moved to the seconds field at (10, 1)
+        assert!(second_tick
+            .iter()
+            .any(|command| command.get_data() == Some(Bits::Bit8(0b1000_0000 | 0x4A))));
+    }
+}
@@ -0,0 +1,180 @@
+//! Scroll a string through a single row of the display window
+
+use crate::lcd::Ext;
+
+/// The minimum delay (in microseconds) between 2 steps of a [`Marquee`],
+/// any faster scrolling will be unreadable on LCD1602
+pub const MARQUEE_MIN_STEP_DELAY_US: u32 = 50_000;
+
+/// [`Marquee`] scrolls a string from right to left through a row of the display window
+///
+/// Unlike shifting the display window, it rewrites the cells of the row,
+/// thus other rows won't be affected.
+pub struct Marquee<'s> {
+    text: &'s str,
+    row: u8,
+    gap: u8,
+    start_pause_ticks: u8,
+    end_pause_ticks: u8,
+    offset: usize,
+    // ticks already held at current offset
+    held_ticks: u8,
+}
+
+impl<'s> Marquee<'s> {
+    /// Create a [`Marquee`] on `row`, with a 1 cell gap between the end of the text and the start of the next loop
+    pub fn new(text: &'s str, row: u8) -> Self {
+        Self {
+            text,
+            row,
+            gap: 1,
+            start_pause_ticks: 0,
+            end_pause_ticks: 0,
+            offset: 0,
+            held_ticks: 0,
+        }
+    }
+
+    /// Set the gap (in cells) between the end of the text and the start of the next loop
+    pub fn set_gap(mut self, gap: u8) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Get the gap (in cells) between the end of the text and the start of the next loop
+    pub fn get_gap(&self) -> u8 {
+        self.gap
+    }
+
+    /// Set the ticks to hold when the start of the text is at the left edge of display window
+    pub fn set_start_pause_ticks(mut self, ticks: u8) -> Self {
+        self.start_pause_ticks = ticks;
+        self
+    }
+
+    /// Get the ticks to hold when the start of the text is at the left edge of display window
+    pub fn get_start_pause_ticks(&self) -> u8 {
+        self.start_pause_ticks
+    }
+
+    /// Set the ticks to hold when the end of the text is revealed at the right edge of display window
+    pub fn set_end_pause_ticks(mut self, ticks: u8) -> Self {
+        self.end_pause_ticks = ticks;
+        self
+    }
+
+    /// Get the ticks to hold when the end of the text is revealed at the right edge of display window
+    pub fn get_end_pause_ticks(&self) -> u8 {
+        self.end_pause_ticks
+    }
+
+    /// Get the count of steps to scroll the text once
+    pub fn steps_per_loop(&self) -> u32 {
+        self.text.chars().count() as u32 + self.gap as u32
+    }
+
+    /// Calculate the delay (in microseconds) of each step, to scroll the text once in `total_duration_us`,
+    /// pause ticks are counted as steps
+    ///
+    /// Note:
+    /// The delay won't be less than [`MARQUEE_MIN_STEP_DELAY_US`], so the scroll may overrun the duration
+    pub fn step_delay_for_duration(&self, total_duration_us: u32) -> u32 {
+        let ticks_per_loop =
+            self.steps_per_loop() + self.start_pause_ticks as u32 + self.end_pause_ticks as u32;
+
+        (total_duration_us / ticks_per_loop.max(1)).max(MARQUEE_MIN_STEP_DELAY_US)
+    }
+
+    /// Show current frame of the marquee, and move the text 1 cell left for the next frame,
+    /// unless the marquee is holding at the start or the end of the text
+    pub fn tick<L: Ext + ?Sized>(&mut self, lcd: &mut L) {
+        let steps_per_loop = self.steps_per_loop() as usize;
+
+        if steps_per_loop == 0 {
+            return;
+        }
+
+        let columns = lcd.get_columns();
+
+        (0..columns).for_each(|visible_col| {
+            let char = self
+                .text
+                .chars()
+                .nth((self.offset + visible_col as usize) % steps_per_loop)
+                .unwrap_or(' ');
+
+            lcd.write_char_to_pos(char, lcd.visible_to_ddram(visible_col, self.row));
+        });
+
+        // the offset where the end of the text reaches the right edge of display window
+        let end_offset = self.text.chars().count().saturating_sub(columns as usize);
+
+        let pause_ticks = if self.offset == 0 {
+            self.start_pause_ticks
+        } else if self.offset == end_offset {
+            self.end_pause_ticks
+        } else {
+            0
+        };
+
+        if self.held_ticks < pause_ticks {
+            self.held_ticks += 1;
+            return;
+        }
+
+        self.held_ticks = 0;
+        self.offset = (self.offset + 1) % steps_per_loop;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::{Marquee, MARQUEE_MIN_STEP_DELAY_US};
+
+    #[test]
+    fn step_delay_fits_duration() {
+        // 5 chars and 1 cell gap, 6 steps per loop
+        let marquee = Marquee::new("Hello", 0);
+        assert_eq!(marquee.step_delay_for_duration(600_000), 100_000);
+
+        // pause ticks are counted as steps
+        let marquee = marquee.set_start_pause_ticks(2).set_end_pause_ticks(2);
+        assert_eq!(marquee.step_delay_for_duration(600_000), 60_000);
+    }
+
+    #[test]
+    fn step_delay_is_capped_for_long_text() {
+        let marquee = Marquee::new("A very long message that cannot fit the duration", 0);
+        assert_eq!(
+            marquee.step_delay_for_duration(1_000_000),
+            MARQUEE_MIN_STEP_DELAY_US
+        );
+    }
+
+    #[test]
+    fn start_pause_holds_offset() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut marquee = Marquee::new("A long label to scroll", 0).set_start_pause_ticks(3);
+
+        (0..3).for_each(|_| {
+            marquee.tick(&mut lcd);
+            assert_eq!(marquee.offset, 0);
+        });
+        marquee.tick(&mut lcd);
+        assert_eq!(marquee.offset, 1);
+        marquee.tick(&mut lcd);
+        assert_eq!(marquee.offset, 2);
+        let (sender, _) = lcd.release();
+
+        // the frame at offset 1 is shown by the last tick
+        assert_eq!(sender.get_ddram()[..4], *b" lon");
+    }
+}
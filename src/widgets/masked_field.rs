@@ -0,0 +1,109 @@
+//! A masked input field, e.g. for PIN entry
+
+use crate::lcd::{Basic, Pos};
+
+// the byte shown in place of a hidden character
+const MASK: u8 = b'*';
+
+/// [`MaskedField`] shows a `*` for each character pushed, the last character can be revealed for a while before masked
+pub struct MaskedField {
+    pos: (u8, u8),
+    width: u8,
+    len: u8,
+}
+
+impl MaskedField {
+    /// Create a [`MaskedField`] `width` cells wide, with its left cell at `pos`
+    pub fn new(pos: impl Into<Pos>, width: u8) -> Self {
+        let pos: (u8, u8) = pos.into().into();
+        Self { pos, width, len: 0 }
+    }
+
+    /// Get the count of characters pushed
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    #[allow(missing_docs)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Push a character into the field, the character is shown for `reveal_ms` (in milliseconds), then replaced by `*`,
+    /// if `reveal_ms` is 0, `*` is shown directly
+    ///
+    /// Return false if the field is full, and the character is dropped
+    pub fn push(&mut self, lcd: &mut impl Basic, c: char, reveal_ms: u32) -> bool {
+        if self.len == self.width {
+            return false;
+        }
+
+        let pos = (self.pos.0 + self.len, self.pos.1);
+
+        if reveal_ms > 0 {
+            let char_rom = lcd.get_char_rom();
+            lcd.set_cursor_pos(pos);
+            lcd.write_u8_to_cur(char_rom.map_char(c).unwrap_or(char_rom.replacement_byte()));
+            lcd.delay_ms(reveal_ms);
+        }
+
+        lcd.set_cursor_pos(pos);
+        lcd.write_u8_to_cur(MASK);
+
+        self.len += 1;
+        true
+    }
+
+    /// Fill the field with white space, and drop all characters pushed
+    pub fn clear(&mut self, lcd: &mut impl Basic) {
+        lcd.set_cursor_pos(self.pos);
+        (0..self.width).for_each(|_| lcd.write_u8_to_cur(b' '));
+
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        command::{Bits, ReadWriteOp, RegisterSelection},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::MaskedField;
+
+    #[test]
+    fn revealed_char_is_masked_after_reveal() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut field = MaskedField::new((4, 1), 2);
+        assert!(field.push(&mut lcd, '7', 300));
+        assert!(field.push(&mut lcd, '8', 0));
+        assert!(!field.push(&mut lcd, '9', 0));
+        assert_eq!(field.len(), 2);
+        field.clear(&mut lcd);
+        assert!(field.is_empty());
+        let (sender, delayer) = lcd.release();
+
+        let written: Vec<u8> = sender
+            .get_commands()
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Data
+                    && command.get_read_write_op() == ReadWriteOp::Write
+            })
+            .filter_map(|command| match command.get_data() {
+                Some(Bits::Bit8(byte)) => Some(byte),
+                _ => None,
+            })
+            .collect();
+        // '7' is revealed before masked, then the field is cleared
+        assert_eq!(written, b"7**  ");
+        assert!(delayer.get_delays_ns().contains(&300_000_000));
+    }
+}
@@ -0,0 +1,138 @@
+//! A progress bar with 5 steps per cell
+
+use crate::lcd::{graph::column_fill_graph, Basic, Pos};
+
+use super::cell_fill;
+
+// graphs with 1 to 5 columns filled
+const GRAPH_CNT: u8 = 5;
+
+/// [`ProgressBar`] fills cells across a span of a row
+///
+/// Each cell has 5 pixel columns, thus a 16 cells bar has 80 steps.
+/// Full cells are placed before the partial filled cell,
+/// cells with 1 to 5 columns filled are drawn with 5 custom graphs uploaded at creation,
+/// thus the bar consumes 5 contiguous CGRAM slots, and never rewrites CGRAM when rendering.
+pub struct ProgressBar {
+    first_index: u8,
+}
+
+impl ProgressBar {
+    /// Create a [`ProgressBar`] which uploads its graphs into CGRAM slot `first_index` to `first_index + 4`
+    pub fn new(lcd: &mut impl Basic, first_index: u8) -> Self {
+        assert!(
+            first_index <= 8 - GRAPH_CNT,
+            "Only 8 graphs allowed in CGRAM"
+        );
+
+        // uploading CGRAM will move AC into CGRAM, move it back
+        let cursor_pos = lcd.is_in_ddram().then(|| lcd.get_cursor_pos());
+        (1..=GRAPH_CNT).for_each(|columns| {
+            lcd.write_graph_to_cgram(first_index + columns - 1, &column_fill_graph(columns))
+        });
+        if let Some(cursor_pos) = cursor_pos {
+            lcd.set_cursor_pos(cursor_pos);
+        }
+
+        Self { first_index }
+    }
+
+    /// Get the filled pixel columns (0 to 5) of each cell, for a bar `cells` long at `percent`,
+    /// rounded to the nearest pixel column
+    pub fn cell_fill(cells: u8, percent: u8, cell: u8) -> u8 {
+        cell_fill(cells, percent, cell)
+    }
+
+    /// Get the graph of the partial filled cell, for a bar `cells` long at `percent`,
+    /// [`None`] if there is no partial filled cell
+    pub fn partial_graph(cells: u8, percent: u8) -> Option<[u8; 8]> {
+        (0..cells)
+            .map(|cell| cell_fill(cells, percent, cell))
+            .find(|&columns| 0 < columns && columns < 5)
+            .map(column_fill_graph)
+    }
+
+    /// Show the progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - the position of the left cell of the bar
+    /// * `cells` - the width (in cells) of the bar
+    /// * `percent` - the progress, larger than 100 will be treated as 100
+    pub fn render(&self, lcd: &mut impl Basic, start: impl Into<Pos>, cells: u8, percent: u8) {
+        let start: (u8, u8) = start.into().into();
+        assert!(
+            start.0 as u16 + cells as u16 <= lcd.get_line_capacity() as u16,
+            "bar is out of line"
+        );
+
+        lcd.set_cursor_pos(start);
+
+        (0..cells).for_each(|cell| match cell_fill(cells, percent, cell) {
+            0 => lcd.write_u8_to_cur(b' '),
+            columns => lcd.write_graph_to_cur(self.first_index + columns - 1),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{Bits, RegisterSelection},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::ProgressBar;
+
+    #[test]
+    fn partial_graph_at_37_percent() {
+        // 25 pixel columns, 9 of them filled
+        let fill: [u8; 5] = core::array::from_fn(|cell| ProgressBar::cell_fill(5, 37, cell as u8));
+        assert_eq!(fill, [5, 4, 0, 0, 0]);
+        assert_eq!(ProgressBar::partial_graph(5, 37), Some([0b11110; 8]));
+        assert_eq!(ProgressBar::partial_graph(5, 40), None);
+    }
+
+    #[test]
+    fn render_places_full_cells_before_partial_cell() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        ProgressBar::new(&mut lcd, 2).render(&mut lcd, (0, 1), 5, 37);
+        let (sender, _) = lcd.release();
+
+        // slot 2 has 1 column filled, slot 6 has 5
+        assert_eq!(sender.get_ddram()[0x40..0x45], [6, 5, b' ', b' ', b' ']);
+    }
+
+    #[test]
+    fn render_never_touches_cgram() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let bar = ProgressBar::new(&mut lcd, 0);
+        let (sender, delayer) = lcd.release();
+        let upload_len = sender.get_commands().len();
+
+        // rebuilding Lcd sends no CGRAM command
+        let mut lcd = Lcd::new(sender, delayer, Config::default(), 0);
+        bar.render(&mut lcd, (0, 0), 16, 37);
+        bar.render(&mut lcd, (0, 0), 16, 81);
+        let (sender, _) = lcd.release();
+
+        let cgram_addr_sets = sender.get_commands()[upload_len..]
+            .iter()
+            .filter(|command| {
+                command.get_register_selection() == RegisterSelection::Command
+                    && matches!(
+                        command.get_data(),
+                        Some(Bits::Bit8(0b0100_0000..=0b0111_1111))
+                    )
+            })
+            .count();
+        assert_eq!(cgram_addr_sets, 0);
+    }
+}
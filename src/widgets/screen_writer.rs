@@ -0,0 +1,121 @@
+//! A whole screen writer which only rewrites changed rows
+
+use crate::lcd::Ext;
+
+// max rows and columns a screen can have
+const MAX_ROWS: usize = 4;
+const MAX_COLUMNS: usize = 40;
+
+/// [`ScreenWriter`] writes every row of the display window from a slice of strings,
+/// each row is padded with white space to the width of display window
+#[derive(Default)]
+pub struct ScreenWriter {
+    // bytes last written to each row, [`None`] if the row is not written since last invalidate
+    last_rows: [Option<[u8; MAX_COLUMNS]>; MAX_ROWS],
+}
+
+impl ScreenWriter {
+    /// Create a [`ScreenWriter`], the first [`ScreenWriter::write_screen_diff()`] will draw all rows
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force the next [`ScreenWriter::write_screen_diff()`] to draw all rows, e.g. after the display is cleaned
+    pub fn invalidate(&mut self) {
+        self.last_rows = [None; MAX_ROWS];
+    }
+
+    /// Write all rows
+    pub fn write_screen(&mut self, lcd: &mut impl Ext, rows: &[&str]) {
+        self.invalidate();
+        self.write_screen_diff(lcd, rows);
+    }
+
+    /// Write the rows whose content changed since last write
+    pub fn write_screen_diff(&mut self, lcd: &mut impl Ext, rows: &[&str]) {
+        assert!(
+            rows.len() <= lcd.get_rows() as usize,
+            "more rows than the display has"
+        );
+
+        let columns = lcd.get_columns() as usize;
+        let char_rom = lcd.get_char_rom();
+
+        rows.iter().enumerate().for_each(|(row, str)| {
+            let mut bytes = [b' '; MAX_COLUMNS];
+            bytes.iter_mut().zip(str.chars()).for_each(|(byte, char)| {
+                *byte = char_rom
+                    .map_char(char)
+                    .unwrap_or(char_rom.replacement_byte())
+            });
+
+            if self.last_rows[row] == Some(bytes) {
+                return;
+            }
+
+            lcd.set_cursor_pos((0, row as u8));
+            bytes[..columns]
+                .iter()
+                .for_each(|&byte| lcd.write_u8_to_cur(byte));
+
+            self.last_rows[row] = Some(bytes);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        command::{Bits, Command, RegisterSelection},
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::ScreenWriter;
+
+    // commands sent by creating a Lcd, then writing each screen with diff
+    fn commands_of_screens(screens: &[[&str; 2]]) -> Vec<Command> {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut writer = ScreenWriter::new();
+        screens
+            .iter()
+            .for_each(|screen| writer.write_screen_diff(&mut lcd, screen));
+
+        sender.get_commands().to_vec()
+    }
+
+    #[test]
+    fn only_changed_row_is_written() {
+        let first_screen = commands_of_screens(&[["Temp", "21C"]]);
+        let both_screens = commands_of_screens(&[["Temp", "21C"], ["Temp", "22C"]]);
+        assert_eq!(both_screens[..first_screen.len()], first_screen);
+
+        let second_screen = &both_screens[first_screen.len()..];
+        let written: Vec<u8> = second_screen
+            .iter()
+            .filter(|command| command.get_register_selection() == RegisterSelection::Data)
+            .filter_map(|command| match command.get_data() {
+                Some(Bits::Bit8(byte)) => Some(byte),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(written, b"22C             ");
+
+        // Set DDRAM Address to (0, 1) before the row is written
+        assert_eq!(
+            second_screen
+                .iter()
+                .find(
+                    |command| command.get_register_selection() == RegisterSelection::Command
+                        && matches!(command.get_data(), Some(Bits::Bit8(0b1000_0000..)))
+                )
+                .and_then(|command| command.get_data()),
+            Some(Bits::Bit8(0b1000_0000 | 0x40))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Move the display window periodically to spread wear of the display
+
+use crate::{
+    command::{MoveDirection, ShiftType},
+    lcd::Basic,
+};
+
+/// [`Screensaver`] nudges the display window 1 cell away and back periodically,
+/// to prevent burn-in of OLED based character displays
+pub struct Screensaver {
+    interval_us: u64,
+    // the time of last nudge, [`None`] before the first tick
+    last_us: Option<u64>,
+    nudged: bool,
+}
+
+impl Screensaver {
+    /// Create a [`Screensaver`], which nudges the display window every `interval_us`
+    pub fn new(interval_us: u64) -> Self {
+        assert!(interval_us > 0, "interval should be larger than 0");
+
+        Self {
+            interval_us,
+            last_us: None,
+            nudged: false,
+        }
+    }
+
+    /// Get the interval (in microseconds) between 2 nudges
+    pub fn get_interval_us(&self) -> u64 {
+        self.interval_us
+    }
+
+    /// Nudge the display window if `interval_us` elapsed since last nudge,
+    /// otherwise do nothing
+    ///
+    /// # Arguments
+    ///
+    /// * `now_us` - a monotonic timestamp (in microseconds)
+    pub fn tick(&mut self, lcd: &mut impl Basic, now_us: u64) {
+        let last_us = *self.last_us.get_or_insert(now_us);
+
+        if now_us.saturating_sub(last_us) < self.interval_us {
+            return;
+        }
+
+        let dir = match self.nudged {
+            false => MoveDirection::RightToLeft,
+            true => MoveDirection::LeftToRight,
+        };
+        lcd.shift_cursor_or_display(ShiftType::CursorAndDisplay, dir);
+
+        self.nudged = !self.nudged;
+        self.last_us = Some(now_us);
+    }
+
+    /// Move the display window back if it's nudged, e.g. before the screensaver is dropped
+    pub fn restore(&mut self, lcd: &mut impl Basic) {
+        if self.nudged {
+            lcd.shift_cursor_or_display(ShiftType::CursorAndDisplay, MoveDirection::LeftToRight);
+            self.nudged = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Basic, Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::Screensaver;
+
+    #[test]
+    fn shift_only_after_interval() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        let mut screensaver = Screensaver::new(1_000);
+
+        screensaver.tick(&mut lcd, 5_000);
+        screensaver.tick(&mut lcd, 5_999);
+        assert_eq!(lcd.get_display_offset(), 0);
+
+        screensaver.tick(&mut lcd, 6_000);
+        let nudged_offset = lcd.get_display_offset();
+        assert_ne!(nudged_offset, 0);
+
+        screensaver.tick(&mut lcd, 6_500);
+        assert_eq!(lcd.get_display_offset(), nudged_offset);
+
+        screensaver.tick(&mut lcd, 7_000);
+        assert_eq!(lcd.get_display_offset(), 0);
+    }
+}
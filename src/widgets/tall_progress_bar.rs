@@ -0,0 +1,103 @@
+//! A progress bar two rows tall
+
+use crate::lcd::{graph::column_fill_graph, Basic, Pos};
+
+use super::{cell_fill, FULL_BLOCK};
+
+/// [`TallProgressBar`] fills cells across a span on both rows at once
+///
+/// Each cell has 5 pixel columns, the partial filled cell is drawn with a custom graph,
+/// thus the bar consumes only one CGRAM slot, and rewrites the slot when the partial cell changes.
+pub struct TallProgressBar {
+    index: u8,
+    last_partial_columns: Option<u8>,
+}
+
+impl TallProgressBar {
+    /// Create a [`TallProgressBar`] which uses CGRAM slot `index` for the partial filled cell
+    pub fn new(index: u8) -> Self {
+        assert!(index < 8, "Only 8 graphs allowed in CGRAM");
+
+        Self {
+            index,
+            last_partial_columns: None,
+        }
+    }
+
+    /// Get the filled pixel columns (0 to 5) of each cell, for a bar `cells` long at `percent`,
+    /// rounded to the nearest pixel column
+    pub fn cell_fill(cells: u8, percent: u8, cell: u8) -> u8 {
+        cell_fill(cells, percent, cell)
+    }
+
+    /// Show the progress bar
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - the position of the top left cell of the bar, the bar will use row `start.1` and `start.1 + 1`
+    /// * `cells` - the width (in cells) of the bar
+    /// * `percent` - the progress, larger than 100 will be treated as 100
+    pub fn render(&mut self, lcd: &mut impl Basic, start: impl Into<Pos>, cells: u8, percent: u8) {
+        let start: (u8, u8) = start.into().into();
+        assert!(
+            start.0 as u16 + cells as u16 <= lcd.get_line_capacity() as u16,
+            "bar is out of line"
+        );
+
+        // partial filled cell is the only one with filled columns between 1 to 4
+        let partial_columns = (0..cells)
+            .map(|cell| cell_fill(cells, percent, cell))
+            .find(|&columns| 0 < columns && columns < 5);
+
+        if let Some(partial_columns) = partial_columns {
+            if self.last_partial_columns != Some(partial_columns) {
+                lcd.write_graph_to_cgram(self.index, &column_fill_graph(partial_columns));
+                self.last_partial_columns = Some(partial_columns);
+            }
+        }
+
+        // DDRAM address of 2 rows are not continuous, set cursor for each row
+        (start.1..start.1 + 2).for_each(|row| {
+            lcd.set_cursor_pos((start.0, row));
+
+            (0..cells).for_each(|cell| match cell_fill(cells, percent, cell) {
+                0 => lcd.write_u8_to_cur(b' '),
+                5 => lcd.write_u8_to_cur(FULL_BLOCK),
+                _ => lcd.write_graph_to_cur(self.index),
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lcd::{Config, Lcd},
+        sender::{MockDelay, MockSender},
+    };
+
+    use super::TallProgressBar;
+
+    #[test]
+    fn cell_fill_at_half() {
+        // 25 pixel columns, 12.5 of them rounded to 13
+        let fill: [u8; 5] =
+            core::array::from_fn(|cell| TallProgressBar::cell_fill(5, 50, cell as u8));
+        assert_eq!(fill, [5, 5, 3, 0, 0]);
+    }
+
+    #[test]
+    fn render_fills_both_rows() {
+        let mut sender = MockSender::new();
+        let mut delayer = MockDelay::new();
+
+        let mut lcd = Lcd::new(&mut sender, &mut delayer, Config::default(), 0);
+        TallProgressBar::new(3).render(&mut lcd, (1, 0), 5, 50);
+        let (sender, _) = lcd.release();
+
+        let expected = [0xFF, 0xFF, 3, b' ', b' '];
+        assert_eq!(sender.get_ddram()[0x01..0x06], expected);
+        assert_eq!(sender.get_ddram()[0x41..0x46], expected);
+        assert_eq!(sender.get_cgram()[3 * 8..4 * 8], [0b11100; 8]);
+    }
+}